@@ -0,0 +1,282 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+/// Two-qubit state that is diagonal in the Bell basis.
+///
+/// The coefficients are the probabilities of the Bell states obtained by
+/// applying a Pauli error to one qubit of |Φ+⟩, in this order:
+/// - |Φ+⟩ (no error)
+/// - |Ψ+⟩ (X error)
+/// - |Φ-⟩ (Z error)
+/// - |Ψ-⟩ (Y error)
+///
+/// With this ordering, the index of the error obtained by composing two
+/// errors is the bitwise XOR of their indices.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BellDiagonal {
+    coefficients: [f64; 4],
+}
+
+/// Index of |Φ+⟩.
+const PHI_PLUS: usize = 0;
+/// Index of |Ψ+⟩.
+const PSI_PLUS: usize = 1;
+/// Index of |Φ-⟩.
+const PHI_MINUS: usize = 2;
+/// Index of |Ψ-⟩.
+const PSI_MINUS: usize = 3;
+
+impl BellDiagonal {
+    /// Create a state from the coefficients of |Φ+⟩, |Ψ+⟩, |Φ-⟩, |Ψ-⟩.
+    pub fn new(coefficients: [f64; 4]) -> Self {
+        Self { coefficients }
+    }
+
+    /// Create a Werner state with given fidelity.
+    pub fn werner(fidelity: f64) -> Self {
+        let error = (1.0 - fidelity) / 3.0;
+        Self::new([fidelity, error, error, error])
+    }
+
+    /// Return the coefficients of |Φ+⟩, |Ψ+⟩, |Φ-⟩, |Ψ-⟩.
+    pub fn coefficients(&self) -> [f64; 4] {
+        self.coefficients
+    }
+
+    /// Return the fidelity with respect to |Φ+⟩.
+    pub fn fidelity(&self) -> f64 {
+        self.coefficients[PHI_PLUS]
+    }
+
+    /// Return the Werner state with the same fidelity, i.e., apply twirling.
+    pub fn twirl(&self) -> Self {
+        Self::werner(self.fidelity())
+    }
+
+    /// Apply depolarizing noise to one qubit for a given time, in s.
+    pub fn depolarize(&self, rate: f64, time: f64) -> Self {
+        let p = (-rate * time).exp();
+        Self::new(self.coefficients.map(|c| p * c + (1.0 - p) / 4.0))
+    }
+
+    /// Apply dephasing noise to one qubit for a given time, in s.
+    ///
+    /// A Z error occurs with probability (1 - exp(-rate * time)) / 2.
+    pub fn dephase(&self, rate: f64, time: f64) -> Self {
+        let q = (1.0 - (-rate * time).exp()) / 2.0;
+        let c = &self.coefficients;
+        Self::new(std::array::from_fn(|i| {
+            (1.0 - q) * c[i] + q * c[i ^ PHI_MINUS]
+        }))
+    }
+
+    /// Apply both depolarizing and dephasing noise to one qubit for a given
+    /// time, in s.
+    pub fn decay(&self, depolarizing_rate: f64, dephasing_rate: f64, time: f64) -> Self {
+        self.depolarize(depolarizing_rate, time)
+            .dephase(dephasing_rate, time)
+    }
+
+    /// Return the state obtained by entanglement swapping with another state.
+    ///
+    /// The Pauli errors of the two input states compose.
+    pub fn swap(&self, other: &Self) -> Self {
+        let mut coefficients = [0.0; 4];
+        for (i, a) in self.coefficients.iter().enumerate() {
+            for (j, b) in other.coefficients.iter().enumerate() {
+                coefficients[i ^ j] += a * b;
+            }
+        }
+        Self::new(coefficients)
+    }
+
+    /// Return the success probability and output state of one DEJMPS round
+    /// with another state.
+    pub fn dejmps(&self, other: &Self) -> (f64, Self) {
+        let (a1, b1, c1, d1) = self.dejmps_coefficients();
+        let (a2, b2, c2, d2) = other.dejmps_coefficients();
+        let success_prob = (a1 + b1) * (a2 + b2) + (c1 + d1) * (c2 + d2);
+        let mut coefficients = [0.0; 4];
+        coefficients[PHI_PLUS] = (a1 * a2 + b1 * b2) / success_prob;
+        coefficients[PSI_MINUS] = (c1 * d2 + d1 * c2) / success_prob;
+        coefficients[PSI_PLUS] = (c1 * c2 + d1 * d2) / success_prob;
+        coefficients[PHI_MINUS] = (a1 * b2 + b1 * a2) / success_prob;
+        (success_prob, Self::new(coefficients))
+    }
+
+    /// Return the success probability and output state of one BBPSSW round
+    /// with another state, which twirls both the inputs and the output.
+    pub fn bbpssw(&self, other: &Self) -> (f64, Self) {
+        let (success_prob, state) = self.twirl().dejmps(&other.twirl());
+        (success_prob, state.twirl())
+    }
+
+    /// Return the time, in s, after which the fidelity decays to a given
+    /// threshold with the given noise rates applied to one qubit.
+    ///
+    /// Return zero if the fidelity is not above the threshold, or None if
+    /// the fidelity never reaches the threshold.
+    pub fn fidelity_time(
+        &self,
+        depolarizing_rate: f64,
+        dephasing_rate: f64,
+        threshold: f64,
+    ) -> Option<f64> {
+        let fidelity = |time| {
+            self.decay(depolarizing_rate, dephasing_rate, time)
+                .fidelity()
+        };
+        if self.fidelity() <= threshold {
+            return Some(0.0);
+        }
+
+        let asymptote = if depolarizing_rate > 0.0 {
+            0.25
+        } else if dephasing_rate > 0.0 {
+            (self.coefficients[PHI_PLUS] + self.coefficients[PHI_MINUS]) / 2.0
+        } else {
+            self.fidelity()
+        };
+        if asymptote >= threshold {
+            return None;
+        }
+
+        // Bisection, with a precision of 1 ns or a relative precision of
+        // 1e-9 for times longer than 1 s.
+        let mut lo = 0.0;
+        let mut hi = 1.0;
+        while fidelity(hi) > threshold {
+            lo = hi;
+            hi *= 2.0;
+            if hi.is_infinite() {
+                return None;
+            }
+        }
+        while hi - lo > 1e-9 * hi.max(1.0) {
+            let mid = (lo + hi) / 2.0;
+            if fidelity(mid) > threshold {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        Some(hi)
+    }
+
+    /// Return the coefficients in the order used by DEJMPS, i.e.,
+    /// |Φ+⟩, |Ψ-⟩, |Ψ+⟩, |Φ-⟩.
+    fn dejmps_coefficients(&self) -> (f64, f64, f64, f64) {
+        let c = &self.coefficients;
+        (c[PHI_PLUS], c[PSI_MINUS], c[PSI_PLUS], c[PHI_MINUS])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BellDiagonal;
+
+    fn assert_state_near(expected: [f64; 4], state: BellDiagonal) {
+        for (e, c) in expected.iter().zip(state.coefficients().iter()) {
+            assert_float_eq::assert_f64_near!(*e, *c, 8);
+        }
+    }
+
+    #[test]
+    fn test_bell_diagonal_noise() {
+        // Depolarizing noise matches the scalar fidelity model.
+        let state = BellDiagonal::werner(0.9).depolarize(0.5, 2.0);
+        assert_state_near(BellDiagonal::werner(state.fidelity()).coefficients(), state);
+        assert_float_eq::assert_f64_near!(crate::utils::fidelity(0.9, 0.5, 2.0), state.fidelity());
+
+        // Dephasing noise mixes |Φ+⟩ with |Φ-⟩ and |Ψ+⟩ with |Ψ-⟩.
+        let state = BellDiagonal::new([1.0, 0.0, 0.0, 0.0]).dephase(1.0, 1e6);
+        assert_state_near([0.5, 0.0, 0.5, 0.0], state);
+        let q = (1.0 - (-1.0_f64).exp()) / 2.0;
+        let state = BellDiagonal::new([0.7, 0.2, 0.1, 0.0]).dephase(1.0, 1.0);
+        assert_state_near(
+            [
+                0.7 * (1.0 - q) + 0.1 * q,
+                0.2 * (1.0 - q),
+                0.1 * (1.0 - q) + 0.7 * q,
+                0.2 * q,
+            ],
+            state,
+        );
+
+        // No noise.
+        let state = BellDiagonal::new([0.7, 0.2, 0.1, 0.0]);
+        assert_eq!(state, state.decay(0.0, 0.0, 10.0));
+    }
+
+    #[test]
+    fn test_bell_diagonal_swap() {
+        // Werner states: same as the scalar formula.
+        let state = BellDiagonal::werner(0.9).swap(&BellDiagonal::werner(0.8));
+        assert_float_eq::assert_f64_near!(
+            crate::tests::swapping_fidelity(0.9, 0.8),
+            state.fidelity()
+        );
+
+        // Errors compose.
+        let x = BellDiagonal::new([0.0, 1.0, 0.0, 0.0]);
+        let z = BellDiagonal::new([0.0, 0.0, 1.0, 0.0]);
+        assert_state_near([0.0, 0.0, 0.0, 1.0], x.swap(&z));
+        assert_state_near([1.0, 0.0, 0.0, 0.0], z.swap(&z));
+    }
+
+    #[test]
+    fn test_bell_diagonal_purification() {
+        // BBPSSW and DEJMPS are the same on Werner states.
+        let werner = BellDiagonal::werner(0.8);
+        let (p_bbpssw, state_bbpssw) = werner.bbpssw(&werner);
+        let (p_dejmps, state_dejmps) = werner.dejmps(&werner);
+        assert_float_eq::assert_f64_near!(p_bbpssw, p_dejmps);
+        assert_float_eq::assert_f64_near!(state_bbpssw.fidelity(), state_dejmps.fidelity());
+        assert_float_eq::assert_f64_near!(
+            0.8 * 0.8 + 0.2 * 0.2 / 9.0,
+            p_dejmps * state_dejmps.fidelity()
+        );
+
+        // DEJMPS is better than BBPSSW with non-Werner states, e.g., with
+        // only dephasing errors.
+        let state = BellDiagonal::new([0.8, 0.0, 0.2, 0.0]);
+        let (p, purified) = state.dejmps(&state);
+        assert_float_eq::assert_f64_near!(0.68, p);
+        assert_float_eq::assert_f64_near!(0.64 / 0.68, purified.fidelity());
+        assert!(purified.fidelity() > state.bbpssw(&state).1.fidelity());
+        let sum: f64 = purified.coefficients().iter().sum();
+        assert_float_eq::assert_f64_near!(1.0, sum);
+    }
+
+    #[test]
+    fn test_bell_diagonal_fidelity_time() {
+        let state = BellDiagonal::werner(0.95);
+        assert_eq!(Some(0.0), state.fidelity_time(1.0, 1.0, 0.95));
+        assert!(state.fidelity_time(0.0, 0.0, 0.9).is_none());
+        assert!(state.fidelity_time(0.0, 1.0, 0.4).is_none());
+
+        for (depolarizing_rate, dephasing_rate) in [(0.5, 0.0), (0.0, 0.5), (0.3, 0.2)] {
+            let time = state
+                .fidelity_time(depolarizing_rate, dephasing_rate, 0.6)
+                .unwrap();
+            assert!(time > 0.0);
+            assert_float_eq::assert_f64_near!(
+                0.6,
+                state
+                    .decay(depolarizing_rate, dephasing_rate, time)
+                    .fidelity(),
+                1_000_000
+            );
+        }
+        assert_float_eq::assert_f64_near!(
+            ((0.95 - 0.25) / (0.6 - 0.25_f64)).ln() / 0.5,
+            state.fidelity_time(0.5, 0.0, 0.6).unwrap(),
+            1_000_000
+        );
+
+        // Very slow decay: the time scales with the inverse of the rate.
+        let time = state.fidelity_time(0.0, 1e-12, 0.6).unwrap();
+        let expected = state.fidelity_time(0.0, 1.0, 0.6).unwrap() * 1e12;
+        assert!((time - expected).abs() <= 1e-8 * expected);
+    }
+}
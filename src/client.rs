@@ -1,11 +1,48 @@
 // SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
 // SPDX-License-Identifier: MIT
 
+use rand::SeedableRng;
 use rand_distr::Distribution;
 
 use crate::event::*;
 use crate::output::Sample;
 
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ClientParams {
+    /// Rate at which each client requests new EPR pairs, in Hz.
+    pub epr_request_rate: f64,
+    /// Rate of the local operations done on an EPR pair, in Hz.
+    pub local_ops_rate: f64,
+}
+
+impl Default for ClientParams {
+    fn default() -> Self {
+        Self {
+            epr_request_rate: 1.0,
+            local_ops_rate: 10.0,
+        }
+    }
+}
+
+impl ClientParams {
+    pub fn valid(&self) -> anyhow::Result<()> {
+        let mut errors = vec![];
+        if self.epr_request_rate <= 0.0 {
+            errors.push(format!("EPR request rate ({}) <= 0", self.epr_request_rate))
+        }
+        if self.local_ops_rate <= 0.0 {
+            errors.push(format!(
+                "local operations rate ({}) <= 0",
+                self.local_ops_rate
+            ))
+        }
+        if !errors.is_empty() {
+            anyhow::bail!("invalid client parameters: {}", errors.join(","))
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 struct EprRequest {
     /// Neighbor node ID, used to identify the NIC, and memory cell index.
@@ -44,6 +81,30 @@ pub struct Client {
 }
 
 impl Client {
+    /// Create a client that requests EPR pairs to a given peer application.
+    pub fn new(
+        this_node_id: u32,
+        this_port: u16,
+        peer_node_id: u32,
+        peer_port: u16,
+        params: &ClientParams,
+        seed: u64,
+    ) -> Self {
+        Self {
+            this_node_id,
+            this_port,
+            peer_node_id,
+            peer_port,
+            next_request_id: 0,
+            rv_next_epr: rand_distr::Exp::new(params.epr_request_rate)
+                .expect("could not create an expo rv"),
+            rv_local_ops: rand_distr::Exp::new(params.local_ops_rate)
+                .expect("could not create an expo rv"),
+            rng: rand::rngs::StdRng::seed_from_u64(seed),
+            pending: std::collections::HashMap::new(),
+        }
+    }
+
     fn get_request(&mut self, epr: &EprFiveTuple) -> &mut EprRequest {
         assert_eq!(epr.source_node_id, self.this_node_id);
         assert_eq!(epr.source_port, self.this_port);
@@ -135,73 +196,68 @@ impl Client {
     }
 
     fn handle_local_complete(&mut self, now: u64, epr: EprFiveTuple) -> (Vec<Event>, Vec<Sample>) {
-        let mut events = vec![];
-        let mut samples = vec![];
-
         let request = self.get_request(&epr);
 
         assert!(
-            request.local_operations_done,
+            !request.local_operations_done,
             "duplicate execution of local operations for request {}",
             epr
         );
         request.local_operations_done = true;
 
         if request.remote_operations_done {
-            let memory_cell = std::mem::take(&mut request.memory_cell);
-            let (neighbor_node_id, role, index) = memory_cell
-                .unwrap_or_else(|| panic!("local operation completed on a failed request {}", epr));
-            events.push(Event::new(
-                0.0,
-                EventType::NodeEvent(NodeEventData::EprFidelity(EprFidelityData {
-                    app_node_id: self.this_node_id,
-                    port: self.this_port,
-                    consume_node_id: self.this_node_id,
-                    neighbor_node_id,
-                    role,
-                    index,
-                })),
-            ));
-
-            let epr_request = self.pending.remove(&epr.request_id);
-
-            if let Some(epr_request) = epr_request {
-                samples.push(Sample::Series(
-                    "latency-node,latency-port".to_string(),
-                    format!("{},{}", self.this_node_id, self.this_port),
-                    crate::utils::to_seconds(now - epr_request.created),
-                ));
-            }
+            self.complete(now, epr)
+        } else {
+            (vec![], vec![])
         }
-
-        (events, samples)
     }
 
     fn handle_remote_complete(&mut self, now: u64, epr: EprFiveTuple) -> (Vec<Event>, Vec<Sample>) {
-        let mut samples = vec![];
-
         let request = self.get_request(&epr);
 
         assert!(
-            request.remote_operations_done,
+            !request.remote_operations_done,
             "duplicate execution of remote operations for request {}",
             epr
         );
         request.remote_operations_done = true;
 
         if request.local_operations_done {
-            let epr_request = self.pending.remove(&epr.request_id);
-
-            if let Some(epr_request) = epr_request {
-                samples.push(Sample::Series(
-                    "latency-node,latency-port".to_string(),
-                    format!("{},{}", self.this_node_id, self.this_port),
-                    crate::utils::to_seconds(now - epr_request.created),
-                ));
-            }
+            self.complete(now, epr)
+        } else {
+            (vec![], vec![])
         }
+    }
 
-        (vec![], samples)
+    /// Both local and remote operations are complete: consume the EPR pair
+    /// and remove the request.
+    fn complete(&mut self, now: u64, epr: EprFiveTuple) -> (Vec<Event>, Vec<Sample>) {
+        let epr_request = self
+            .pending
+            .remove(&epr.request_id)
+            .unwrap_or_else(|| panic!("non-existing pending request {}", epr));
+        let (neighbor_node_id, role, index) = epr_request
+            .memory_cell
+            .unwrap_or_else(|| panic!("operations completed on a failed request {}", epr));
+
+        (
+            vec![Event::new(
+                0.0,
+                EventType::NodeEvent(NodeEventData::EprFidelity(EprFidelityData {
+                    app_node_id: self.this_node_id,
+                    port: self.this_port,
+                    consume_node_id: self.this_node_id,
+                    neighbor_node_id,
+                    role,
+                    index,
+                })),
+            )],
+            vec![Sample::Series(
+                "latency-node,latency-port".to_string(),
+                format!("{},{}", self.this_node_id, self.this_port),
+                crate::utils::to_seconds(now - epr_request.created),
+            )],
+        )
     }
 }
 
@@ -234,9 +290,94 @@ impl EventHandler for Client {
 
 #[cfg(test)]
 mod tests {
+    use super::{Client, ClientParams};
+    use crate::event::*;
+
+    fn five_tuple(request_id: u64) -> EprFiveTuple {
+        EprFiveTuple {
+            source_node_id: 1,
+            source_port: 0,
+            target_node_id: 2,
+            target_port: 0,
+            request_id,
+        }
+    }
 
     #[test]
     fn test_client() {
-        // XXX
+        let mut client = Client::new(1, 0, 2, 0, &ClientParams::default(), 42);
+
+        let events = client.initial();
+        assert_eq!(1, events.len());
+        assert!(matches!(
+            events[0].event_type,
+            EventType::AppEvent(AppEventData::EprRequest(1, 0))
+        ));
+
+        // Issue two requests.
+        for request_id in 0..2 {
+            let (events, samples) = client.handle(Event::new(
+                0.0,
+                EventType::AppEvent(AppEventData::EprRequest(1, 0)),
+            ));
+            assert_eq!(2, events.len());
+            assert_eq!(
+                EventType::OsEvent(OsEventData::EprRequestApp(five_tuple(request_id))),
+                events[0].event_type
+            );
+            assert_eq!(1, samples.len());
+        }
+        assert_eq!(2, client.pending.len());
+
+        // The first request fails.
+        let (events, _) = client.handle(Event::new(
+            0.0,
+            EventType::AppEvent(AppEventData::EprResponse(EprResponseData {
+                epr: five_tuple(0),
+                memory_cell: None,
+            })),
+        ));
+        assert!(events.is_empty());
+        assert_eq!(1, client.pending.len());
+
+        // The second one succeeds.
+        let (events, _) = client.handle(Event::new(
+            0.0,
+            EventType::AppEvent(AppEventData::EprResponse(EprResponseData {
+                epr: five_tuple(1),
+                memory_cell: Some((3, crate::nic::Role::Master, 7)),
+            })),
+        ));
+        assert_eq!(1, events.len());
+        assert_eq!(
+            EventType::AppEvent(AppEventData::LocalComplete(five_tuple(1))),
+            events[0].event_type
+        );
+
+        let (events, samples) = client.handle(Event::new(
+            0.0,
+            EventType::AppEvent(AppEventData::RemoteComplete(five_tuple(1))),
+        ));
+        assert!(events.is_empty());
+        assert!(samples.is_empty());
+
+        let (events, samples) = client.handle(Event::new(
+            0.0,
+            EventType::AppEvent(AppEventData::LocalComplete(five_tuple(1))),
+        ));
+        assert_eq!(1, events.len());
+        assert_eq!(
+            EventType::NodeEvent(NodeEventData::EprFidelity(EprFidelityData {
+                app_node_id: 1,
+                port: 0,
+                consume_node_id: 1,
+                neighbor_node_id: 3,
+                role: crate::nic::Role::Master,
+                index: 7
+            })),
+            events[0].event_type
+        );
+        assert_eq!(1, samples.len());
+        assert!(client.pending.is_empty());
     }
 }
@@ -0,0 +1,660 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+/// Standard gravitational parameter of the Earth, in m^3/s^2.
+pub const EARTH_MU: f64 = 3.986004418e14;
+/// Mean radius of the Earth, in m.
+pub const EARTH_RADIUS: f64 = 6_371_000.0;
+/// Rotation rate of the Earth, in rad/s.
+pub const EARTH_ROTATION_RATE: f64 = 7.2921159e-5;
+/// Minimum altitude of the line of sight between two satellites, in m,
+/// below which the atmosphere blocks inter-satellite links.
+pub const MIN_LINE_OF_SIGHT_ALTITUDE: f64 = 100_000.0;
+
+/// Position in the Earth-centered inertial frame, in m.
+pub type Position = [f64; 3];
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WalkerParams {
+    /// Altitude of the circular orbits, in m.
+    pub altitude: f64,
+    /// Inclination of the orbital planes, in degrees.
+    pub inclination: f64,
+    /// Number of orbital planes.
+    pub num_planes: u32,
+    /// Number of satellites in each orbital plane.
+    pub sats_per_plane: u32,
+    /// Phasing factor between adjacent planes, from 0 to `num_planes` - 1.
+    pub phasing: u32,
+    /// Minimum elevation of a satellite to be visible from an OGS, in degrees.
+    pub elevation_mask: f64,
+    /// Time elapsed since the epoch when the topology is created, in s.
+    pub time: f64,
+}
+
+impl Default for WalkerParams {
+    fn default() -> Self {
+        Self {
+            altitude: 550e3,
+            inclination: 53.0,
+            num_planes: 12,
+            sats_per_plane: 20,
+            phasing: 1,
+            elevation_mask: 20.0,
+            time: 0.0,
+        }
+    }
+}
+
+impl WalkerParams {
+    pub fn valid(&self) -> anyhow::Result<()> {
+        let mut errors = vec![];
+        if self.altitude <= 0.0 {
+            errors.push(format!("altitude ({}) <= 0", self.altitude));
+        }
+        if !(0.0..=180.0).contains(&self.inclination) {
+            errors.push(format!("invalid inclination ({})", self.inclination));
+        }
+        if self.num_planes == 0 {
+            errors.push(String::from("vanishing number of planes"));
+        }
+        if self.sats_per_plane == 0 {
+            errors.push(String::from("vanishing number of satellites per plane"));
+        }
+        if self.num_planes > 0 && self.phasing >= self.num_planes {
+            errors.push(format!(
+                "phasing ({}) >= number of planes ({})",
+                self.phasing, self.num_planes
+            ));
+        }
+        if !(0.0..90.0).contains(&self.elevation_mask) {
+            errors.push(format!("invalid elevation mask ({})", self.elevation_mask));
+        }
+        if self.time < 0.0 {
+            errors.push(format!("time ({}) < 0", self.time));
+        }
+        if !errors.is_empty() {
+            anyhow::bail!("invalid Walker-delta parameters: {}", errors.join(","))
+        }
+        Ok(())
+    }
+}
+
+impl WalkerParams {
+    /// Return the orbital period, in s.
+    pub fn period(&self) -> f64 {
+        2.0 * std::f64::consts::PI / self.mean_motion()
+    }
+
+    /// Return the semi-major axis of the orbits, in m.
+    fn semi_major_axis(&self) -> f64 {
+        EARTH_RADIUS + self.altitude
+    }
+
+    /// Return the mean motion of the satellites, in rad/s.
+    fn mean_motion(&self) -> f64 {
+        (EARTH_MU / self.semi_major_axis().powi(3)).sqrt()
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TleParams {
+    /// Path of the local file containing the two-line element sets.
+    pub tle_file: String,
+    /// UTC date and time of the beginning of the simulation, in the format
+    /// YYYY-MM-DDTHH:MM:SS.
+    pub start: String,
+    /// Minimum elevation of a satellite to be visible from an OGS, in degrees.
+    pub elevation_mask: f64,
+    /// Maximum length of an inter-satellite link, in m.
+    pub max_isl_distance: f64,
+    /// Number of nearest neighbors with which every satellite establishes
+    /// inter-satellite links.
+    pub max_isls: u32,
+    /// Time elapsed since the beginning when the topology is created, in s.
+    pub time: f64,
+}
+
+impl Default for TleParams {
+    fn default() -> Self {
+        Self {
+            tle_file: String::from("tle.txt"),
+            start: String::from("2025-01-01T00:00:00"),
+            elevation_mask: 20.0,
+            max_isl_distance: 5000e3,
+            max_isls: 4,
+            time: 0.0,
+        }
+    }
+}
+
+impl TleParams {
+    pub fn valid(&self) -> anyhow::Result<()> {
+        let mut errors = vec![];
+        if let Err(err) = crate::tle::parse_utc(&self.start) {
+            errors.push(err.to_string());
+        }
+        if !(0.0..90.0).contains(&self.elevation_mask) {
+            errors.push(format!("invalid elevation mask ({})", self.elevation_mask));
+        }
+        if self.max_isl_distance < 0.0 {
+            errors.push(format!(
+                "maximum inter-satellite link distance ({}) < 0",
+                self.max_isl_distance
+            ));
+        }
+        if self.time < 0.0 {
+            errors.push(format!("time ({}) < 0", self.time));
+        }
+        if !errors.is_empty() {
+            anyhow::bail!("invalid TLE constellation parameters: {}", errors.join(","))
+        }
+        Ok(())
+    }
+}
+
+/// Location of an OGS on the Earth's surface.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct GroundStation {
+    /// Latitude, in degrees.
+    pub latitude: f64,
+    /// Longitude, in degrees.
+    pub longitude: f64,
+}
+
+impl GroundStation {
+    fn valid(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            (-90.0..=90.0).contains(&self.latitude) && (-180.0..=180.0).contains(&self.longitude),
+            "invalid ground station location ({}, {})",
+            self.latitude,
+            self.longitude
+        );
+        Ok(())
+    }
+
+    /// Return the position when the Earth is rotated by the given angle, in
+    /// rad, i.e., the angle between the prime meridian and the X axis.
+    pub fn position(&self, rotation: f64) -> Position {
+        let latitude = self.latitude.to_radians();
+        let longitude = self.longitude.to_radians() + rotation;
+        [
+            EARTH_RADIUS * latitude.cos() * longitude.cos(),
+            EARTH_RADIUS * latitude.cos() * longitude.sin(),
+            EARTH_RADIUS * latitude.sin(),
+        ]
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Orbits {
+    Walker(WalkerParams),
+    Tle {
+        params: TleParams,
+        /// Julian date of the beginning of the simulation.
+        start: f64,
+        satellites: Vec<crate::tle::Sgp4>,
+    },
+}
+
+/// Constellation of satellites, with the OGSs on a spherical Earth.
+///
+/// The orbits are either those of a synthetic Walker-delta constellation,
+/// with circular orbits, or propagated with SGP4 from two-line element sets.
+///
+/// In a Walker-delta constellation the satellites are identified by
+/// `plane * sats_per_plane + slot`, and every satellite has inter-satellite
+/// links with the previous and next ones in the same plane and with those
+/// in the same slot of the adjacent planes. With TLEs the satellites are
+/// identified by their order in the file, and every satellite has
+/// inter-satellite links with its nearest neighbors in range.
+/// In both cases, links blocked by the Earth are removed.
+///
+/// The OGSs follow the satellites, in the order given, and have a link with
+/// every satellite above the elevation mask.
+#[derive(Debug, Clone)]
+pub struct Constellation {
+    orbits: Orbits,
+    ground_stations: Vec<GroundStation>,
+}
+
+impl Constellation {
+    /// Create a Walker-delta constellation.
+    pub fn new(params: WalkerParams, ground_stations: Vec<GroundStation>) -> anyhow::Result<Self> {
+        params.valid()?;
+        for ground_station in &ground_stations {
+            ground_station.valid()?;
+        }
+        Ok(Self {
+            orbits: Orbits::Walker(params),
+            ground_stations,
+        })
+    }
+
+    /// Create a constellation from the given two-line element sets.
+    pub fn from_tle(
+        params: TleParams,
+        tles: &[crate::tle::Tle],
+        ground_stations: Vec<GroundStation>,
+    ) -> anyhow::Result<Self> {
+        params.valid()?;
+        for ground_station in &ground_stations {
+            ground_station.valid()?;
+        }
+        let start = crate::tle::parse_utc(&params.start)?;
+        let satellites = tles
+            .iter()
+            .map(crate::tle::Sgp4::new)
+            .collect::<anyhow::Result<Vec<crate::tle::Sgp4>>>()?;
+        Ok(Self {
+            orbits: Orbits::Tle {
+                params,
+                start,
+                satellites,
+            },
+            ground_stations,
+        })
+    }
+
+    /// Return the time at which the topology is created, in s.
+    pub fn time(&self) -> f64 {
+        match &self.orbits {
+            Orbits::Walker(params) => params.time,
+            Orbits::Tle { params, .. } => params.time,
+        }
+    }
+
+    /// Return the minimum elevation of a satellite visible from an OGS,
+    /// in degrees.
+    pub fn elevation_mask(&self) -> f64 {
+        match &self.orbits {
+            Orbits::Walker(params) => params.elevation_mask,
+            Orbits::Tle { params, .. } => params.elevation_mask,
+        }
+    }
+
+    /// Return the number of satellites.
+    pub fn num_satellites(&self) -> u32 {
+        match &self.orbits {
+            Orbits::Walker(params) => params.num_planes * params.sats_per_plane,
+            Orbits::Tle { satellites, .. } => satellites.len() as u32,
+        }
+    }
+
+    /// Return the number of OGSs.
+    pub fn num_ground_stations(&self) -> u32 {
+        self.ground_stations.len() as u32
+    }
+
+    /// Return the position of a satellite at a given time, in s.
+    /// Return error if the TLE propagation fails, e.g., if the satellite
+    /// has decayed.
+    pub fn satellite_position(&self, sat: u32, time: f64) -> anyhow::Result<Position> {
+        anyhow::ensure!(sat < self.num_satellites(), "invalid satellite {}", sat);
+        match &self.orbits {
+            Orbits::Walker(params) => {
+                let num_planes = params.num_planes as f64;
+                let sats_per_plane = params.sats_per_plane as f64;
+                let plane = (sat / params.sats_per_plane) as f64;
+                let slot = (sat % params.sats_per_plane) as f64;
+
+                let two_pi = 2.0 * std::f64::consts::PI;
+                let raan = two_pi * plane / num_planes;
+                let argument_of_latitude = two_pi * slot / sats_per_plane
+                    + two_pi * params.phasing as f64 * plane / (num_planes * sats_per_plane)
+                    + params.mean_motion() * time;
+                let inclination = params.inclination.to_radians();
+                let radius = params.semi_major_axis();
+
+                let (sin_raan, cos_raan) = raan.sin_cos();
+                let (sin_u, cos_u) = argument_of_latitude.sin_cos();
+                let (sin_i, cos_i) = inclination.sin_cos();
+                Ok([
+                    radius * (cos_raan * cos_u - sin_raan * sin_u * cos_i),
+                    radius * (sin_raan * cos_u + cos_raan * sin_u * cos_i),
+                    radius * sin_u * sin_i,
+                ])
+            }
+            Orbits::Tle {
+                start, satellites, ..
+            } => {
+                let satellite = &satellites[sat as usize];
+                let tsince = (start - satellite.epoch()) * 1440.0 + time / 60.0;
+                let (position, _velocity) = satellite.propagate(tsince)?;
+                Ok(position.map(|x| x * 1e3))
+            }
+        }
+    }
+
+    /// Return the position of an OGS at a given time, in s.
+    pub fn ground_station_position(&self, ogs: u32, time: f64) -> Position {
+        let rotation = match &self.orbits {
+            Orbits::Walker(_) => EARTH_ROTATION_RATE * time,
+            Orbits::Tle { start, .. } => crate::tle::gmst(start + time / 86400.0),
+        };
+        self.ground_stations[ogs as usize].position(rotation)
+    }
+
+    /// Return the elevation of a satellite seen from an OGS, in degrees.
+    pub fn elevation(&self, ogs: u32, sat: u32, time: f64) -> anyhow::Result<f64> {
+        Ok(elevation(
+            &self.ground_station_position(ogs, time),
+            &self.satellite_position(sat, time)?,
+        ))
+    }
+
+    /// Return the links at a given time, in s, as (u, v, distance in m),
+    /// where the OGS identifiers follow those of the satellites.
+    ///
+    /// The satellites whose position cannot be determined have no links.
+    pub fn links(&self, time: f64) -> Vec<(u32, u32, f64)> {
+        let num_sats = self.num_satellites();
+        let positions = (0..num_sats)
+            .map(|sat| match self.satellite_position(sat, time) {
+                Ok(position) => Some(position),
+                Err(err) => {
+                    log::debug!("satellite {} unavailable: {}", sat, err);
+                    None
+                }
+            })
+            .collect::<Vec<Option<Position>>>();
+        let distance = |u: u32, v: u32| match (positions[u as usize], positions[v as usize]) {
+            (Some(a), Some(b)) if line_of_sight(&a, &b) => Some(norm(&sub(&a, &b))),
+            _ => None,
+        };
+
+        // Inter-satellite links.
+        let mut pairs = std::collections::BTreeSet::new();
+        match &self.orbits {
+            Orbits::Walker(params) => {
+                let num_planes = params.num_planes;
+                let sats_per_plane = params.sats_per_plane;
+                for plane in 0..num_planes {
+                    for slot in 0..sats_per_plane {
+                        let sat = plane * sats_per_plane + slot;
+                        let next_slot = plane * sats_per_plane + (slot + 1) % sats_per_plane;
+                        let next_plane = ((plane + 1) % num_planes) * sats_per_plane + slot;
+                        for other in [next_slot, next_plane] {
+                            if other != sat {
+                                pairs.insert((sat.min(other), sat.max(other)));
+                            }
+                        }
+                    }
+                }
+            }
+            Orbits::Tle { params, .. } => {
+                for sat in 0..num_sats {
+                    let mut neighbors = (0..num_sats)
+                        .filter(|other| *other != sat)
+                        .filter_map(|other| distance(sat, other).map(|d| (d, other)))
+                        .filter(|(d, _)| *d <= params.max_isl_distance)
+                        .collect::<Vec<(f64, u32)>>();
+                    neighbors.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                    for (_, other) in neighbors.into_iter().take(params.max_isls as usize) {
+                        pairs.insert((sat.min(other), sat.max(other)));
+                    }
+                }
+            }
+        }
+        let mut links = pairs
+            .into_iter()
+            .filter_map(|(u, v)| distance(u, v).map(|d| (u, v, d)))
+            .collect::<Vec<(u32, u32, f64)>>();
+
+        // Ground-to-orbit links.
+        let elevation_mask = self.elevation_mask();
+        for ogs in 0..self.num_ground_stations() {
+            let ogs_position = self.ground_station_position(ogs, time);
+            for (sat, sat_position) in positions.iter().enumerate() {
+                if let Some(sat_position) = sat_position {
+                    if elevation(&ogs_position, sat_position) >= elevation_mask {
+                        links.push((
+                            sat as u32,
+                            num_sats + ogs,
+                            norm(&sub(sat_position, &ogs_position)),
+                        ));
+                    }
+                }
+            }
+        }
+
+        links
+    }
+}
+
+/// Return the elevation of a satellite seen from an OGS, in degrees.
+fn elevation(ogs_position: &Position, sat_position: &Position) -> f64 {
+    let line_of_sight = sub(sat_position, ogs_position);
+    (dot(&line_of_sight, ogs_position) / norm(&line_of_sight) / norm(ogs_position))
+        .asin()
+        .to_degrees()
+}
+
+fn sub(a: &Position, b: &Position) -> Position {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: &Position, b: &Position) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn norm(a: &Position) -> f64 {
+    dot(a, a).sqrt()
+}
+
+/// Return true if the segment between two satellites does not cross the
+/// atmosphere.
+fn line_of_sight(a: &Position, b: &Position) -> bool {
+    // Closest point of the segment to the Earth's center.
+    let ab = sub(b, a);
+    let t = (-dot(a, &ab) / dot(&ab, &ab)).clamp(0.0, 1.0);
+    let closest = [a[0] + t * ab[0], a[1] + t * ab[1], a[2] + t * ab[2]];
+    norm(&closest) > EARTH_RADIUS + MIN_LINE_OF_SIGHT_ALTITUDE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ground_stations() -> Vec<GroundStation> {
+        vec![
+            GroundStation {
+                latitude: 43.72,
+                longitude: 10.40,
+            },
+            GroundStation {
+                latitude: -33.87,
+                longitude: 151.21,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_constellation_params() {
+        assert!(WalkerParams::default().valid().is_ok());
+        for params in [
+            WalkerParams {
+                altitude: 0.0,
+                ..Default::default()
+            },
+            WalkerParams {
+                num_planes: 0,
+                ..Default::default()
+            },
+            WalkerParams {
+                sats_per_plane: 0,
+                ..Default::default()
+            },
+            WalkerParams {
+                phasing: 12,
+                ..Default::default()
+            },
+            WalkerParams {
+                elevation_mask: 90.0,
+                ..Default::default()
+            },
+        ] {
+            assert!(Constellation::new(params, vec![]).is_err());
+        }
+        assert!(Constellation::new(
+            WalkerParams::default(),
+            vec![GroundStation {
+                latitude: 91.0,
+                longitude: 0.0
+            }]
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_constellation_orbits() -> anyhow::Result<()> {
+        let constellation = Constellation::new(WalkerParams::default(), ground_stations())?;
+        assert_eq!(240, constellation.num_satellites());
+        assert_eq!(2, constellation.num_ground_stations());
+
+        // About 95 minutes at 550 km.
+        let period = WalkerParams::default().period();
+        assert!((95.0 * 60.0 - period).abs() < 60.0, "{}", period);
+
+        for sat in 0..constellation.num_satellites() {
+            // Circular orbits.
+            let position = constellation.satellite_position(sat, 1000.0)?;
+            assert_float_eq::assert_f64_near!(EARTH_RADIUS + 550e3, norm(&position), 1_000);
+
+            // Periodic motion.
+            let start = constellation.satellite_position(sat, 0.0)?;
+            let end = constellation.satellite_position(sat, period)?;
+            assert!(norm(&sub(&start, &end)) < 1e-3);
+
+            // The maximum latitude is the inclination.
+            let latitude = (position[2] / norm(&position)).asin().to_degrees();
+            assert!(latitude.abs() <= 53.0 + 1e-9);
+        }
+
+        // The OGSs rotate with the Earth.
+        let day = 2.0 * std::f64::consts::PI / EARTH_ROTATION_RATE;
+        let start = constellation.ground_station_position(0, 0.0);
+        assert_float_eq::assert_f64_near!(EARTH_RADIUS, norm(&start), 1_000);
+        assert!(norm(&sub(&start, &constellation.ground_station_position(0, day))) < 1e-3);
+        assert!(
+            norm(&sub(
+                &start,
+                &constellation.ground_station_position(0, day / 2.0)
+            )) > 1e6
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_constellation_links() -> anyhow::Result<()> {
+        let constellation = Constellation::new(WalkerParams::default(), ground_stations())?;
+        let num_sats = constellation.num_satellites();
+
+        let mut num_ground_links = vec![];
+        for time in [0.0, 600.0, 1200.0, 1800.0] {
+            let links = constellation.links(time);
+
+            // Every satellite has four neighbors in a large constellation.
+            let inter_satellite = links
+                .iter()
+                .filter(|(_, v, _)| *v < num_sats)
+                .collect::<Vec<&(u32, u32, f64)>>();
+            assert_eq!(2 * num_sats as usize, inter_satellite.len());
+
+            // Intra-plane links have a fixed length.
+            let intra_plane = 2.0 * (EARTH_RADIUS + 550e3) * (std::f64::consts::PI / 20.0).sin();
+            for (u, v, distance) in &inter_satellite {
+                assert!(*distance > 0.0);
+                if u / 20 == v / 20 {
+                    assert_float_eq::assert_f64_near!(intra_plane, *distance, 1_000);
+                }
+            }
+
+            // Ground links satisfy the elevation mask and are not longer than
+            // the slant range at the minimum elevation.
+            let ground = links
+                .iter()
+                .filter(|(_, v, _)| *v >= num_sats)
+                .collect::<Vec<&(u32, u32, f64)>>();
+            for (sat, ogs, distance) in &ground {
+                assert!(constellation.elevation(ogs - num_sats, *sat, time)? >= 20.0);
+                assert!(*distance >= 550e3 - 1.0);
+                assert!(*distance < 1500e3);
+            }
+            num_ground_links.push(ground.len());
+        }
+
+        // The ground links change over time.
+        assert!(num_ground_links.iter().any(|n| *n > 0));
+        assert!(num_ground_links.windows(2).any(|w| w[0] != w[1]));
+
+        // Small constellation: links blocked by the Earth are removed.
+        let constellation = Constellation::new(
+            WalkerParams {
+                num_planes: 2,
+                sats_per_plane: 3,
+                phasing: 0,
+                ..Default::default()
+            },
+            vec![],
+        )?;
+        assert!(constellation.links(0.0).is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_constellation_tle() -> anyhow::Result<()> {
+        let tles = crate::tle::Tle::parse_all(&crate::tests::tle_constellation(12, 20))?;
+        let params = TleParams {
+            start: String::from("2025-01-01T06:00:00"),
+            ..Default::default()
+        };
+        assert!(params.valid().is_ok());
+        assert!(TleParams {
+            start: String::from("yesterday"),
+            ..Default::default()
+        }
+        .valid()
+        .is_err());
+
+        let constellation = Constellation::from_tle(params, &tles, ground_stations())?;
+        assert_eq!(240, constellation.num_satellites());
+        assert_eq!(2, constellation.num_ground_stations());
+        assert!(constellation.satellite_position(240, 0.0).is_err());
+
+        for sat in 0..constellation.num_satellites() {
+            let radius = norm(&constellation.satellite_position(sat, 0.0)?);
+            assert!(radius > EARTH_RADIUS + 400e3 && radius < EARTH_RADIUS + 700e3);
+        }
+
+        let num_sats = constellation.num_satellites();
+        let mut num_ground_links = vec![];
+        for time in [0.0, 600.0, 1200.0, 1800.0] {
+            let links = constellation.links(time);
+            let mut degrees = vec![0; num_sats as usize];
+            for (u, v, distance) in &links {
+                assert!(u < v);
+                if *v < num_sats {
+                    assert!(*distance <= 5000e3);
+                    degrees[*u as usize] += 1;
+                    degrees[*v as usize] += 1;
+                } else {
+                    assert!(constellation.elevation(v - num_sats, *u, time)? >= 20.0);
+                }
+            }
+            assert!(degrees.iter().all(|degree| *degree >= 4));
+            num_ground_links.push(links.iter().filter(|(_, v, _)| *v >= num_sats).count());
+        }
+        assert!(num_ground_links.iter().any(|n| *n > 0));
+        assert!(num_ground_links.windows(2).any(|w| w[0] != w[1]));
+
+        // Deep-space satellites are not supported.
+        let mut deep_space = tles[0].clone();
+        deep_space.mean_motion = 1.0;
+        assert!(Constellation::from_tle(TleParams::default(), &[deep_space], vec![]).is_err());
+
+        Ok(())
+    }
+}
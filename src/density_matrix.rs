@@ -0,0 +1,527 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+use num_complex::Complex64;
+
+use crate::bell_diagonal::BellDiagonal;
+
+/// Single-qubit gate, row-major.
+type Gate = [[Complex64; 2]; 2];
+
+const ZERO: Complex64 = Complex64::new(0.0, 0.0);
+const ONE: Complex64 = Complex64::new(1.0, 0.0);
+
+const PAULI_I: Gate = [[ONE, ZERO], [ZERO, ONE]];
+const PAULI_X: Gate = [[ZERO, ONE], [ONE, ZERO]];
+const PAULI_Y: Gate = [
+    [ZERO, Complex64::new(0.0, -1.0)],
+    [Complex64::new(0.0, 1.0), ZERO],
+];
+const PAULI_Z: Gate = [[ONE, ZERO], [ZERO, Complex64::new(-1.0, 0.0)]];
+
+/// Pauli errors, in the same order as the coefficients of [`BellDiagonal`].
+const PAULI_ERRORS: [Gate; 4] = [PAULI_I, PAULI_X, PAULI_Z, PAULI_Y];
+
+/// Density matrix of a register of qubits, stored in full.
+///
+/// This is an exact, but much slower, alternative to [`BellDiagonal`]: it
+/// does not make any assumption on the form of the state, hence it is used
+/// as an oracle to validate the closed-form updates of the fidelity.
+///
+/// Qubit 0 is the most significant one in the computational basis. The EPR
+/// pairs are registers with two qubits, i.e., Alice's and Bob's ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DensityMatrix {
+    /// Number of qubits.
+    num_qubits: usize,
+    /// Matrix elements, row-major.
+    elements: Vec<Complex64>,
+}
+
+impl DensityMatrix {
+    /// Create the density matrix of a two-qubit Bell-diagonal state.
+    pub fn from_bell_diagonal(state: &BellDiagonal) -> Self {
+        let mut phi_plus = Self::zero(2);
+        for i in [0b00, 0b11] {
+            for j in [0b00, 0b11] {
+                phi_plus.elements[i * 4 + j] = Complex64::new(0.5, 0.0);
+            }
+        }
+
+        let mut elements = vec![ZERO; 16];
+        for (coefficient, error) in state.coefficients().iter().zip(PAULI_ERRORS.iter()) {
+            let bell = phi_plus.apply(&operator(2, 1, error));
+            for (e, b) in elements.iter_mut().zip(bell.elements.iter()) {
+                *e += b * coefficient;
+            }
+        }
+        Self {
+            num_qubits: 2,
+            elements,
+        }
+    }
+
+    /// Create the density matrix of a Werner state with given fidelity.
+    pub fn werner(fidelity: f64) -> Self {
+        Self::from_bell_diagonal(&BellDiagonal::werner(fidelity))
+    }
+
+    /// Return the diagonal of a two-qubit state in the Bell basis, i.e.,
+    /// the closest Bell-diagonal state.
+    pub fn bell_diagonal(&self) -> BellDiagonal {
+        assert_eq!(2, self.num_qubits);
+        let phi_plus = Self::from_bell_diagonal(&BellDiagonal::new([1.0, 0.0, 0.0, 0.0]));
+        BellDiagonal::new(std::array::from_fn(|i| {
+            let bell = phi_plus.apply(&operator(2, 1, &PAULI_ERRORS[i]));
+            self.overlap(&bell)
+        }))
+    }
+
+    /// Return the fidelity of a two-qubit state with respect to |Φ+⟩.
+    pub fn fidelity(&self) -> f64 {
+        self.bell_diagonal().fidelity()
+    }
+
+    /// Return the trace.
+    pub fn trace(&self) -> f64 {
+        let dim = self.dim();
+        (0..dim).map(|i| self.elements[i * dim + i].re).sum()
+    }
+
+    /// Return the Werner state with the same fidelity, i.e., apply twirling.
+    pub fn twirl(&self) -> Self {
+        Self::werner(self.fidelity())
+    }
+
+    /// Apply depolarizing noise to a qubit for a given time, in s.
+    pub fn depolarize(&self, qubit: usize, rate: f64, time: f64) -> Self {
+        let p = (-rate * time).exp();
+        let mut ret = self.scale(p);
+        for error in PAULI_ERRORS {
+            ret = ret.add(
+                &self
+                    .apply(&operator(self.num_qubits, qubit, &error))
+                    .scale((1.0 - p) / 4.0),
+            );
+        }
+        ret
+    }
+
+    /// Apply dephasing noise to a qubit for a given time, in s.
+    ///
+    /// A Z error occurs with probability (1 - exp(-rate * time)) / 2.
+    pub fn dephase(&self, qubit: usize, rate: f64, time: f64) -> Self {
+        let q = (1.0 - (-rate * time).exp()) / 2.0;
+        self.scale(1.0 - q).add(
+            &self
+                .apply(&operator(self.num_qubits, qubit, &PAULI_Z))
+                .scale(q),
+        )
+    }
+
+    /// Apply both depolarizing and dephasing noise to a qubit for a given
+    /// time, in s.
+    pub fn decay(
+        &self,
+        qubit: usize,
+        depolarizing_rate: f64,
+        dephasing_rate: f64,
+        time: f64,
+    ) -> Self {
+        self.depolarize(qubit, depolarizing_rate, time)
+            .dephase(qubit, dephasing_rate, time)
+    }
+
+    /// Return the two-qubit state with Alice's and Bob's qubits exchanged.
+    pub fn exchange(&self) -> Self {
+        assert_eq!(2, self.num_qubits);
+        self.apply(&swap_gate())
+    }
+
+    /// Return the state obtained by entanglement swapping with another state.
+    ///
+    /// A Bell measurement is done on Bob's qubit of this state and Alice's
+    /// qubit of the other one, followed by the Pauli correction on Bob's qubit
+    /// of the other state for every outcome.
+    pub fn swap(&self, other: &Self) -> Self {
+        assert_eq!(2, self.num_qubits);
+        assert_eq!(2, other.num_qubits);
+
+        // Qubits: 0 = Alice, 1 and 2 = measured, 3 = Bob.
+        let state = self
+            .tensor(other)
+            .apply(&cnot(4, 1, 2))
+            .apply(&operator(4, 1, &hadamard()));
+        let mut ret = Self::zero(2);
+        for m1 in 0..2 {
+            for m2 in 0..2 {
+                let mut outcome = state.project(1, m1).project(2, m2);
+                if m2 == 1 {
+                    outcome = outcome.apply(&operator(4, 3, &PAULI_X));
+                }
+                if m1 == 1 {
+                    outcome = outcome.apply(&operator(4, 3, &PAULI_Z));
+                }
+                ret = ret.add(&outcome.partial_trace(&[0, 3]));
+            }
+        }
+        ret
+    }
+
+    /// Return the success probability and output state of one DEJMPS round
+    /// with another state.
+    pub fn dejmps(&self, other: &Self) -> (f64, Self) {
+        let c = std::f64::consts::FRAC_1_SQRT_2;
+        let rotation = |sign: f64| -> Gate {
+            [
+                [Complex64::new(c, 0.0), Complex64::new(0.0, -sign * c)],
+                [Complex64::new(0.0, -sign * c), Complex64::new(c, 0.0)],
+            ]
+        };
+
+        // Qubits: 0 and 2 = Alice, 1 and 3 = Bob. Alice rotates by π/2 and
+        // Bob by -π/2 around the X axis.
+        let state = self
+            .tensor(other)
+            .apply(&operator(4, 0, &rotation(1.0)))
+            .apply(&operator(4, 2, &rotation(1.0)))
+            .apply(&operator(4, 1, &rotation(-1.0)))
+            .apply(&operator(4, 3, &rotation(-1.0)));
+        Self::bilateral_cnot(&state)
+    }
+
+    /// Return the success probability and output state of one BBPSSW round
+    /// with another state, which twirls both the inputs and the output.
+    pub fn bbpssw(&self, other: &Self) -> (f64, Self) {
+        let (success_prob, state) = Self::bilateral_cnot(&self.twirl().tensor(&other.twirl()));
+        (success_prob, state.twirl())
+    }
+
+    /// Apply the bilateral CNOT to two EPR pairs, from the first one to the
+    /// second one, then measure the latter and keep the former if the
+    /// outcomes at Alice and Bob are the same.
+    fn bilateral_cnot(state: &Self) -> (f64, Self) {
+        let state = state.apply(&cnot(4, 0, 2)).apply(&cnot(4, 1, 3));
+        let mut ret = Self::zero(2);
+        for m in 0..2 {
+            ret = ret.add(&state.project(2, m).project(3, m).partial_trace(&[0, 1]));
+        }
+        let success_prob = ret.trace();
+        (success_prob, ret.scale(1.0 / success_prob))
+    }
+
+    /// Return an all-zero matrix.
+    fn zero(num_qubits: usize) -> Self {
+        Self {
+            num_qubits,
+            elements: vec![ZERO; 1 << (2 * num_qubits)],
+        }
+    }
+
+    /// Return the dimension of the Hilbert space.
+    fn dim(&self) -> usize {
+        1 << self.num_qubits
+    }
+
+    /// Return Tr(self * other), assuming both are Hermitian.
+    fn overlap(&self, other: &Self) -> f64 {
+        self.elements
+            .iter()
+            .zip(other.elements.iter())
+            .map(|(a, b)| (a * b.conj()).re)
+            .sum()
+    }
+
+    /// Return the matrix multiplied by a real scalar.
+    fn scale(&self, factor: f64) -> Self {
+        Self {
+            num_qubits: self.num_qubits,
+            elements: self.elements.iter().map(|e| e * factor).collect(),
+        }
+    }
+
+    /// Return the sum with another matrix of the same size.
+    fn add(&self, other: &Self) -> Self {
+        assert_eq!(self.num_qubits, other.num_qubits);
+        Self {
+            num_qubits: self.num_qubits,
+            elements: self
+                .elements
+                .iter()
+                .zip(other.elements.iter())
+                .map(|(a, b)| a + b)
+                .collect(),
+        }
+    }
+
+    /// Return the tensor product with another register, whose qubits follow.
+    fn tensor(&self, other: &Self) -> Self {
+        let (dim_a, dim_b) = (self.dim(), other.dim());
+        let dim = dim_a * dim_b;
+        let mut ret = Self::zero(self.num_qubits + other.num_qubits);
+        for i in 0..dim {
+            for j in 0..dim {
+                ret.elements[i * dim + j] = self.elements[(i / dim_b) * dim_a + j / dim_b]
+                    * other.elements[(i % dim_b) * dim_b + j % dim_b];
+            }
+        }
+        ret
+    }
+
+    /// Return U * self * U^†.
+    fn apply(&self, unitary: &[Complex64]) -> Self {
+        let dim = self.dim();
+        let left = multiply(unitary, &self.elements, dim);
+        let dagger = (0..dim * dim)
+            .map(|k| unitary[(k % dim) * dim + k / dim].conj())
+            .collect::<Vec<Complex64>>();
+        Self {
+            num_qubits: self.num_qubits,
+            elements: multiply(&left, &dagger, dim),
+        }
+    }
+
+    /// Return the unnormalized state after measuring a qubit in the
+    /// computational basis with given outcome.
+    fn project(&self, qubit: usize, outcome: usize) -> Self {
+        let dim = self.dim();
+        let mut ret = self.clone();
+        for i in 0..dim {
+            for j in 0..dim {
+                if bit(self.num_qubits, i, qubit) != outcome
+                    || bit(self.num_qubits, j, qubit) != outcome
+                {
+                    ret.elements[i * dim + j] = ZERO;
+                }
+            }
+        }
+        ret
+    }
+
+    /// Return the reduced state of the given qubits, in this order, by
+    /// tracing out all the others.
+    fn partial_trace(&self, keep: &[usize]) -> Self {
+        let dim = self.dim();
+        let traced = (0..self.num_qubits)
+            .filter(|q| !keep.contains(q))
+            .collect::<Vec<usize>>();
+        let index = |i: usize, qubits: &[usize]| {
+            qubits
+                .iter()
+                .fold(0, |acc, q| (acc << 1) | bit(self.num_qubits, i, *q))
+        };
+
+        let mut ret = Self::zero(keep.len());
+        let reduced_dim = ret.dim();
+        for i in 0..dim {
+            for j in 0..dim {
+                if index(i, &traced) == index(j, &traced) {
+                    ret.elements[index(i, keep) * reduced_dim + index(j, keep)] +=
+                        self.elements[i * dim + j];
+                }
+            }
+        }
+        ret
+    }
+}
+
+/// Return the value of a qubit in a computational basis state.
+fn bit(num_qubits: usize, state: usize, qubit: usize) -> usize {
+    (state >> (num_qubits - 1 - qubit)) & 1
+}
+
+/// Return the product of two square matrices, row-major.
+fn multiply(a: &[Complex64], b: &[Complex64], dim: usize) -> Vec<Complex64> {
+    let mut ret = vec![ZERO; dim * dim];
+    for i in 0..dim {
+        for k in 0..dim {
+            let a_ik = a[i * dim + k];
+            if a_ik == ZERO {
+                continue;
+            }
+            for j in 0..dim {
+                ret[i * dim + j] += a_ik * b[k * dim + j];
+            }
+        }
+    }
+    ret
+}
+
+/// Return the Hadamard gate.
+fn hadamard() -> Gate {
+    let c = Complex64::new(std::f64::consts::FRAC_1_SQRT_2, 0.0);
+    [[c, c], [c, -c]]
+}
+
+/// Return the operator applying a single-qubit gate to one qubit of a
+/// register, and the identity to all the others.
+fn operator(num_qubits: usize, qubit: usize, gate: &Gate) -> Vec<Complex64> {
+    let dim = 1 << num_qubits;
+    let mut ret = vec![ZERO; dim * dim];
+    for i in 0..dim {
+        for j in 0..dim {
+            let others = (0..num_qubits)
+                .filter(|q| *q != qubit)
+                .all(|q| bit(num_qubits, i, q) == bit(num_qubits, j, q));
+            if others {
+                ret[i * dim + j] = gate[bit(num_qubits, i, qubit)][bit(num_qubits, j, qubit)];
+            }
+        }
+    }
+    ret
+}
+
+/// Return the permutation operator mapping every basis state to another.
+fn permutation(num_qubits: usize, map: impl Fn(usize) -> usize) -> Vec<Complex64> {
+    let dim = 1 << num_qubits;
+    let mut ret = vec![ZERO; dim * dim];
+    for j in 0..dim {
+        ret[map(j) * dim + j] = ONE;
+    }
+    ret
+}
+
+/// Return the CNOT operator between two qubits of a register.
+fn cnot(num_qubits: usize, control: usize, target: usize) -> Vec<Complex64> {
+    permutation(num_qubits, |j| {
+        if bit(num_qubits, j, control) == 1 {
+            j ^ (1 << (num_qubits - 1 - target))
+        } else {
+            j
+        }
+    })
+}
+
+/// Return the operator exchanging the two qubits of a two-qubit register.
+fn swap_gate() -> Vec<Complex64> {
+    permutation(2, |j| ((j & 1) << 1) | (j >> 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DensityMatrix;
+    use crate::bell_diagonal::BellDiagonal;
+
+    fn assert_near(expected: f64, value: f64) {
+        assert!(
+            (expected - value).abs() < 1e-12,
+            "{} != {}",
+            expected,
+            value
+        );
+    }
+
+    fn assert_state_near(expected: BellDiagonal, state: &DensityMatrix) {
+        assert_near(1.0, state.trace());
+        for (e, c) in expected
+            .coefficients()
+            .iter()
+            .zip(state.bell_diagonal().coefficients().iter())
+        {
+            assert_near(*e, *c);
+        }
+
+        // The state is Bell-diagonal, too.
+        for (e, c) in DensityMatrix::from_bell_diagonal(&expected)
+            .elements
+            .iter()
+            .zip(state.elements.iter())
+        {
+            assert!((e - c).norm() < 1e-12, "{} != {}", e, c);
+        }
+    }
+
+    fn states() -> Vec<BellDiagonal> {
+        vec![
+            BellDiagonal::werner(1.0),
+            BellDiagonal::werner(0.8),
+            BellDiagonal::new([0.7, 0.2, 0.1, 0.0]),
+            BellDiagonal::new([0.6, 0.05, 0.15, 0.2]),
+            BellDiagonal::new([0.1, 0.2, 0.3, 0.4]),
+        ]
+    }
+
+    #[test]
+    fn test_density_matrix_generation() {
+        for state in states() {
+            let matrix = DensityMatrix::from_bell_diagonal(&state);
+            assert_state_near(state, &matrix);
+            assert_state_near(state, &matrix.exchange());
+            assert_near(state.fidelity(), matrix.fidelity());
+        }
+        assert_state_near(BellDiagonal::werner(0.8), &DensityMatrix::werner(0.8));
+        assert_state_near(
+            BellDiagonal::werner(0.7),
+            &DensityMatrix::from_bell_diagonal(&BellDiagonal::new([0.7, 0.2, 0.1, 0.0])).twirl(),
+        );
+    }
+
+    #[test]
+    fn test_density_matrix_decay() {
+        for state in states() {
+            let matrix = DensityMatrix::from_bell_diagonal(&state);
+            for qubit in [0, 1] {
+                assert_state_near(
+                    state.depolarize(0.3, 2.0),
+                    &matrix.depolarize(qubit, 0.3, 2.0),
+                );
+                assert_state_near(state.dephase(0.3, 2.0), &matrix.dephase(qubit, 0.3, 2.0));
+                assert_state_near(
+                    state.decay(0.3, 0.1, 2.0),
+                    &matrix.decay(qubit, 0.3, 0.1, 2.0),
+                );
+            }
+
+            // Both qubits decay with the sum of the rates.
+            assert_state_near(
+                state.decay(0.4, 0.3, 1.0),
+                &matrix.decay(0, 0.1, 0.2, 1.0).decay(1, 0.3, 0.1, 1.0),
+            );
+        }
+
+        // Depolarizing noise matches the scalar fidelity model.
+        assert_near(
+            crate::utils::fidelity(0.9, 0.5, 2.0),
+            DensityMatrix::werner(0.9)
+                .depolarize(1, 0.5, 2.0)
+                .fidelity(),
+        );
+    }
+
+    #[test]
+    fn test_density_matrix_swap() {
+        for a in states() {
+            for b in states() {
+                let matrix_a = DensityMatrix::from_bell_diagonal(&a);
+                let matrix_b = DensityMatrix::from_bell_diagonal(&b);
+                assert_state_near(a.swap(&b), &matrix_a.swap(&matrix_b));
+            }
+        }
+        assert_near(
+            crate::tests::swapping_fidelity(0.9, 0.8),
+            DensityMatrix::werner(0.9)
+                .swap(&DensityMatrix::werner(0.8))
+                .fidelity(),
+        );
+    }
+
+    #[test]
+    fn test_density_matrix_purification() {
+        for a in states() {
+            for b in states() {
+                let matrix_a = DensityMatrix::from_bell_diagonal(&a);
+                let matrix_b = DensityMatrix::from_bell_diagonal(&b);
+
+                let (p, state) = a.dejmps(&b);
+                let (p_exact, matrix) = matrix_a.dejmps(&matrix_b);
+                assert_near(p, p_exact);
+                assert_state_near(state, &matrix);
+
+                let (p, state) = a.bbpssw(&b);
+                let (p_exact, matrix) = matrix_a.bbpssw(&matrix_b);
+                assert_near(p, p_exact);
+                assert_state_near(state, &matrix);
+            }
+        }
+    }
+}
@@ -1,65 +1,231 @@
 // SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
 // SPDX-License-Identifier: MIT
 
-use std::u32;
+/// Representation of the state of the EPR pairs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Backend {
+    /// Closed-form updates of Bell-diagonal states.
+    #[default]
+    BellDiagonal,
+    /// Exact density matrices, tracked alongside the Bell-diagonal states to
+    /// validate them. Much slower, only meant for small networks.
+    DensityMatrix,
+}
+
+/// One end of an EPR pair.
+#[derive(Debug)]
+struct End {
+    /// Identifier of the node holding this end.
+    node_id: u32,
+    /// Depolarizing rate of the memory of the node holding this end.
+    depolarizing_rate: f64,
+    /// Dephasing rate of the memory of the node holding this end.
+    dephasing_rate: f64,
+    /// Time when this end was consumed, or None if still stored.
+    consumed: Option<u64>,
+}
+
+impl End {
+    fn new(node_id: u32, (depolarizing_rate, dephasing_rate): (f64, f64)) -> Self {
+        Self {
+            node_id,
+            depolarizing_rate,
+            dephasing_rate,
+            consumed: None,
+        }
+    }
+
+    /// Return true if this end is held by the node and not consumed yet.
+    fn is_stored_at(&self, node_id: u32) -> bool {
+        self.node_id == node_id && self.consumed.is_none()
+    }
+
+    /// Return the time this end has been stored from `since` until `now`,
+    /// or until it was consumed, in s.
+    fn storage_time(&self, since: u64, now: u64) -> f64 {
+        let until = self.consumed.unwrap_or(now);
+        crate::utils::to_seconds(until.max(since) - since)
+    }
+}
 
 // EPR pair.
 #[derive(Debug)]
 pub struct EprPair {
-    /// Identifier of one of the nodes holding the EPR pair or None if consumed.
-    alice_id: Option<u32>,
-    /// Identifier of the other node holding the EPR pair or None if consumed.
-    bob_id: Option<u32>,
+    /// One end of the EPR pair.
+    alice: End,
+    /// The other end of the EPR pair.
+    bob: End,
     /// Time when the fidelity was last updated.
     updated: u64,
-    /// Fidelity the EPR pair at `updated` time.
-    fidelity: f64,
+    /// State of the EPR pair at `updated` time.
+    state: crate::bell_diagonal::BellDiagonal,
+    /// Number of purification rounds that produced this EPR pair.
+    rounds: u32,
+    /// Exact state of the EPR pair at `updated` time, with Alice's qubit
+    /// first, only with the density matrix backend.
+    exact: Option<crate::density_matrix::DensityMatrix>,
 }
 
 impl EprPair {
-    /// Consume one end of the EPR pair.
-    /// Return None if `node_id` does not match any of the nodes' indices,
+    /// Consume one end of the EPR pair at `now`.
+    /// Return None if `node_id` does not match any of the ends not consumed,
     /// otherwise return true if the EPR pair is fully consumed.
-    pub fn consume(&mut self, node_id: u32) -> Option<(u64, f64, bool)> {
-        let alice_id = self.alice_id.clone().unwrap_or(u32::MAX);
-        let bob_id = self.bob_id.clone().unwrap_or(u32::MAX);
-
-        if node_id != alice_id && node_id != bob_id {
+    pub fn consume(&mut self, node_id: u32, now: u64) -> Option<bool> {
+        if self.alice.is_stored_at(node_id) {
+            self.alice.consumed = Some(now);
+        } else if self.bob.is_stored_at(node_id) {
+            self.bob.consumed = Some(now);
+        } else {
             return None;
         }
 
-        if node_id == alice_id {
-            self.alice_id = None;
-        } else if node_id == bob_id {
-            self.bob_id = None;
+        Some(self.alice.consumed.is_some() && self.bob.consumed.is_some())
+    }
+
+    /// Return the identifier of the node holding the other end of the EPR
+    /// pair, if `node_id` holds one of them and the other one is not consumed.
+    pub fn peer(&self, node_id: u32) -> Option<u32> {
+        if self.alice.is_stored_at(node_id) && self.bob.consumed.is_none() {
+            Some(self.bob.node_id)
+        } else if self.bob.is_stored_at(node_id) && self.alice.consumed.is_none() {
+            Some(self.alice.node_id)
+        } else {
+            None
         }
+    }
 
-        Some((
-            self.updated,
-            self.fidelity,
-            self.alice_id.is_none() && self.bob_id.is_none(),
+    /// Return the state of the EPR pair at `now`. Each end decays with the
+    /// rates of its own node from the last update until `now` or until it was
+    /// consumed, whichever comes first.
+    pub fn state(&self, now: u64) -> crate::bell_diagonal::BellDiagonal {
+        assert!(now >= self.updated);
+        [&self.alice, &self.bob]
+            .iter()
+            .fold(self.state, |state, end| {
+                state.decay(
+                    end.depolarizing_rate,
+                    end.dephasing_rate,
+                    end.storage_time(self.updated, now),
+                )
+            })
+    }
+
+    /// Return the fidelity of the EPR pair at `now`.
+    pub fn fidelity(&self, now: u64) -> f64 {
+        self.state(now).fidelity()
+    }
+
+    /// Return the exact state of the EPR pair at `now`, if tracked, with the
+    /// same decay as `state()`.
+    pub fn exact_state(&self, now: u64) -> Option<crate::density_matrix::DensityMatrix> {
+        assert!(now >= self.updated);
+        let exact = self.exact.as_ref()?;
+        Some([&self.alice, &self.bob].iter().enumerate().fold(
+            exact.clone(),
+            |exact, (qubit, end)| {
+                exact.decay(
+                    qubit,
+                    end.depolarizing_rate,
+                    end.dephasing_rate,
+                    end.storage_time(self.updated, now),
+                )
+            },
         ))
     }
+
+    /// Return the exact state of the EPR pair at `now`, if tracked, with the
+    /// qubit held by `node_id` first.
+    fn exact_state_from(
+        &self,
+        node_id: u32,
+        now: u64,
+    ) -> Option<crate::density_matrix::DensityMatrix> {
+        let exact = self.exact_state(now)?;
+        if self.alice.node_id == node_id {
+            Some(exact)
+        } else {
+            Some(exact.exchange())
+        }
+    }
+
+    /// Return the ends not consumed yet.
+    fn stored_ends(&self) -> impl Iterator<Item = &End> {
+        [&self.alice, &self.bob]
+            .into_iter()
+            .filter(|end| end.consumed.is_none())
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct EprRegister {
     epr_pairs: std::collections::HashMap<u64, EprPair>,
     last_epr_pair_id: u64,
+    /// Depolarizing and dephasing rates of the memory of every node, indexed
+    /// by the node ID. The memories of nodes not included do not decay.
+    noise_rates: Vec<(f64, f64)>,
+    /// Representation of the state of the EPR pairs.
+    backend: Backend,
 }
 
 impl EprRegister {
-    /// Create a new EPR pair with given characteristics. Return its identifier.
+    /// Create an empty register with the depolarizing and dephasing rates of
+    /// the nodes' memories, indexed by the node ID.
+    pub fn new(noise_rates: Vec<(f64, f64)>, backend: Backend) -> Self {
+        Self {
+            noise_rates,
+            backend,
+            ..Default::default()
+        }
+    }
+
+    /// Create a new EPR pair in a Werner state with given fidelity.
+    /// Return its identifier.
     pub fn new_epr_pair(&mut self, alice_id: u32, bob_id: u32, updated: u64, fidelity: f64) -> u64 {
+        self.new_epr_pair_with_state(
+            alice_id,
+            bob_id,
+            updated,
+            crate::bell_diagonal::BellDiagonal::werner(fidelity),
+        )
+    }
+
+    /// Create a new EPR pair with given state. Return its identifier.
+    pub fn new_epr_pair_with_state(
+        &mut self,
+        alice_id: u32,
+        bob_id: u32,
+        updated: u64,
+        state: crate::bell_diagonal::BellDiagonal,
+    ) -> u64 {
+        let exact = match self.backend {
+            Backend::BellDiagonal => None,
+            Backend::DensityMatrix => Some(
+                crate::density_matrix::DensityMatrix::from_bell_diagonal(&state),
+            ),
+        };
+        self.insert(alice_id, bob_id, updated, state, exact)
+    }
+
+    /// Add a new EPR pair with given states. Return its identifier.
+    fn insert(
+        &mut self,
+        alice_id: u32,
+        bob_id: u32,
+        updated: u64,
+        state: crate::bell_diagonal::BellDiagonal,
+        exact: Option<crate::density_matrix::DensityMatrix>,
+    ) -> u64 {
         let epr_pair_id = self.last_epr_pair_id;
 
         let res = self.epr_pairs.insert(
             epr_pair_id,
             EprPair {
-                alice_id: Some(alice_id),
-                bob_id: Some(bob_id),
+                alice: End::new(alice_id, self.noise_rates_of(alice_id)),
+                bob: End::new(bob_id, self.noise_rates_of(bob_id)),
                 updated,
-                fidelity,
+                state,
+                rounds: 0,
+                exact,
             },
         );
         assert!(
@@ -74,54 +240,248 @@ impl EprRegister {
 
     /// Consume an EPR pair with given ID at a node.
     /// Remove the EPR pair if both end-points consumed it.
-    pub fn consume(&mut self, epr_pair_id: u64, node_id: u32) -> Option<(u64, f64)> {
-        let epr_pair = self.epr_pairs.get_mut(&epr_pair_id);
-        let ret = if let Some(epr_pair) = epr_pair {
-            epr_pair.consume(node_id)
-        } else {
-            None
+    ///
+    /// Return the fidelity of the EPR pair at `now`, or None if the node
+    /// does not hold an end of the EPR pair.
+    pub fn consume(&mut self, epr_pair_id: u64, node_id: u32, now: u64) -> Option<f64> {
+        let epr_pair = self.epr_pairs.get_mut(&epr_pair_id)?;
+        let remove = epr_pair.consume(node_id, now)?;
+        let fidelity = epr_pair.fidelity(now);
+        if remove {
+            self.epr_pairs.remove(&epr_pair_id);
+        }
+        Some(fidelity)
+    }
+
+    /// Perform entanglement swapping at a node of two EPR pairs, which must
+    /// both have one end at `node_id`, and create a new EPR pair between the
+    /// two other end-points. The two EPR pairs are removed.
+    /// The fidelity of the input EPR pairs decays, from their last update
+    /// until `now`, at both ends.
+    ///
+    /// Return the identifier and fidelity of the new EPR pair or None if the
+    /// EPR pairs were not found or do not share the node.
+    pub fn swap(
+        &mut self,
+        epr_pair_id_a: u64,
+        epr_pair_id_b: u64,
+        node_id: u32,
+        now: u64,
+    ) -> Option<(u64, f64)> {
+        let alice_id = self.epr_pairs.get(&epr_pair_id_a)?.peer(node_id)?;
+        let bob_id = self.epr_pairs.get(&epr_pair_id_b)?.peer(node_id)?;
+        if epr_pair_id_a == epr_pair_id_b {
+            return None;
+        }
+
+        // The exact states are oriented so that the qubits held by the
+        // swapping node are in the middle.
+        let mut states = vec![];
+        let mut exact_states = vec![];
+        for (epr_pair_id, first_id) in [(epr_pair_id_a, alice_id), (epr_pair_id_b, node_id)] {
+            let epr_pair = self.epr_pairs.remove(&epr_pair_id).unwrap();
+            states.push(epr_pair.state(now));
+            exact_states.push(epr_pair.exact_state_from(first_id, now));
+        }
+
+        let state = states[0].swap(&states[1]);
+        let exact = match (&exact_states[0], &exact_states[1]) {
+            (Some(a), Some(b)) => Some(a.swap(b)),
+            _ => None,
         };
+        Some((
+            self.insert(alice_id, bob_id, now, state, exact),
+            state.fidelity(),
+        ))
+    }
 
-        if let Some((updated, fidelity, remove)) = ret {
-            if remove {
-                self.epr_pairs.remove(&epr_pair_id);
-            }
-            Some((updated, fidelity))
-        } else {
-            None
+    /// Return the state of an EPR pair at `now`, which decays from its last
+    /// update at both ends, or None if the EPR pair is not found.
+    pub fn state(&self, epr_pair_id: u64, now: u64) -> Option<crate::bell_diagonal::BellDiagonal> {
+        Some(self.epr_pairs.get(&epr_pair_id)?.state(now))
+    }
+
+    /// Return the fidelity of an EPR pair at `now`, which decays from its last
+    /// update at both ends, or None if the EPR pair is not found.
+    pub fn fidelity(&self, epr_pair_id: u64, now: u64) -> Option<f64> {
+        Some(self.epr_pairs.get(&epr_pair_id)?.fidelity(now))
+    }
+
+    /// Return the fidelity of the exact state of an EPR pair at `now`, or None
+    /// if the EPR pair is not found or its exact state is not tracked.
+    pub fn exact_fidelity(&self, epr_pair_id: u64, now: u64) -> Option<f64> {
+        Some(
+            self.epr_pairs
+                .get(&epr_pair_id)?
+                .exact_state(now)?
+                .fidelity(),
+        )
+    }
+
+    /// Return the time, in s, after which the fidelity of an EPR pair decays
+    /// from `now` to a given threshold with the noise of the ends not
+    /// consumed yet, or None if the EPR pair is not found or the fidelity
+    /// never reaches the threshold.
+    pub fn fidelity_time(&self, epr_pair_id: u64, now: u64, threshold: f64) -> Option<f64> {
+        let epr_pair = self.epr_pairs.get(&epr_pair_id)?;
+
+        // The noise channels acting on the two qubits of a Bell-diagonal
+        // state have the same effect, hence their rates add up.
+        let (depolarizing_rate, dephasing_rate) =
+            epr_pair
+                .stored_ends()
+                .fold((0.0, 0.0), |(depolarizing_rate, dephasing_rate), end| {
+                    (
+                        depolarizing_rate + end.depolarizing_rate,
+                        dephasing_rate + end.dephasing_rate,
+                    )
+                });
+        epr_pair
+            .state(now)
+            .fidelity_time(depolarizing_rate, dephasing_rate, threshold)
+    }
+
+    /// Return the number of purification rounds of an EPR pair, or None if
+    /// the EPR pair is not found.
+    pub fn rounds(&self, epr_pair_id: u64) -> Option<u32> {
+        self.epr_pairs
+            .get(&epr_pair_id)
+            .map(|epr_pair| epr_pair.rounds)
+    }
+
+    /// Update an EPR pair after its successful purification with other EPR
+    /// pairs, which are removed. The exact state, if tracked, is obtained by
+    /// purifying the exact states of all the EPR pairs with the same protocol.
+    /// Return false if any of the EPR pairs is not found.
+    pub fn purified(
+        &mut self,
+        epr_pair_ids: &[u64],
+        now: u64,
+        protocol: crate::purification::Protocol,
+        state: crate::bell_diagonal::BellDiagonal,
+        rounds: u32,
+    ) -> bool {
+        if epr_pair_ids.is_empty() || !epr_pair_ids.iter().all(|id| self.contains(*id)) {
+            return false;
+        }
+
+        // All the exact states have the qubit of the same node first.
+        let first_id = self.epr_pairs[&epr_pair_ids[0]].alice.node_id;
+        let exact_states = epr_pair_ids
+            .iter()
+            .map(|id| self.epr_pairs[id].exact_state_from(first_id, now))
+            .collect::<Option<Vec<crate::density_matrix::DensityMatrix>>>();
+        for epr_pair_id in epr_pair_ids.iter().skip(1) {
+            self.epr_pairs.remove(epr_pair_id);
         }
+
+        let epr_pair = self.epr_pairs.get_mut(&epr_pair_ids[0]).unwrap();
+        epr_pair.updated = now;
+        epr_pair.state = state;
+        epr_pair.rounds = rounds;
+        epr_pair.exact = exact_states
+            .map(|exact_states| crate::purification::purify_exact(protocol, &exact_states).1);
+        true
+    }
+
+    /// Return the node holding the other end of an EPR pair, or None if the
+    /// EPR pair is not found or the other end has been consumed already.
+    pub fn peer(&self, epr_pair_id: u64, node_id: u32) -> Option<u32> {
+        self.epr_pairs
+            .get(&epr_pair_id)
+            .and_then(|epr_pair| epr_pair.peer(node_id))
+    }
+
+    /// Return the nodes holding the ends of an EPR pair that have not been
+    /// consumed yet, or None if the EPR pair is not found.
+    pub fn ends(&self, epr_pair_id: u64) -> Option<Vec<u32>> {
+        self.epr_pairs
+            .get(&epr_pair_id)
+            .map(|epr_pair| epr_pair.stored_ends().map(|end| end.node_id).collect())
+    }
+
+    /// Return true if the EPR pair exists.
+    pub fn contains(&self, epr_pair_id: u64) -> bool {
+        self.epr_pairs.contains_key(&epr_pair_id)
+    }
+
+    /// Remove an EPR pair, irrespective of whether its ends are consumed.
+    /// Return true if the EPR pair was found.
+    pub fn remove(&mut self, epr_pair_id: u64) -> bool {
+        self.epr_pairs.remove(&epr_pair_id).is_some()
+    }
+
+    /// Return the depolarizing and dephasing rates of a node's memory.
+    fn noise_rates_of(&self, node_id: u32) -> (f64, f64) {
+        self.noise_rates
+            .get(node_id as usize)
+            .cloned()
+            .unwrap_or_default()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{EprPair, EprRegister};
+    use super::{Backend, End, EprPair, EprRegister};
+    use crate::bell_diagonal::BellDiagonal;
 
     #[test]
     fn test_epr_pair_consume() {
         let mut epr_pair = EprPair {
-            alice_id: Some(1),
-            bob_id: Some(2),
+            alice: End::new(1, (0.0, 0.0)),
+            bob: End::new(2, (0.0, 0.0)),
             updated: 999,
-            fidelity: 0.5,
+            state: BellDiagonal::werner(0.5),
+            rounds: 0,
+            exact: None,
         };
 
-        assert!(epr_pair.consume(42).is_none());
+        assert!(epr_pair.consume(42, 1000).is_none());
+        assert_eq!(Some(2), epr_pair.peer(1));
 
-        let (updated, fidelity, remove) = epr_pair.consume(1).unwrap();
-        assert_eq!(999, updated);
-        assert_float_eq::assert_f64_near!(0.5, fidelity);
-        assert!(!remove);
+        assert_eq!(Some(false), epr_pair.consume(1, 1000));
+        assert!(epr_pair.peer(2).is_none());
+        assert_float_eq::assert_f64_near!(0.5, epr_pair.fidelity(1000));
 
-        assert!(epr_pair.consume(1).is_none());
+        assert!(epr_pair.consume(1, 1000).is_none());
 
-        let (updated, fidelity, remove) = epr_pair.consume(2).unwrap();
-        assert_eq!(999, updated);
-        assert_float_eq::assert_f64_near!(0.5, fidelity);
-        assert!(remove);
+        assert_eq!(Some(true), epr_pair.consume(2, 1000));
+        assert_float_eq::assert_f64_near!(0.5, epr_pair.fidelity(1000));
 
-        assert!(epr_pair.consume(1).is_none());
-        assert!(epr_pair.consume(2).is_none());
+        assert!(epr_pair.consume(1, 1000).is_none());
+        assert!(epr_pair.consume(2, 1000).is_none());
+    }
+
+    #[test]
+    fn test_epr_pair_decay_at_both_ends() {
+        let second = crate::utils::to_nanoseconds(1.0);
+        let mut epr_pair = EprPair {
+            alice: End::new(1, (0.1, 0.0)),
+            bob: End::new(2, (0.3, 0.0)),
+            updated: second,
+            state: BellDiagonal::werner(0.9),
+            rounds: 0,
+            exact: None,
+        };
+
+        // Both ends decay while stored.
+        assert_float_eq::assert_f64_near!(
+            crate::utils::fidelity(0.9, 0.4, 2.0),
+            epr_pair.fidelity(3 * second)
+        );
+
+        // Alice is consumed after 1 s, Bob keeps decaying.
+        assert_eq!(Some(false), epr_pair.consume(1, 2 * second));
+        assert_float_eq::assert_f64_near!(
+            crate::utils::fidelity(crate::utils::fidelity(0.9, 0.1, 1.0), 0.3, 4.0),
+            epr_pair.fidelity(5 * second)
+        );
+
+        // Bob is consumed after 2 s, no more decay after that.
+        assert_eq!(Some(true), epr_pair.consume(2, 3 * second));
+        let expected = crate::utils::fidelity(crate::utils::fidelity(0.9, 0.1, 1.0), 0.3, 2.0);
+        assert_float_eq::assert_f64_near!(expected, epr_pair.fidelity(3 * second));
+        assert_float_eq::assert_f64_near!(expected, epr_pair.fidelity(9 * second));
     }
 
     #[test]
@@ -129,20 +489,15 @@ mod tests {
         let mut register = EprRegister::default();
         assert_eq!(0, register.new_epr_pair(1, 2, 990, 0.42));
 
-        assert!(register.consume(0, 99).is_none());
-        assert!(register.consume(1, 1).is_none());
-        assert!(register.consume(1, 2).is_none());
-
-        let (updated, fidelity) = register.consume(0, 1).unwrap();
-        assert_eq!(990, updated);
-        assert_float_eq::assert_f64_near!(0.42, fidelity);
+        assert!(register.consume(0, 99, 1000).is_none());
+        assert!(register.consume(1, 1, 1000).is_none());
+        assert!(register.consume(1, 2, 1000).is_none());
 
-        let (updated, fidelity) = register.consume(0, 2).unwrap();
-        assert_eq!(990, updated);
-        assert_float_eq::assert_f64_near!(0.42, fidelity);
+        assert_float_eq::assert_f64_near!(0.42, register.consume(0, 1, 1000).unwrap());
+        assert_float_eq::assert_f64_near!(0.42, register.consume(0, 2, 1000).unwrap());
 
-        assert!(register.consume(0, 1).is_none());
-        assert!(register.consume(0, 2).is_none());
+        assert!(register.consume(0, 1, 1000).is_none());
+        assert!(register.consume(0, 2, 1000).is_none());
     }
 
     #[test]
@@ -160,16 +515,177 @@ mod tests {
         }
         assert_eq!(100, register.epr_pairs.len());
 
-        assert!(register.consume(999, 1).is_none());
+        assert!(register.consume(999, 1, 100).is_none());
         for i in 0..100_u64 {
-            let epr_pair = register.consume(i, 1);
-            assert!(epr_pair.is_some());
-            let (updated, fidelity) = epr_pair.unwrap();
-            assert_eq!(i, updated);
-            assert_float_eq::assert_f64_near!(0.42, fidelity);
+            let fidelity = register.consume(i, 1, 100);
+            assert!(fidelity.is_some());
+            assert_float_eq::assert_f64_near!(0.42, fidelity.unwrap());
         }
 
-        assert!(register.consume(0, 1).is_none());
-        assert!(register.consume(99, 1).is_none());
+        assert!(register.consume(0, 1, 100).is_none());
+        assert!(register.consume(99, 1, 100).is_none());
+    }
+
+    #[test]
+    fn test_epr_pair_register_swap() {
+        // Only the memories of nodes 3 and 6 decay.
+        let mut register = EprRegister::new(
+            vec![
+                (0.0, 0.0),
+                (0.0, 0.0),
+                (0.0, 0.0),
+                (0.1, 0.0),
+                (0.0, 0.0),
+                (0.0, 0.0),
+                (0.2, 0.0),
+            ],
+            Backend::BellDiagonal,
+        );
+        let id_a = register.new_epr_pair(1, 2, 0, 0.9);
+        let id_b = register.new_epr_pair(2, 3, 0, 0.8);
+        let id_c = register.new_epr_pair(4, 5, 0, 0.8);
+
+        // Invalid swaps.
+        assert!(register.swap(id_a, id_c, 2, 0).is_none());
+        assert!(register.swap(id_a, id_b, 1, 0).is_none());
+        assert!(register.swap(id_a, id_a, 2, 0).is_none());
+        assert!(register.swap(id_a, 99, 2, 0).is_none());
+
+        // Valid swap, without decay.
+        let (id_d, fidelity) = register.swap(id_a, id_b, 2, 0).unwrap();
+        let fidelity_d = 0.9 * 0.8 + 0.1 * 0.2 / 3.0;
+        assert_float_eq::assert_f64_near!(fidelity_d, fidelity);
+        assert!(register.consume(id_a, 1, 0).is_none());
+        assert!(register.consume(id_b, 3, 0).is_none());
+        assert_eq!(2, register.epr_pairs.len());
+
+        // Swap again, with decay at both ends.
+        let id_e = register.new_epr_pair(3, 6, 0, 1.0);
+        let (id_f, fidelity) = register
+            .swap(id_d, id_e, 3, crate::utils::to_nanoseconds(20.0))
+            .unwrap();
+        let expected = crate::tests::swapping_fidelity(
+            crate::utils::fidelity(fidelity_d, 0.1, 20.0),
+            crate::utils::fidelity(1.0, 0.3, 20.0),
+        );
+        assert_float_eq::assert_f64_near!(expected, fidelity);
+
+        // The new EPR pair decays only at node 6.
+        assert_float_eq::assert_f64_near!(
+            2.0_f64.ln() / 0.2,
+            register
+                .fidelity_time(
+                    id_f,
+                    crate::utils::to_nanoseconds(20.0),
+                    0.25 + (expected - 0.25) / 2.0
+                )
+                .unwrap(),
+            1_000_000
+        );
+        assert_eq!(Some(vec![1, 6]), register.ends(id_f));
+        assert_float_eq::assert_f64_near!(
+            expected,
+            register
+                .consume(id_f, 1, crate::utils::to_nanoseconds(20.0))
+                .unwrap()
+        );
+        assert_eq!(Some(vec![6]), register.ends(id_f));
+        assert_float_eq::assert_f64_near!(
+            crate::utils::fidelity(expected, 0.2, 1.0),
+            register
+                .consume(id_f, 6, crate::utils::to_nanoseconds(21.0))
+                .unwrap()
+        );
+        assert!(register.ends(id_f).is_none());
+
+        // Fidelity.
+        assert!(register.fidelity(99, 0).is_none());
+        assert!(register.fidelity_time(99, 0, 0.5).is_none());
+        assert!(register.rounds(99).is_none());
+        assert_float_eq::assert_f64_near!(0.8, register.fidelity(id_c, 0).unwrap());
+        assert_eq!(Some(0), register.rounds(id_c));
+
+        // Remove.
+        assert!(register.remove(id_c));
+        assert!(!register.remove(id_c));
+        assert!(register.epr_pairs.is_empty());
+    }
+
+    #[test]
+    fn test_epr_pair_register_dephasing() {
+        // Nodes 1 and 2 only dephase.
+        let mut register = EprRegister::new(
+            vec![(0.0, 0.0), (0.0, 0.5), (0.0, 0.5)],
+            Backend::BellDiagonal,
+        );
+        let id = register.new_epr_pair(1, 2, 0, 1.0);
+
+        // Both ends contribute to dephasing.
+        let second = crate::utils::to_nanoseconds(1.0);
+        let expected = BellDiagonal::werner(1.0).dephase(1.0, 1.0);
+        let state = register.state(id, second).unwrap();
+        for (e, c) in expected.coefficients().iter().zip(state.coefficients()) {
+            assert_float_eq::assert_f64_near!(*e, c);
+        }
+
+        // The fidelity never drops below 0.5 with dephasing only.
+        assert!(register.fidelity_time(id, second, 0.5).is_none());
+        let time = register.fidelity_time(id, second, 0.6).unwrap();
+        assert!(time > 0.0);
+    }
+
+    #[test]
+    fn test_epr_pair_register_density_matrix() {
+        let noise_rates = vec![(0.1, 0.2), (0.3, 0.0), (0.0, 0.4), (0.2, 0.1)];
+        let mut register = EprRegister::new(noise_rates.clone(), Backend::DensityMatrix);
+        assert!(EprRegister::new(noise_rates, Backend::BellDiagonal)
+            .exact_fidelity(0, 0)
+            .is_none());
+
+        let assert_exact = |register: &EprRegister, epr_pair_id, now| {
+            let fidelity = register.fidelity(epr_pair_id, now).unwrap();
+            let exact_fidelity = register.exact_fidelity(epr_pair_id, now).unwrap();
+            assert!((fidelity - exact_fidelity).abs() < 1e-12);
+        };
+
+        // Decay, with the ends in both orientations.
+        let second = crate::utils::to_nanoseconds(1.0);
+        let id_a = register.new_epr_pair(0, 1, 0, 0.95);
+        let id_b = register.new_epr_pair(2, 1, 0, 0.9);
+        assert_exact(&register, id_a, second);
+        assert_exact(&register, id_b, second);
+
+        // Swapping.
+        let (id_c, _) = register.swap(id_a, id_b, 1, second).unwrap();
+        assert_exact(&register, id_c, 2 * second);
+
+        // Purification.
+        let id_d = register.new_epr_pair(2, 0, 2 * second, 0.8);
+        let id_e = register.new_epr_pair(0, 2, 2 * second, 0.85);
+        let states = [id_c, id_d, id_e].map(|id| register.state(id, 3 * second).unwrap());
+        let (_, state) =
+            crate::purification::purify(crate::purification::Protocol::Dejmps, &states);
+        assert!(!register.purified(
+            &[id_c, 99],
+            3 * second,
+            crate::purification::Protocol::Dejmps,
+            state,
+            1
+        ));
+        assert!(register.purified(
+            &[id_c, id_d, id_e],
+            3 * second,
+            crate::purification::Protocol::Dejmps,
+            state,
+            2
+        ));
+        assert_eq!(Some(2), register.rounds(id_c));
+        assert!(!register.contains(id_d));
+        assert!(!register.contains(id_e));
+        assert_exact(&register, id_c, 4 * second);
+
+        // Consume one end, the other one keeps decaying.
+        assert!(register.consume(id_c, 0, 4 * second).is_some());
+        assert_exact(&register, id_c, 5 * second);
     }
 }
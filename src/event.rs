@@ -34,6 +34,26 @@ pub struct EprFidelityData {
     pub index: usize,
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub struct CutoffExpiredData {
+    /// ID of the EPR pair.
+    pub epr_pair_id: u64,
+    /// ID of the timer.
+    pub timer_id: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PurificationOutcomeData {
+    /// ID of the master node of the logical link.
+    pub master_node_id: u32,
+    /// ID of the slave node of the logical link.
+    pub slave_node_id: u32,
+    /// ID of the EPR pair kept.
+    pub epr_pair_id: u64,
+    /// True if purification succeeded.
+    pub success: bool,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum NodeEventData {
     /// New EPR generated by a tx.
@@ -42,13 +62,35 @@ pub enum NodeEventData {
     EprNotified(EprNotifiedData),
     /// Measure fidelity of a given EPR pair.
     EprFidelity(EprFidelityData),
+    /// The memory cutoff timer of an EPR pair expired.
+    CutoffExpired(CutoffExpiredData),
+    /// The outcome of purification is known at both ends of a logical link.
+    PurificationOutcome(PurificationOutcomeData),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PhysicalLinkData {
+    /// ID of one end-point of the physical link.
+    pub u: u32,
+    /// ID of the other end-point of the physical link.
+    pub v: u32,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TopologyEventData {
+    /// The positions of the nodes must be updated.
+    Update,
+    /// A physical link has appeared.
+    LinkUp(PhysicalLinkData),
+    /// A physical link has disappeared.
+    LinkDown(PhysicalLinkData),
 }
 
 /// Every EPR request is uniquely identified by the five-tuple:
 /// - source node ID and port
 /// - target node ID and port
 /// - request ID
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub struct EprFiveTuple {
     /// Source node ID.
     pub source_node_id: u32,
@@ -112,6 +154,8 @@ pub enum EventType {
     /// Print progress.
     Progress(u16),
 
+    /// Physical topology-related event.
+    TopologyEvent(TopologyEventData),
     /// Node-related event.
     NodeEvent(NodeEventData),
     /// OS-related event.
@@ -0,0 +1,251 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+use crate::physical_topology::{NodeType, NodeWeight, StaticFidelities};
+
+/// Physical link crossed by one of the photons of an EPR pair, from the
+/// transmitter to a receiver.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hop {
+    /// Length of the link, in m.
+    pub distance: f64,
+    /// Elevation of the satellite seen from the node on the ground, in
+    /// degrees, only for free-space links between the ground and orbit.
+    pub elevation: Option<f64>,
+    /// Attenuation, in dB/km, only for fiber links.
+    pub fiber_attenuation: Option<f64>,
+}
+
+impl Hop {
+    /// Return the probability that a photon is not absorbed along the
+    /// link, which is always 1 for free-space links.
+    pub fn fiber_transmittance(&self) -> f64 {
+        match self.fiber_attenuation {
+            Some(attenuation) => 10.0_f64.powf(-attenuation * self.distance / 1e3 / 10.0),
+            None => 1.0,
+        }
+    }
+}
+
+/// Physical path followed by the two photons of an EPR pair.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhysicalPath {
+    /// Link from the transmitter to u, none if u is the transmitter.
+    pub to_u: Option<Hop>,
+    /// Link from the transmitter to v, none if v is the transmitter.
+    pub to_v: Option<Hop>,
+}
+
+impl PhysicalPath {
+    /// Return the number of physical links crossed.
+    pub fn hops(&self) -> u32 {
+        self.to_u.is_some() as u32 + self.to_v.is_some() as u32
+    }
+
+    /// Return the probability that both photons are not absorbed along
+    /// fiber links.
+    pub fn fiber_transmittance(&self) -> f64 {
+        self.to_u
+            .iter()
+            .chain(self.to_v.iter())
+            .map(|hop| hop.fiber_transmittance())
+            .product()
+    }
+
+    /// Return the total length of the physical links crossed, in m.
+    pub fn distance(&self) -> f64 {
+        self.to_u
+            .iter()
+            .chain(self.to_v.iter())
+            .map(|hop| hop.distance)
+            .sum()
+    }
+}
+
+/// Model of the initial fidelity of the EPR pairs generated.
+pub trait FidelityModel: std::fmt::Debug + Send {
+    /// Return the initial fidelity of the EPR pairs generated by the
+    /// transmitter `tx` towards the nodes `u` and `v` along `path`.
+    fn fidelity(&self, tx: &NodeWeight, u: &NodeWeight, v: &NodeWeight, path: &PhysicalPath)
+        -> f64;
+}
+
+impl Default for Box<dyn FidelityModel> {
+    fn default() -> Self {
+        Box::new(StaticFidelities::default())
+    }
+}
+
+/// Fixed values depending only on whether the generation is one or two
+/// hops and on the type of the receivers, where ground repeaters are
+/// considered as OGSs.
+impl FidelityModel for StaticFidelities {
+    fn fidelity(
+        &self,
+        _tx: &NodeWeight,
+        u: &NodeWeight,
+        v: &NodeWeight,
+        path: &PhysicalPath,
+    ) -> f64 {
+        if path.to_u.is_none() || path.to_v.is_none() {
+            let rx = if path.to_u.is_none() { v } else { u };
+            match rx.node_type {
+                NodeType::SAT => self.f_o,
+                NodeType::OGS | NodeType::GR => self.f_g,
+            }
+        } else {
+            match (u.node_type.on_ground(), v.node_type.on_ground()) {
+                (false, false) => self.f_oo,
+                (false, true) | (true, false) => self.f_og,
+                (true, true) => self.f_gg,
+            }
+        }
+    }
+}
+
+/// Fidelity decaying exponentially towards that of a maximally mixed state
+/// with the total distance travelled by the photons.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ExponentialFidelity {
+    /// Fidelity of the EPR pairs at vanishing distance.
+    pub f0: f64,
+    /// Distance after which the fidelity in excess of 0.25 is reduced by
+    /// a factor 1/e, in m.
+    pub decay_length: f64,
+}
+
+impl Default for ExponentialFidelity {
+    fn default() -> Self {
+        Self {
+            f0: 1.0,
+            decay_length: 1e6,
+        }
+    }
+}
+
+impl ExponentialFidelity {
+    pub fn valid(&self) -> anyhow::Result<()> {
+        let mut errors = vec![];
+        if !(0.25..=1.0).contains(&self.f0) {
+            errors.push(format!("invalid fidelity at zero distance ({})", self.f0));
+        }
+        if self.decay_length <= 0.0 {
+            errors.push(format!("decay length ({}) <= 0", self.decay_length));
+        }
+        if !errors.is_empty() {
+            anyhow::bail!(
+                "invalid exponential fidelity parameters: {}",
+                errors.join(",")
+            )
+        }
+        Ok(())
+    }
+}
+
+impl FidelityModel for ExponentialFidelity {
+    fn fidelity(
+        &self,
+        _tx: &NodeWeight,
+        _u: &NodeWeight,
+        _v: &NodeWeight,
+        path: &PhysicalPath,
+    ) -> f64 {
+        0.25 + (self.f0 - 0.25) * (-path.distance() / self.decay_length).exp()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExponentialFidelity, FidelityModel, Hop, PhysicalPath};
+    use crate::physical_topology::{NodeWeight, StaticFidelities};
+
+    fn path(to_u: Option<f64>, to_v: Option<f64>) -> PhysicalPath {
+        let hop = |distance| Hop {
+            distance,
+            elevation: None,
+            fiber_attenuation: None,
+        };
+        PhysicalPath {
+            to_u: to_u.map(hop),
+            to_v: to_v.map(hop),
+        }
+    }
+
+    #[test]
+    fn test_fidelity_model_path() {
+        assert_eq!(1, path(None, Some(10.0)).hops());
+        assert_eq!(2, path(Some(5.0), Some(10.0)).hops());
+        assert_float_eq::assert_f64_near!(10.0, path(None, Some(10.0)).distance());
+        assert_float_eq::assert_f64_near!(15.0, path(Some(5.0), Some(10.0)).distance());
+        assert_float_eq::assert_f64_near!(1.0, path(Some(5.0), Some(10.0)).fiber_transmittance());
+
+        let fiber = PhysicalPath {
+            to_u: Some(Hop {
+                distance: 100e3,
+                elevation: None,
+                fiber_attenuation: Some(0.2),
+            }),
+            to_v: None,
+        };
+        assert_float_eq::assert_f64_near!(0.01, fiber.fiber_transmittance(), 8);
+    }
+
+    #[test]
+    fn test_fidelity_model_static() {
+        let fidelities = StaticFidelities {
+            f_o: 0.6,
+            f_g: 0.7,
+            f_oo: 0.8,
+            f_og: 0.9,
+            f_gg: 1.0,
+        };
+        let sat = NodeWeight::default_sat();
+        let ogs = NodeWeight::default_ogs();
+        let one_hop = path(None, Some(1.0));
+        let two_hops = path(Some(1.0), Some(1.0));
+
+        assert_eq!(0.6, fidelities.fidelity(&sat, &sat, &sat, &one_hop));
+        assert_eq!(0.7, fidelities.fidelity(&sat, &sat, &ogs, &one_hop));
+        assert_eq!(
+            0.7,
+            fidelities.fidelity(&sat, &ogs, &sat, &path(Some(1.0), None))
+        );
+        assert_eq!(0.8, fidelities.fidelity(&sat, &sat, &sat, &two_hops));
+        assert_eq!(0.9, fidelities.fidelity(&sat, &ogs, &sat, &two_hops));
+        assert_eq!(0.9, fidelities.fidelity(&sat, &sat, &ogs, &two_hops));
+        assert_eq!(1.0, fidelities.fidelity(&sat, &ogs, &ogs, &two_hops));
+    }
+
+    #[test]
+    fn test_fidelity_model_exponential() {
+        assert!(ExponentialFidelity::default().valid().is_ok());
+        assert!(ExponentialFidelity {
+            f0: 0.2,
+            ..Default::default()
+        }
+        .valid()
+        .is_err());
+        assert!(ExponentialFidelity {
+            decay_length: 0.0,
+            ..Default::default()
+        }
+        .valid()
+        .is_err());
+
+        let model = ExponentialFidelity {
+            f0: 0.95,
+            decay_length: 1000.0,
+        };
+        let sat = NodeWeight::default_sat();
+        let ogs = NodeWeight::default_ogs();
+        assert_float_eq::assert_f64_near!(
+            0.95,
+            model.fidelity(&sat, &sat, &ogs, &path(None, Some(0.0)))
+        );
+        assert_float_eq::assert_f64_near!(
+            0.25 + 0.7 / std::f64::consts::E,
+            model.fidelity(&sat, &ogs, &ogs, &path(Some(400.0), Some(600.0)))
+        );
+        assert!(model.fidelity(&sat, &ogs, &ogs, &path(Some(1e9), Some(1e9))) >= 0.25);
+    }
+}
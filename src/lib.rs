@@ -1,19 +1,33 @@
 // SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
 // SPDX-License-Identifier: MIT
 
+pub mod bell_diagonal;
 pub mod client;
 pub mod config;
+pub mod constellation;
+pub mod density_matrix;
 pub mod epr_register;
 pub mod event;
 pub mod event_queue;
+pub mod fidelity_model;
+pub mod link_budget;
 pub mod logical_topology;
 pub mod network;
 pub mod nic;
 pub mod node;
+pub mod os;
 pub mod output;
 pub mod physical_topology;
+pub mod purification;
+pub mod purification_policy;
+pub mod routing;
+pub mod signalling;
 pub mod simulation;
 #[cfg(test)]
 pub mod tests;
+pub mod timer;
+pub mod tle;
+pub mod topology_file;
+pub mod topology_search;
 pub mod user_config;
 pub mod utils;
@@ -0,0 +1,316 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+use crate::fidelity_model::{FidelityModel, Hop, PhysicalPath};
+use crate::physical_topology::{NodeType, NodeWeight};
+
+/// Free-space optical link budget of the photons emitted by an entangled
+/// photon source, on board of a satellite (downlinks and inter-satellite
+/// links) or on the ground (uplinks).
+///
+/// The transmittance of a link from the source to a receiver at distance L
+/// is the product of:
+///
+/// - diffraction: the Gaussian beam, with waist equal to the radius of the
+///   transmitter aperture, i.e., that of the satellites or of the OGSs
+///   depending on where the source is, spreads over a radius w(L) and only
+///   the fraction
+///   1 - exp(-2 a^2 / w(L)^2) is collected by a receiver aperture of radius a;
+/// - pointing: a random pointing error with given RMS per axis reduces the
+///   average power collected by 1 / (1 + 4 (sigma L / w(L))^2);
+/// - atmospheric extinction, only for ground links: the zenith
+///   transmittance is raised to the air mass 1 / sin(elevation);
+/// - detector efficiency.
+///
+/// The transmittance of a fiber link only accounts for its attenuation and
+/// the detector efficiency.
+///
+/// An EPR pair is established if both photons are detected. Dark counts
+/// falling within the coincidence window produce false coincidences,
+/// which are assumed to be in the maximally mixed state.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LinkBudgetParams {
+    /// Wavelength of the photons, in m.
+    pub wavelength: f64,
+    /// Diameter of the telescope aperture on satellites, in m.
+    pub sat_aperture: f64,
+    /// Diameter of the telescope aperture on OGSs, in m.
+    pub ogs_aperture: f64,
+    /// RMS pointing error per axis, in rad.
+    pub pointing_error: f64,
+    /// Atmospheric extinction at zenith, in dB.
+    pub zenith_extinction: f64,
+    /// Detector efficiency.
+    pub detector_efficiency: f64,
+    /// Dark count rate of the detectors, in Hz.
+    pub dark_count_rate: f64,
+    /// Coincidence window, in s.
+    pub coincidence_window: f64,
+    /// Fidelity of the EPR pairs emitted by the source.
+    pub source_fidelity: f64,
+}
+
+impl Default for LinkBudgetParams {
+    fn default() -> Self {
+        Self {
+            wavelength: 810e-9,
+            sat_aperture: 0.2,
+            ogs_aperture: 1.0,
+            pointing_error: 1e-6,
+            zenith_extinction: 0.5,
+            detector_efficiency: 0.8,
+            dark_count_rate: 100.0,
+            coincidence_window: 1e-9,
+            source_fidelity: 0.99,
+        }
+    }
+}
+
+impl LinkBudgetParams {
+    pub fn valid(&self) -> anyhow::Result<()> {
+        let mut errors = vec![];
+        for (value, name) in [
+            (self.wavelength, "wavelength"),
+            (self.sat_aperture, "satellite aperture"),
+            (self.ogs_aperture, "OGS aperture"),
+        ] {
+            if value <= 0.0 {
+                errors.push(format!("{} ({}) <= 0", name, value));
+            }
+        }
+        for (value, name) in [
+            (self.pointing_error, "pointing error"),
+            (self.zenith_extinction, "zenith extinction"),
+            (self.dark_count_rate, "dark count rate"),
+            (self.coincidence_window, "coincidence window"),
+        ] {
+            if value < 0.0 {
+                errors.push(format!("{} ({}) < 0", name, value));
+            }
+        }
+        if self.detector_efficiency <= 0.0 || self.detector_efficiency > 1.0 {
+            errors.push(format!(
+                "invalid detector efficiency ({})",
+                self.detector_efficiency
+            ));
+        }
+        if !(0.25..=1.0).contains(&self.source_fidelity) {
+            errors.push(format!(
+                "invalid source fidelity ({})",
+                self.source_fidelity
+            ));
+        }
+        if !errors.is_empty() {
+            anyhow::bail!("invalid link budget parameters: {}", errors.join(","))
+        }
+        Ok(())
+    }
+
+    /// Return the probability that a photon emitted by a transmitter is
+    /// detected by a receiver.
+    ///
+    /// Parameters:
+    /// - `distance`: distance between the transmitter and the receiver, in m.
+    /// - `tx_aperture`: diameter of the transmitter aperture, in m.
+    /// - `rx_aperture`: diameter of the receiver aperture, in m.
+    /// - `elevation`: elevation of the satellite seen from the ground end,
+    ///   in degrees, if this is a ground link.
+    pub fn transmittance(
+        &self,
+        distance: f64,
+        tx_aperture: f64,
+        rx_aperture: f64,
+        elevation: Option<f64>,
+    ) -> f64 {
+        let waist = tx_aperture / 2.0;
+        let rayleigh_range = std::f64::consts::PI * waist * waist / self.wavelength;
+        let beam_radius = waist * (1.0 + (distance / rayleigh_range).powi(2)).sqrt();
+        let rx_radius = rx_aperture / 2.0;
+
+        let diffraction = 1.0 - (-2.0 * (rx_radius / beam_radius).powi(2)).exp();
+        let pointing = 1.0 / (1.0 + 4.0 * (self.pointing_error * distance / beam_radius).powi(2));
+        let atmosphere = match elevation {
+            Some(elevation) => 10.0_f64.powf(
+                -self.zenith_extinction / 10.0 / elevation.to_radians().sin().max(f64::EPSILON),
+            ),
+            None => 1.0,
+        };
+        diffraction * pointing * atmosphere * self.detector_efficiency
+    }
+
+    /// Return the diameter of the telescope aperture of a node, in m.
+    pub fn aperture(&self, node: &NodeWeight) -> f64 {
+        match node.node_type {
+            NodeType::SAT => self.sat_aperture,
+            NodeType::OGS | NodeType::GR => self.ogs_aperture,
+        }
+    }
+
+    /// Return the probability that a photon emitted by the transmitter `tx`
+    /// is detected by the receiver `rx` after crossing the given physical
+    /// link, if any.
+    pub fn hop_transmittance(&self, tx: &NodeWeight, rx: &NodeWeight, hop: Option<&Hop>) -> f64 {
+        match hop {
+            Some(hop) if hop.fiber_attenuation.is_some() => {
+                hop.fiber_transmittance() * self.detector_efficiency
+            }
+            Some(hop) => self.transmittance(
+                hop.distance,
+                self.aperture(tx),
+                self.aperture(rx),
+                hop.elevation,
+            ),
+            None => self.detector_efficiency,
+        }
+    }
+
+    /// Return the probability of a false coincidence due to dark counts
+    /// for every pair emitted, given the transmittances of the two photons.
+    fn accidental_probability(&self, eta_u: f64, eta_v: f64) -> f64 {
+        let dark = self.dark_count_rate * self.coincidence_window;
+        dark * dark + eta_u * dark * (1.0 - eta_v) + eta_v * dark * (1.0 - eta_u)
+    }
+
+    /// Return the probability that an EPR pair is established for every
+    /// pair emitted, given the transmittances of the two photons.
+    pub fn success_probability(&self, eta_u: f64, eta_v: f64) -> f64 {
+        eta_u * eta_v + self.accidental_probability(eta_u, eta_v)
+    }
+
+    /// Return the fidelity of the EPR pairs established, given the
+    /// transmittances of the two photons.
+    pub fn pair_fidelity(&self, eta_u: f64, eta_v: f64) -> f64 {
+        let signal = eta_u * eta_v;
+        let accidental = self.accidental_probability(eta_u, eta_v);
+        if signal + accidental == 0.0 {
+            return 0.25;
+        }
+        (signal * self.source_fidelity + accidental * 0.25) / (signal + accidental)
+    }
+}
+
+impl FidelityModel for LinkBudgetParams {
+    fn fidelity(
+        &self,
+        tx: &NodeWeight,
+        u: &NodeWeight,
+        v: &NodeWeight,
+        path: &PhysicalPath,
+    ) -> f64 {
+        self.pair_fidelity(
+            self.hop_transmittance(tx, u, path.to_u.as_ref()),
+            self.hop_transmittance(tx, v, path.to_v.as_ref()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinkBudgetParams;
+    use crate::fidelity_model::Hop;
+    use crate::physical_topology::NodeWeight;
+
+    #[test]
+    fn test_link_budget_params() {
+        assert!(LinkBudgetParams::default().valid().is_ok());
+        for params in [
+            LinkBudgetParams {
+                wavelength: 0.0,
+                ..Default::default()
+            },
+            LinkBudgetParams {
+                pointing_error: -1.0,
+                ..Default::default()
+            },
+            LinkBudgetParams {
+                detector_efficiency: 1.1,
+                ..Default::default()
+            },
+            LinkBudgetParams {
+                source_fidelity: 0.1,
+                ..Default::default()
+            },
+        ] {
+            assert!(params.valid().is_err());
+        }
+    }
+
+    #[test]
+    fn test_link_budget_transmittance() {
+        let params = LinkBudgetParams {
+            pointing_error: 0.0,
+            zenith_extinction: 0.0,
+            detector_efficiency: 1.0,
+            ..Default::default()
+        };
+
+        // Short distance: all the light is collected by a large aperture.
+        assert!(params.transmittance(1.0, 0.2, 1.0, None) > 0.999);
+
+        // Far field: the transmittance decreases with the square of the
+        // distance and increases with the square of the aperture.
+        let eta = params.transmittance(1000e3, 0.2, 0.2, None);
+        assert!(eta > 1e-4 && eta < 1e-2, "{}", eta);
+        assert!((eta / params.transmittance(2000e3, 0.2, 0.2, None) - 4.0).abs() < 0.1);
+        assert!((params.transmittance(1000e3, 0.2, 0.1, None) / eta - 0.25).abs() < 0.01);
+
+        // Pointing errors, atmosphere and detectors only reduce it.
+        let lossy = LinkBudgetParams::default();
+        assert!(lossy.transmittance(1000e3, 0.2, 0.2, None) < eta);
+        let zenith = lossy.transmittance(1000e3, 0.2, 0.2, Some(90.0));
+        let low = lossy.transmittance(1000e3, 0.2, 0.2, Some(20.0));
+        assert!(low < zenith);
+        assert_float_eq::assert_f64_near!(
+            10.0_f64.powf(-0.05),
+            zenith / lossy.transmittance(1000e3, 0.2, 0.2, None),
+            8
+        );
+    }
+
+    #[test]
+    fn test_link_budget_uplink_downlink() {
+        let params = LinkBudgetParams::default();
+        let sat = NodeWeight::default_sat();
+        let ogs = NodeWeight::default_ogs();
+        let hop = Hop {
+            distance: 1000e3,
+            elevation: Some(60.0),
+            fiber_attenuation: None,
+        };
+
+        // The transmitter and receiver apertures depend on the node types.
+        let downlink = params.hop_transmittance(&sat, &ogs, Some(&hop));
+        let uplink = params.hop_transmittance(&ogs, &sat, Some(&hop));
+        assert_float_eq::assert_f64_near!(
+            params.transmittance(1000e3, 0.2, 1.0, Some(60.0)),
+            downlink
+        );
+        assert_float_eq::assert_f64_near!(
+            params.transmittance(1000e3, 1.0, 0.2, Some(60.0)),
+            uplink
+        );
+
+        // With the default parameters, uplinks are lossier than downlinks.
+        assert!(uplink < downlink);
+    }
+
+    #[test]
+    fn test_link_budget_fidelity() {
+        let params = LinkBudgetParams::default();
+        assert_float_eq::assert_f64_near!(0.99, params.pair_fidelity(1.0, 1.0), 1_000_000);
+
+        // The fidelity decreases as dark counts become comparable with the
+        // photons received.
+        let mut prev = params.pair_fidelity(1e-1, 1e-1);
+        for eta in [1e-2, 1e-3, 1e-4, 1e-5, 1e-6] {
+            let fidelity = params.pair_fidelity(eta, eta);
+            assert!(fidelity < prev && fidelity >= 0.25);
+            prev = fidelity;
+        }
+        assert_float_eq::assert_f64_near!(0.25, params.pair_fidelity(0.0, 0.0));
+
+        // The success probability is dominated by the coincidences.
+        let p = params.success_probability(1e-2, 1e-3);
+        assert!((p - 1e-5).abs() < 1e-8);
+    }
+}
@@ -2,13 +2,104 @@
 // SPDX-License-Identifier: MIT
 
 use petgraph::visit::{EdgeRef, IntoNodeReferences};
-use shuffle::shuffler::Shuffler;
+use rand::seq::SliceRandom;
 
 const NEGLIGIBLE_AMOUNT: f64 = 1e-5;
 
+/// Weight of the logical edges not crossed by any demand, relative to the
+/// maximum weight, so that they retain some capacity and memory qubits.
+const MIN_DEMAND_WEIGHT: f64 = 0.01;
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum PhysicalToLogicalPolicy {
     RandomGreedy,
+    /// Maximize the minimum capacity between any two OGS nodes, by solving
+    /// a mixed-integer linear program.
+    MaxMinCapacity,
+    /// Reserve logical edges along the shortest physical paths between
+    /// OGS nodes, starting from the closest ones, without randomness.
+    ShortestPath,
+    /// Search the possible logical edges with simulated annealing.
+    SimulatedAnnealing,
+    /// Search the possible logical edges with a genetic algorithm.
+    Genetic,
+}
+
+/// Rate of end-to-end EPR pairs requested between two OGS nodes.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Demand {
+    /// Index of the source OGS node.
+    pub source: u32,
+    /// Index of the target OGS node.
+    pub target: u32,
+    /// Rate of EPR pairs, in Hz.
+    pub rate: f64,
+}
+
+/// Allocation of the capacity of the transmitters and of the memory qubits
+/// of the nodes to the logical edges, once these have been selected.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Allocation {
+    /// Leave the allocation of the physical-to-logical policy, which in
+    /// general divides evenly the capacity of every transmitter and
+    /// assigns memory qubits round-robin.
+    #[default]
+    Even,
+    /// Allocate resources based on the traffic demands between OGS nodes,
+    /// each following the logical path used for routing: the capacity of
+    /// every transmitter is divided in proportion to the number of demands
+    /// crossing its logical edges, the memory qubits in proportion to the
+    /// sum of the products of the rate of the demands crossing the logical
+    /// edges by their end-to-end latency.
+    Demand(Vec<Demand>),
+}
+
+impl Allocation {
+    /// Return the name of the allocation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Allocation::Even => "Even",
+            Allocation::Demand(_) => "Demand",
+        }
+    }
+
+    /// Return the number of traffic demands.
+    pub fn num_demands(&self) -> usize {
+        match self {
+            Allocation::Even => 0,
+            Allocation::Demand(demands) => demands.len(),
+        }
+    }
+
+    pub fn valid(
+        &self,
+        physical_topology: &crate::physical_topology::PhysicalTopology,
+    ) -> anyhow::Result<()> {
+        let mut errors = vec![];
+        if let Allocation::Demand(demands) = self {
+            let ogs_nodes = physical_topology.ogs_indices();
+            for demand in demands {
+                for ndx in [demand.source, demand.target] {
+                    if !ogs_nodes.contains(&ndx) {
+                        errors.push(format!("demand end-point {} is not an OGS", ndx));
+                    }
+                }
+                if demand.source == demand.target {
+                    errors.push(format!("demand from {} to itself", demand.source));
+                }
+                if !demand.rate.is_finite() || demand.rate < 0.0 {
+                    errors.push(format!(
+                        "invalid rate of demand {}->{} ({})",
+                        demand.source, demand.target, demand.rate
+                    ));
+                }
+            }
+        }
+        if !errors.is_empty() {
+            anyhow::bail!("invalid allocation: {}", errors.join(","))
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialOrd, PartialEq)]
@@ -75,7 +166,7 @@ impl std::ops::Add for EdgeWeight {
     }
 }
 
-type Graph = petgraph::Graph<NodeWeight, EdgeWeight, petgraph::Directed, u32>;
+pub(crate) type Graph = petgraph::Graph<NodeWeight, EdgeWeight, petgraph::Directed, u32>;
 type Paths = std::collections::HashMap<
     u32,
     petgraph::algo::bellman_ford::Paths<petgraph::graph::NodeIndex, EdgeWeight>,
@@ -93,6 +184,7 @@ type Paths = std::collections::HashMap<
 pub struct LogicalTopology {
     graph: Graph,
     paths: Paths,
+    convergence: crate::topology_search::Convergence,
 }
 
 impl LogicalTopology {
@@ -100,23 +192,187 @@ impl LogicalTopology {
         &self.graph
     }
 
+    /// Return the fitness of the current and best solutions at every
+    /// iteration, if found by a metaheuristic search.
+    pub fn convergence(&self) -> &crate::topology_search::Convergence {
+        &self.convergence
+    }
+
+    /// Create a logical topology from a physical one with a given policy,
+    /// then allocate the resources to the logical edges.
+    ///
+    /// The search parameters are only used by metaheuristic policies, which
+    /// draw from their own pseudo-random number generator if a seed is
+    /// specified, otherwise from `rng`.
     pub fn from_physical_topology(
         policy: &PhysicalToLogicalPolicy,
+        search_params: &crate::topology_search::SearchParams,
+        allocation: &Allocation,
         physical_topology: &crate::physical_topology::PhysicalTopology,
         rng: &mut rand::rngs::StdRng,
     ) -> anyhow::Result<Self> {
-        let graph = match policy {
-            PhysicalToLogicalPolicy::RandomGreedy => {
-                physical_to_logical_random_greedy(physical_topology, rng)?
+        allocation.valid(physical_topology)?;
+        let mut search_rng = search_params
+            .search_seed
+            .map(<rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64);
+        let search_rng = search_rng.as_mut().unwrap_or(rng);
+        let (graph, convergence) = match policy {
+            PhysicalToLogicalPolicy::RandomGreedy => (
+                physical_to_logical_random_greedy(physical_topology, rng)?,
+                vec![],
+            ),
+            PhysicalToLogicalPolicy::MaxMinCapacity => (
+                physical_to_logical_max_min_capacity(physical_topology)?,
+                vec![],
+            ),
+            PhysicalToLogicalPolicy::ShortestPath => (
+                physical_to_logical_shortest_path(physical_topology)?,
+                vec![],
+            ),
+            PhysicalToLogicalPolicy::SimulatedAnnealing => {
+                crate::topology_search::simulated_annealing(
+                    physical_topology,
+                    &search_params.fitness,
+                    search_params.iterations,
+                    search_rng,
+                )?
             }
+            PhysicalToLogicalPolicy::Genetic => crate::topology_search::genetic(
+                physical_topology,
+                &search_params.fitness,
+                search_params.iterations,
+                search_rng,
+            )?,
         };
+        let mut logical_topology = Self::from_graph(graph)?;
+        logical_topology.convergence = convergence;
+        if let Allocation::Demand(demands) = allocation {
+            logical_topology.allocate_by_demand(physical_topology, demands)?;
+        }
+        Ok(logical_topology)
+    }
+
+    /// Create a logical topology from a given graph.
+    pub(crate) fn from_graph(graph: Graph) -> anyhow::Result<Self> {
         let paths = find_paths(&graph)?;
-        Ok(Self { graph, paths })
+        Ok(Self {
+            graph,
+            paths,
+            convergence: vec![],
+        })
+    }
+
+    /// Allocate the capacity of the transmitters and the memory qubits of
+    /// the nodes based on the given traffic demands, see `Allocation::Demand`.
+    ///
+    /// The latency of a demand is the time for light to travel along the
+    /// shortest physical paths between the end-points of its logical edges.
+    fn allocate_by_demand(
+        &mut self,
+        physical_topology: &crate::physical_topology::PhysicalTopology,
+        demands: &[Demand],
+    ) -> anyhow::Result<()> {
+        let mut num_crossing = vec![0.0; self.graph.edge_count()];
+        let mut rate_latency = vec![0.0; self.graph.edge_count()];
+        for demand in demands {
+            let path = match self.path(demand.source, demand.target) {
+                Some(path) => path,
+                None => anyhow::bail!(
+                    "no logical path for demand {}->{}",
+                    demand.source,
+                    demand.target
+                ),
+            };
+            let mut edges = vec![];
+            let mut latency = 0.0;
+            for hop in path.windows(2) {
+                edges.push(self.graph.find_edge(hop[0].into(), hop[1].into()).unwrap());
+                latency += physical_topology.shortest_path(hop[0], hop[1])?.1
+                    / crate::signalling::SPEED_OF_LIGHT;
+            }
+            for edge in edges {
+                num_crossing[edge.index()] += 1.0;
+                rate_latency[edge.index()] += demand.rate * latency;
+            }
+        }
+
+        // Divide the capacity of every transmitter between its logical edges.
+        let capacity_weights = demand_weights(&num_crossing);
+        for (u, w) in physical_topology.graph().node_references() {
+            let u_ndx = u.index() as u32;
+            let served = self
+                .graph
+                .edge_references()
+                .filter(|e| e.weight().tx == u_ndx)
+                .map(|e| e.id())
+                .collect::<Vec<_>>();
+            let total: f64 = served.iter().map(|e| capacity_weights[e.index()]).sum();
+            for e in served {
+                self.graph[e].capacity = w.capacity * capacity_weights[e.index()] / total;
+            }
+        }
+
+        // Assign the memory qubits one at a time, starting from one per
+        // logical edge, to the logical edge with the highest ratio between
+        // its weight and the memory qubits already assigned.
+        let memory_weights = demand_weights(&rate_latency);
+        let mut residual_memory_qubits = physical_topology
+            .graph()
+            .node_weights()
+            .map(|w| w.memory_qubits)
+            .collect::<Vec<_>>();
+        for e in self.graph.edge_references() {
+            residual_memory_qubits[e.source().index()] -= 1;
+            residual_memory_qubits[e.target().index()] -= 1;
+        }
+        for e in self.graph.edge_weights_mut() {
+            e.memory_qubits = 1;
+        }
+        while let Some(e) = self
+            .graph
+            .edge_references()
+            .filter(|e| {
+                residual_memory_qubits[e.source().index()] > 0
+                    && residual_memory_qubits[e.target().index()] > 0
+            })
+            .map(|e| {
+                (
+                    e.id(),
+                    memory_weights[e.id().index()] / e.weight().memory_qubits as f64,
+                )
+            })
+            .reduce(|best, cur| if cur.1 > best.1 { cur } else { best })
+            .map(|(e, _)| e)
+        {
+            let (u, v) = self.graph.edge_endpoints(e).unwrap();
+            residual_memory_qubits[u.index()] -= 1;
+            residual_memory_qubits[v.index()] -= 1;
+            self.graph[e].memory_qubits += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Return the shortest path from `source` to `target`, including both
+    /// end-points, or None if `target` cannot be reached from `source`.
+    pub fn path(&self, source: u32, target: u32) -> Option<Vec<u32>> {
+        let paths = self.paths.get(&source)?;
+        if source == target || target as usize >= paths.predecessors.len() {
+            return None;
+        }
+        let mut ret = vec![target];
+        let mut cur = target;
+        while cur != source {
+            cur = paths.predecessors[cur as usize]?.index() as u32;
+            ret.push(cur);
+        }
+        ret.reverse();
+        Some(ret)
     }
 }
 
 #[derive(Debug, Default, Clone)]
-struct LogicalEdge {
+pub(crate) struct LogicalEdge {
     pub tx: u32,
     pub master: u32,
     pub slave: u32,
@@ -144,13 +400,12 @@ macro_rules! weight {
     };
 }
 
-fn physical_to_logical_random_greedy(
+pub(crate) fn physical_to_logical_random_greedy(
     physical_topology: &crate::physical_topology::PhysicalTopology,
     rng: &mut rand::rngs::StdRng,
 ) -> anyhow::Result<Graph> {
     let mut possible_logical_edges = find_possible_logical_edges(physical_topology);
-    let mut irs = shuffle::irs::Irs::default();
-    let _ = irs.shuffle(&mut possible_logical_edges, rng);
+    possible_logical_edges.shuffle(rng);
 
     let mut physical_graph = physical_topology.graph().clone();
 
@@ -228,29 +483,34 @@ fn physical_to_logical_random_greedy(
     for edge in logical_graph.edge_references() {
         candidate_edges.push((edge.source(), edge.target()));
     }
-    let mut irs = shuffle::irs::Irs::default();
-    let _ = irs.shuffle(&mut candidate_edges, rng);
+    candidate_edges.shuffle(rng);
+
+    let mut residual_memory_qubits = physical_graph
+        .node_weights()
+        .map(|w| w.memory_qubits)
+        .collect::<Vec<_>>();
+    assign_residual_memory_qubits(
+        &mut logical_graph,
+        &mut residual_memory_qubits,
+        candidate_edges,
+    );
 
-    while !candidate_edges.is_empty() {
-        let mut candidate_edges_new = vec![];
-        while let Some((u, v)) = candidate_edges.pop() {
-            if physical_graph.node_weight(u).unwrap().memory_qubits > 0
-                && physical_graph.node_weight(v).unwrap().memory_qubits > 0
-            {
-                logical_graph
-                    .edge_weight_mut(logical_graph.find_edge(u, v).unwrap())
-                    .unwrap()
-                    .memory_qubits += 1;
-                physical_graph.node_weight_mut(u).unwrap().memory_qubits -= 1;
-                physical_graph.node_weight_mut(v).unwrap().memory_qubits -= 1;
-                candidate_edges_new.push((u, v));
-            }
-        }
-        std::mem::swap(&mut candidate_edges, &mut candidate_edges_new);
-    }
+    divide_capacity_evenly(&mut logical_graph, &physical_graph);
+
+    Ok(logical_graph)
+}
 
-    // Assign logical edge capacities, by dividing evenly for each node
-    // between the number of logical edges crossing that node.
+/// Assign logical edge capacities, by dividing evenly for each node
+/// between the number of logical edges crossing that node.
+fn divide_capacity_evenly(
+    logical_graph: &mut Graph,
+    physical_graph: &petgraph::Graph<
+        crate::physical_topology::NodeWeight,
+        crate::physical_topology::EdgeWeight,
+        petgraph::Undirected,
+        u32,
+    >,
+) {
     for (u, w) in physical_graph.node_references() {
         let u_ndx = u.index() as u32;
 
@@ -275,6 +535,452 @@ fn physical_to_logical_random_greedy(
             }
         }
     }
+}
+
+/// Reserve the resources for the logical edges in both directions between
+/// `u` and `v`, with EPR pairs generated by `tx`, unless already present.
+///
+/// Return the logical edges to be added, or None if the residual resources
+/// of the physical nodes are not sufficient, in which case they are not
+/// modified.
+fn reserve_logical_edges(
+    logical_graph: &Graph,
+    residual: &mut [crate::physical_topology::NodeWeight],
+    tx: u32,
+    u: u32,
+    v: u32,
+) -> Option<Vec<LogicalEdge>> {
+    let needed = [(u, v), (v, u)]
+        .into_iter()
+        .filter(|(master, slave)| {
+            logical_graph
+                .find_edge((*master).into(), (*slave).into())
+                .is_none()
+        })
+        .map(|(master, slave)| LogicalEdge { tx, master, slave })
+        .collect::<Vec<_>>();
+    let num_needed = needed.len() as u32;
+    if num_needed == 0 {
+        return Some(needed);
+    }
+
+    let (tx_ndx, u_ndx, v_ndx) = (tx as usize, u as usize, v as usize);
+    if residual[tx_ndx].transmitters < num_needed
+        || residual[tx_ndx].capacity <= 0.0
+        || [u_ndx, v_ndx].iter().any(|ndx| {
+            residual[*ndx].detectors < num_needed || residual[*ndx].memory_qubits < num_needed
+        })
+    {
+        return None;
+    }
+
+    residual[tx_ndx].transmitters -= num_needed;
+    for ndx in [u_ndx, v_ndx] {
+        residual[ndx].detectors -= num_needed;
+        residual[ndx].memory_qubits -= num_needed;
+    }
+    Some(needed)
+}
+
+/// Build a logical topology by reserving logical edges, in both directions,
+/// along the shortest physical path between every pair of OGS nodes, in
+/// increasing order of distance. Along a path, a logical edge spans two
+/// physical hops if the node in between can generate the EPR pairs,
+/// otherwise it spans a single hop with either end-point as transmitter.
+/// The pairs whose path cannot be fully reserved are skipped.
+///
+/// Afterwards, the leftover transmitters are used to add more logical edges
+/// between neighbors, the capacity of transmitters is divided evenly between
+/// their logical edges and the residual memory qubits are assigned as
+/// possible.
+pub(crate) fn physical_to_logical_shortest_path(
+    physical_topology: &crate::physical_topology::PhysicalTopology,
+) -> anyhow::Result<Graph> {
+    let physical_graph = physical_topology.graph();
+    let mut residual = physical_graph.node_weights().cloned().collect::<Vec<_>>();
+
+    let mut logical_graph = Graph::new();
+
+    // Add all nodes from the physical topology.
+    for _ in 0..physical_graph.node_count() {
+        logical_graph.add_node(NodeWeight {});
+    }
+
+    // Sort the shortest paths between OGS nodes by increasing distance.
+    let ogs_nodes = physical_topology.ogs_indices();
+    let mut paths = vec![];
+    for (i, u) in ogs_nodes.iter().enumerate() {
+        for v in ogs_nodes.iter().skip(i + 1) {
+            if let Ok(path) = physical_topology.shortest_path(*u, *v) {
+                paths.push(path);
+            }
+        }
+    }
+    paths.sort_by(|lhs, rhs| lhs.1.total_cmp(&rhs.1));
+
+    for (path, _distance) in paths {
+        let mut tentative = residual.clone();
+        let mut new_edges = vec![];
+        let mut i = 0;
+        while i + 1 < path.len() {
+            let mut options = vec![];
+            if i + 2 < path.len() {
+                options.push((path[i + 1], path[i], path[i + 2], 2));
+            }
+            options.push((path[i], path[i], path[i + 1], 1));
+            options.push((path[i + 1], path[i], path[i + 1], 1));
+            match options.into_iter().find_map(|(tx, u, v, step)| {
+                reserve_logical_edges(&logical_graph, &mut tentative, tx, u, v)
+                    .map(|edges| (edges, step))
+            }) {
+                Some((edges, step)) => {
+                    new_edges.extend(edges);
+                    i += step;
+                }
+                None => break,
+            }
+        }
+        if i + 1 < path.len() {
+            continue;
+        }
+
+        residual = tentative;
+        for logical_edge in new_edges {
+            logical_graph.add_edge(
+                logical_edge.master.into(),
+                logical_edge.slave.into(),
+                EdgeWeight {
+                    tx: logical_edge.tx,
+                    memory_qubits: 1,
+                    capacity: 0.0,
+                    cost: 1,
+                },
+            );
+        }
+    }
+
+    anyhow::ensure!(
+        reachable(&logical_graph, &ogs_nodes),
+        "could not find a logical topology for the given physical topology"
+    );
+
+    // Use the leftover transmitters, in order of node identifiers.
+    let mut possible_logical_edges = find_possible_logical_edges(physical_topology);
+    possible_logical_edges.sort_by_key(|e| (e.tx, e.master, e.slave));
+    for logical_edge in possible_logical_edges {
+        add_logical_edge_if_possible(&mut logical_graph, &mut residual, &logical_edge);
+    }
+
+    assign_capacity_and_memory(&mut logical_graph, physical_graph, &residual);
+
+    Ok(logical_graph)
+}
+
+/// Add the given logical edge, with one memory qubit, if not already
+/// present and if the residual resources of the physical nodes allow.
+///
+/// Return true if the logical edge has been added.
+pub(crate) fn add_logical_edge_if_possible(
+    logical_graph: &mut Graph,
+    residual: &mut [crate::physical_topology::NodeWeight],
+    logical_edge: &LogicalEdge,
+) -> bool {
+    let (tx, master, slave) = (
+        logical_edge.tx as usize,
+        logical_edge.master as usize,
+        logical_edge.slave as usize,
+    );
+    if logical_graph
+        .find_edge(logical_edge.master.into(), logical_edge.slave.into())
+        .is_some()
+        || residual[tx].transmitters == 0
+        || residual[tx].capacity <= 0.0
+        || [master, slave]
+            .iter()
+            .any(|ndx| residual[*ndx].detectors == 0 || residual[*ndx].memory_qubits == 0)
+    {
+        return false;
+    }
+    residual[tx].transmitters -= 1;
+    for ndx in [master, slave] {
+        residual[ndx].detectors -= 1;
+        residual[ndx].memory_qubits -= 1;
+    }
+    logical_graph.add_edge(
+        logical_edge.master.into(),
+        logical_edge.slave.into(),
+        EdgeWeight {
+            tx: logical_edge.tx,
+            memory_qubits: 1,
+            capacity: 0.0,
+            cost: 1,
+        },
+    );
+    true
+}
+
+/// Divide evenly the capacity of transmitters between their logical edges
+/// and assign the residual memory qubits, in the order in which the logical
+/// edges have been added.
+pub(crate) fn assign_capacity_and_memory(
+    logical_graph: &mut Graph,
+    physical_graph: &petgraph::Graph<
+        crate::physical_topology::NodeWeight,
+        crate::physical_topology::EdgeWeight,
+        petgraph::Undirected,
+        u32,
+    >,
+    residual: &[crate::physical_topology::NodeWeight],
+) {
+    divide_capacity_evenly(logical_graph, physical_graph);
+
+    let mut candidate_edges = logical_graph
+        .edge_references()
+        .map(|e| (e.source(), e.target()))
+        .collect::<Vec<_>>();
+    candidate_edges.reverse();
+    let mut residual_memory_qubits = residual.iter().map(|w| w.memory_qubits).collect::<Vec<_>>();
+    assign_residual_memory_qubits(logical_graph, &mut residual_memory_qubits, candidate_edges);
+}
+
+/// Return the weights of the logical edges given the values due to the
+/// demands crossing them, with a minimum relative to the maximum value.
+fn demand_weights(values: &[f64]) -> Vec<f64> {
+    let max_value = values.iter().cloned().fold(0.0, f64::max);
+    let min_weight = if max_value > 0.0 {
+        MIN_DEMAND_WEIGHT * max_value
+    } else {
+        1.0
+    };
+    values.iter().map(|value| value.max(min_weight)).collect()
+}
+
+/// Assign the residual memory qubits of the nodes to the given logical
+/// edges, one at a time, in reverse order and round-robin.
+fn assign_residual_memory_qubits(
+    logical_graph: &mut Graph,
+    residual_memory_qubits: &mut [u32],
+    mut candidate_edges: Vec<(petgraph::graph::NodeIndex, petgraph::graph::NodeIndex)>,
+) {
+    while !candidate_edges.is_empty() {
+        let mut candidate_edges_new = vec![];
+        while let Some((u, v)) = candidate_edges.pop() {
+            if residual_memory_qubits[u.index()] > 0 && residual_memory_qubits[v.index()] > 0 {
+                logical_graph
+                    .edge_weight_mut(logical_graph.find_edge(u, v).unwrap())
+                    .unwrap()
+                    .memory_qubits += 1;
+                residual_memory_qubits[u.index()] -= 1;
+                residual_memory_qubits[v.index()] -= 1;
+                candidate_edges_new.push((u, v));
+            }
+        }
+        std::mem::swap(&mut candidate_edges, &mut candidate_edges_new);
+    }
+}
+
+/// Find the logical topology that maximizes the minimum capacity between
+/// any two OGS nodes, subject to the constraints checked by `is_valid`.
+///
+/// The capacity between two OGS nodes is the maximum flow of EPR pairs
+/// that can be established along the logical edges from one to the other,
+/// where the rate of a logical edge is the capacity assigned to it times
+/// the success probability of the EPR pairs generated. It is sufficient
+/// to consider the flows from and to one OGS node, since any cut that
+/// separates two OGS nodes also separates one of them from this one.
+///
+/// The problem is solved as a mixed-integer linear program, with one
+/// binary variable for every possible logical edge. Afterwards, the
+/// residual capacity of transmitters is divided evenly between their
+/// logical edges and the residual memory qubits are assigned as possible.
+fn physical_to_logical_max_min_capacity(
+    physical_topology: &crate::physical_topology::PhysicalTopology,
+) -> anyhow::Result<Graph> {
+    let physical_graph = physical_topology.graph();
+    let num_nodes = physical_graph.node_count();
+    let ogs_nodes = physical_topology.ogs_indices();
+    let possible_logical_edges = find_possible_logical_edges(physical_topology);
+    let node_weight = |u: u32| physical_graph.node_weight(u.into()).unwrap();
+
+    let mut problem = microlp::Problem::new(microlp::OptimizationDirection::Maximize);
+
+    // The minimum capacity cannot exceed the sum of all the capacities.
+    let total_capacity = physical_graph.node_weights().map(|w| w.capacity).sum();
+    let min_capacity = problem.add_var(1.0, (0.0, total_capacity));
+
+    // Decide whether to use each logical edge and with which capacity.
+    let mut used = vec![];
+    let mut capacity = vec![];
+    for logical_edge in &possible_logical_edges {
+        let tx_capacity = node_weight(logical_edge.tx).capacity;
+        let x = problem.add_binary_var(0.0);
+        let c = problem.add_var(0.0, (0.0, tx_capacity));
+        problem.add_constraint(
+            [(c, 1.0), (x, -tx_capacity)],
+            microlp::ComparisonOp::Le,
+            0.0,
+        );
+        used.push(x);
+        capacity.push(c);
+    }
+
+    // At most one logical edge from a master to a slave.
+    let mut same_end_points = std::collections::BTreeMap::new();
+    for (ndx, logical_edge) in possible_logical_edges.iter().enumerate() {
+        same_end_points
+            .entry((logical_edge.master, logical_edge.slave))
+            .or_insert(vec![])
+            .push((used[ndx], 1.0));
+    }
+    for terms in same_end_points.into_values() {
+        problem.add_constraint(terms, microlp::ComparisonOp::Le, 1.0);
+    }
+
+    // Resources of the physical nodes.
+    for u in 0..num_nodes as u32 {
+        let w = node_weight(u);
+        let as_tx = || {
+            possible_logical_edges
+                .iter()
+                .enumerate()
+                .filter(move |(_ndx, e)| e.tx == u)
+                .map(|(ndx, _e)| ndx)
+        };
+        let as_rx = || {
+            possible_logical_edges
+                .iter()
+                .enumerate()
+                .filter(move |(_ndx, e)| e.master == u || e.slave == u)
+                .map(|(ndx, _e)| ndx)
+        };
+        for (terms, bound) in [
+            (
+                as_tx().map(|ndx| (capacity[ndx], 1.0)).collect::<Vec<_>>(),
+                w.capacity,
+            ),
+            (
+                as_tx().map(|ndx| (used[ndx], 1.0)).collect(),
+                w.transmitters as f64,
+            ),
+            (
+                as_rx().map(|ndx| (used[ndx], 1.0)).collect(),
+                w.detectors as f64,
+            ),
+            (
+                as_rx().map(|ndx| (used[ndx], 1.0)).collect(),
+                w.memory_qubits as f64,
+            ),
+        ] {
+            if !terms.is_empty() {
+                problem.add_constraint(terms, microlp::ComparisonOp::Le, bound);
+            }
+        }
+    }
+
+    // Flows from the first OGS node to all the others and vice versa.
+    let success_probability = possible_logical_edges
+        .iter()
+        .map(|e| {
+            physical_topology
+                .success_probability(e.tx, e.master, e.slave)
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    if let Some((root, others)) = ogs_nodes.split_first() {
+        for other in others {
+            for (source, target) in [(*root, *other), (*other, *root)] {
+                let mut balance = vec![vec![]; num_nodes];
+                for (ndx, logical_edge) in possible_logical_edges.iter().enumerate() {
+                    let flow = problem.add_var(0.0, (0.0, f64::INFINITY));
+                    problem.add_constraint(
+                        [(flow, 1.0), (capacity[ndx], -success_probability[ndx])],
+                        microlp::ComparisonOp::Le,
+                        0.0,
+                    );
+                    balance[logical_edge.master as usize].push((flow, 1.0));
+                    balance[logical_edge.slave as usize].push((flow, -1.0));
+                }
+                balance[source as usize].push((min_capacity, -1.0));
+                balance[target as usize].push((min_capacity, 1.0));
+                for terms in balance {
+                    if !terms.is_empty() {
+                        problem.add_constraint(terms, microlp::ComparisonOp::Eq, 0.0);
+                    }
+                }
+            }
+        }
+    }
+
+    let solution = problem
+        .solve()
+        .map_err(|err| anyhow::anyhow!("could not solve the logical topology problem: {}", err))?;
+    anyhow::ensure!(
+        ogs_nodes.len() < 2 || solution[min_capacity] > NEGLIGIBLE_AMOUNT,
+        "could not find a logical topology for the given physical topology"
+    );
+
+    // Add all nodes from the physical topology.
+    let mut logical_graph = Graph::new();
+    for _ in 0..num_nodes {
+        logical_graph.add_node(NodeWeight {});
+    }
+
+    // Add the logical edges used with non-vanishing capacity.
+    let mut residual_capacity = physical_graph
+        .node_weights()
+        .map(|w| w.capacity)
+        .collect::<Vec<_>>();
+    let mut residual_memory_qubits = physical_graph
+        .node_weights()
+        .map(|w| w.memory_qubits)
+        .collect::<Vec<_>>();
+    for (ndx, logical_edge) in possible_logical_edges.iter().enumerate() {
+        let edge_capacity = solution[capacity[ndx]];
+        if solution.var_value_rounded(used[ndx]) < 0.5 || edge_capacity <= NEGLIGIBLE_AMOUNT {
+            continue;
+        }
+        residual_capacity[logical_edge.tx as usize] -= edge_capacity;
+        residual_memory_qubits[logical_edge.master as usize] -= 1;
+        residual_memory_qubits[logical_edge.slave as usize] -= 1;
+        logical_graph.add_edge(
+            logical_edge.master.into(),
+            logical_edge.slave.into(),
+            EdgeWeight {
+                tx: logical_edge.tx,
+                memory_qubits: 1,
+                capacity: edge_capacity,
+                cost: 1,
+            },
+        );
+    }
+
+    // Divide evenly the residual capacity of the transmitters.
+    for (tx, residual) in residual_capacity.iter().enumerate() {
+        let num_served = logical_graph
+            .edge_weights()
+            .filter(|w| w.tx == tx as u32)
+            .count();
+        if num_served == 0 || *residual <= 0.0 {
+            continue;
+        }
+        for w in logical_graph.edge_weights_mut() {
+            if w.tx == tx as u32 {
+                w.capacity += residual / num_served as f64;
+            }
+        }
+    }
+
+    // Assign residual memory qubits as possible, one at a time.
+    let mut candidate_edges = logical_graph
+        .edge_references()
+        .map(|e| (e.source(), e.target()))
+        .collect::<Vec<_>>();
+    candidate_edges.reverse();
+    assign_residual_memory_qubits(
+        &mut logical_graph,
+        &mut residual_memory_qubits,
+        candidate_edges,
+    );
 
     Ok(logical_graph)
 }
@@ -398,7 +1104,7 @@ pub fn is_valid(
 }
 
 /// Return true if any node can reach any other via the given graph.
-fn reachable(graph: &Graph, nodes: &Vec<u32>) -> bool {
+pub(crate) fn reachable(graph: &Graph, nodes: &Vec<u32>) -> bool {
     for u in nodes {
         match petgraph::algo::bellman_ford(&graph, (*u).into()) {
             Ok(paths) => {
@@ -423,7 +1129,7 @@ fn reachable(graph: &Graph, nodes: &Vec<u32>) -> bool {
 /// and can be reached by a transmitter tx with non-zero capacity.
 ///
 /// Return a vector of tuples (tx,u,v).
-fn find_possible_logical_edges(
+pub(crate) fn find_possible_logical_edges(
     physical_topology: &crate::physical_topology::PhysicalTopology,
 ) -> Vec<LogicalEdge> {
     let mut ret = vec![];
@@ -472,7 +1178,10 @@ mod tests {
 
     use crate::logical_topology::is_valid;
 
-    use super::{find_paths, find_possible_logical_edges, physical_to_logical_random_greedy};
+    use super::{
+        find_paths, find_possible_logical_edges, physical_to_logical_max_min_capacity,
+        physical_to_logical_random_greedy, physical_to_logical_shortest_path,
+    };
     use crate::tests::physical_topology_2_2;
 
     #[test]
@@ -516,6 +1225,11 @@ mod tests {
                 }
 
                 let all_paths = find_paths(&logical_graph)?;
+                let logical_topology = super::LogicalTopology {
+                    graph: logical_graph.clone(),
+                    paths: find_paths(&logical_graph)?,
+                    convergence: vec![],
+                };
 
                 let ogs_node_ids: std::collections::HashSet<u32> =
                     std::collections::HashSet::from_iter(
@@ -554,6 +1268,16 @@ mod tests {
                         }
                         assert!(paths.distances[target as usize].cost <= 9);
                         assert!(paths.predecessors[target as usize].is_some());
+
+                        let path = logical_topology.path(source, target).unwrap();
+                        assert_eq!(source, *path.first().unwrap());
+                        assert_eq!(target, *path.last().unwrap());
+                        assert_eq!(paths.distances[target as usize].cost + 1, path.len());
+                        for hop in path.windows(2) {
+                            assert!(logical_graph
+                                .find_edge(hop[0].into(), hop[1].into())
+                                .is_some());
+                        }
                     }
                 }
             }
@@ -563,4 +1287,307 @@ mod tests {
 
         anyhow::bail!("test failed");
     }
+
+    #[test]
+    fn test_logical_topology_physical_to_logical_max_min_capacity() -> anyhow::Result<()> {
+        // In a chain with two repeaters, each OGS can only be served by
+        // the transmitter of the nearest satellite, whose capacity must be
+        // shared between the two directions.
+        let mut sat_weight = crate::physical_topology::NodeWeight::default_sat();
+        sat_weight.memory_qubits = 10;
+        sat_weight.detectors = 4;
+        sat_weight.transmitters = 4;
+        let mut ogs_weight = crate::physical_topology::NodeWeight::default_ogs();
+        ogs_weight.memory_qubits = 3;
+        ogs_weight.detectors = 2;
+        let physical_topology = crate::physical_topology::PhysicalTopology::from_chain_static(
+            crate::physical_topology::ChainParams {
+                orbit_to_orbit_distance: 1.0,
+                ground_to_orbit_distance: 1.0,
+                num_repeaters: 2,
+            },
+            sat_weight,
+            ogs_weight,
+            crate::physical_topology::StaticFidelities::default(),
+        )?;
+        let logical_graph = physical_to_logical_max_min_capacity(&physical_topology)?;
+        is_valid(&logical_graph, &physical_topology)?;
+        for ogs in physical_topology.ogs_indices() {
+            let capacity = |outgoing: bool| -> f64 {
+                logical_graph
+                    .edge_references()
+                    .filter(|e| {
+                        if outgoing {
+                            e.source().index() as u32 == ogs
+                        } else {
+                            e.target().index() as u32 == ogs
+                        }
+                    })
+                    .map(|e| e.weight().capacity)
+                    .sum()
+            };
+            assert_float_eq::assert_f64_near!(0.5, capacity(true), 1000);
+            assert_float_eq::assert_f64_near!(0.5, capacity(false), 1000);
+        }
+
+        // All the memory qubits of the OGSs are used.
+        for ogs in physical_topology.ogs_indices() {
+            let memory_qubits: u32 = logical_graph
+                .edge_references()
+                .filter(|e| e.source().index() as u32 == ogs || e.target().index() as u32 == ogs)
+                .map(|e| e.weight().memory_qubits)
+                .sum();
+            assert_eq!(3, memory_qubits);
+        }
+
+        // The result is valid and deterministic in a larger topology, too.
+        let physical_topology = physical_topology_2_2();
+        let logical_graph = physical_to_logical_max_min_capacity(&physical_topology)?;
+        is_valid(&logical_graph, &physical_topology)?;
+        let edges = |graph: &super::Graph| {
+            graph
+                .edge_references()
+                .map(|e| (e.source().index(), e.target().index(), e.weight().tx))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            edges(&logical_graph),
+            edges(&physical_to_logical_max_min_capacity(&physical_topology)?)
+        );
+
+        // Fails if the OGS nodes cannot be connected.
+        let mut ogs_weight = crate::physical_topology::NodeWeight::default_ogs();
+        ogs_weight.memory_qubits = 0;
+        ogs_weight.detectors = 0;
+        let physical_topology = crate::physical_topology::PhysicalTopology::from_chain_static(
+            crate::physical_topology::ChainParams {
+                orbit_to_orbit_distance: 1.0,
+                ground_to_orbit_distance: 1.0,
+                num_repeaters: 2,
+            },
+            crate::physical_topology::NodeWeight::default_sat(),
+            ogs_weight,
+            crate::physical_topology::StaticFidelities::default(),
+        )?;
+        assert!(physical_to_logical_max_min_capacity(&physical_topology).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_logical_topology_physical_to_logical_shortest_path() -> anyhow::Result<()> {
+        // In a chain with two repeaters, the first satellite generates EPR
+        // pairs for the first OGS and the second satellite, which in turn
+        // generates EPR pairs for itself and the second OGS.
+        let mut sat_weight = crate::physical_topology::NodeWeight::default_sat();
+        sat_weight.memory_qubits = 5;
+        sat_weight.detectors = 4;
+        sat_weight.transmitters = 2;
+        let mut ogs_weight = crate::physical_topology::NodeWeight::default_ogs();
+        ogs_weight.memory_qubits = 3;
+        ogs_weight.detectors = 2;
+        let physical_topology = crate::physical_topology::PhysicalTopology::from_chain_static(
+            crate::physical_topology::ChainParams {
+                orbit_to_orbit_distance: 1.0,
+                ground_to_orbit_distance: 1.0,
+                num_repeaters: 2,
+            },
+            sat_weight,
+            ogs_weight,
+            crate::physical_topology::StaticFidelities::default(),
+        )?;
+        let logical_graph = physical_to_logical_shortest_path(&physical_topology)?;
+        is_valid(&logical_graph, &physical_topology)?;
+        let edges = |graph: &super::Graph| {
+            graph
+                .edge_references()
+                .map(|e| {
+                    (
+                        e.source().index() as u32,
+                        e.target().index() as u32,
+                        e.weight().tx,
+                        e.weight().memory_qubits,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec![(0, 3, 2, 2), (3, 0, 2, 1), (3, 1, 3, 1), (1, 3, 3, 1)],
+            edges(&logical_graph)
+        );
+        for e in logical_graph.edge_weights() {
+            assert_float_eq::assert_f64_near!(0.5, e.capacity);
+        }
+
+        // In a larger topology, the logical paths between OGS nodes are
+        // never longer than the physical ones and the result is always
+        // the same.
+        let mut physical_topology = physical_topology_2_2();
+        let logical_graph = physical_to_logical_shortest_path(&physical_topology)?;
+        is_valid(&logical_graph, &physical_topology)?;
+        assert_eq!(
+            edges(&logical_graph),
+            edges(&physical_to_logical_shortest_path(&physical_topology)?)
+        );
+        let logical_topology = super::LogicalTopology {
+            paths: find_paths(&logical_graph)?,
+            graph: logical_graph,
+            convergence: vec![],
+        };
+        for u in physical_topology.ogs_indices() {
+            for v in physical_topology.ogs_indices() {
+                if u != v {
+                    let path = logical_topology.path(u, v).unwrap();
+                    assert!(path.len() as u32 <= physical_topology.hops(u, v)? + 1);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_logical_topology_metaheuristic_search() -> anyhow::Result<()> {
+        let physical_topology = physical_topology_2_2();
+        for policy in [
+            super::PhysicalToLogicalPolicy::SimulatedAnnealing,
+            super::PhysicalToLogicalPolicy::Genetic,
+        ] {
+            let search_params = crate::topology_search::SearchParams {
+                iterations: 10,
+                search_seed: Some(42),
+                ..Default::default()
+            };
+            let mut convergences = vec![];
+            for seed in [1, 2] {
+                let logical_topology = super::LogicalTopology::from_physical_topology(
+                    &policy,
+                    &search_params,
+                    &super::Allocation::default(),
+                    &physical_topology,
+                    &mut rand::rngs::StdRng::seed_from_u64(seed),
+                )?;
+                is_valid(logical_topology.graph(), &physical_topology)?;
+                assert_eq!(10, logical_topology.convergence().len());
+                convergences.push(logical_topology.convergence().clone());
+            }
+
+            // The search seed overrides that of the simulation.
+            assert_eq!(convergences[0], convergences[1]);
+        }
+
+        // Other policies do not report convergence.
+        let logical_topology = super::LogicalTopology::from_physical_topology(
+            &super::PhysicalToLogicalPolicy::ShortestPath,
+            &crate::topology_search::SearchParams::default(),
+            &crate::logical_topology::Allocation::default(),
+            &physical_topology,
+            &mut rand::rngs::StdRng::seed_from_u64(42),
+        )?;
+        assert!(logical_topology.convergence().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_logical_topology_demand_allocation() -> anyhow::Result<()> {
+        // Same chain as in the shortest-path test, whose logical edges are
+        // 0->3, 3->0 (tx 2) and 3->1, 1->3 (tx 3), with one residual memory
+        // qubit in each node.
+        let mut sat_weight = crate::physical_topology::NodeWeight::default_sat();
+        sat_weight.memory_qubits = 5;
+        sat_weight.detectors = 4;
+        sat_weight.transmitters = 2;
+        let mut ogs_weight = crate::physical_topology::NodeWeight::default_ogs();
+        ogs_weight.memory_qubits = 3;
+        ogs_weight.detectors = 2;
+        let physical_topology = crate::physical_topology::PhysicalTopology::from_chain_static(
+            crate::physical_topology::ChainParams {
+                orbit_to_orbit_distance: 1.0,
+                ground_to_orbit_distance: 1.0,
+                num_repeaters: 2,
+            },
+            sat_weight,
+            ogs_weight,
+            crate::physical_topology::StaticFidelities::default(),
+        )?;
+        let from_demands = |demands: Vec<super::Demand>| {
+            super::LogicalTopology::from_physical_topology(
+                &super::PhysicalToLogicalPolicy::ShortestPath,
+                &crate::topology_search::SearchParams::default(),
+                &super::Allocation::Demand(demands),
+                &physical_topology,
+                &mut rand::rngs::StdRng::seed_from_u64(42),
+            )
+        };
+        let weights = |logical_topology: &super::LogicalTopology| {
+            logical_topology
+                .graph()
+                .edge_references()
+                .map(|e| {
+                    (
+                        (e.source().index() as u32, e.target().index() as u32),
+                        (e.weight().capacity, e.weight().memory_qubits),
+                    )
+                })
+                .collect::<std::collections::HashMap<_, _>>()
+        };
+
+        // A single demand: the logical edges along its path get most of the
+        // capacity and the only residual memory qubit of the satellite.
+        let logical_topology = from_demands(vec![super::Demand {
+            source: 0,
+            target: 1,
+            rate: 100.0,
+        }])?;
+        is_valid(logical_topology.graph(), &physical_topology)?;
+        let weights_single = weights(&logical_topology);
+        assert_float_eq::assert_f64_near!(1.0 / 1.01, weights_single[&(0, 3)].0);
+        assert_float_eq::assert_f64_near!(0.01 / 1.01, weights_single[&(3, 0)].0);
+        assert_float_eq::assert_f64_near!(1.0 / 1.01, weights_single[&(3, 1)].0);
+        assert_float_eq::assert_f64_near!(0.01 / 1.01, weights_single[&(1, 3)].0);
+        assert_eq!(2, weights_single[&(0, 3)].1);
+        assert_eq!(1, weights_single[&(3, 0)].1);
+
+        // Demands in both directions: the capacity is divided evenly and
+        // the memory qubits favor the highest rate.
+        let logical_topology = from_demands(vec![
+            super::Demand {
+                source: 1,
+                target: 0,
+                rate: 10.0,
+            },
+            super::Demand {
+                source: 0,
+                target: 1,
+                rate: 100.0,
+            },
+        ])?;
+        is_valid(logical_topology.graph(), &physical_topology)?;
+        let weights_both = weights(&logical_topology);
+        for (capacity, _) in weights_both.values() {
+            assert_float_eq::assert_f64_near!(0.5, *capacity);
+        }
+        assert_eq!(2, weights_both[&(0, 3)].1);
+        assert_eq!(1, weights_both[&(1, 3)].1);
+
+        // Without demands, all logical edges are equal.
+        let logical_topology = from_demands(vec![])?;
+        is_valid(logical_topology.graph(), &physical_topology)?;
+        for (capacity, _) in weights(&logical_topology).values() {
+            assert_float_eq::assert_f64_near!(0.5, *capacity);
+        }
+
+        // Invalid demands.
+        for (source, target, rate) in [(0, 2, 1.0), (0, 0, 1.0), (0, 1, -1.0)] {
+            assert!(from_demands(vec![super::Demand {
+                source,
+                target,
+                rate
+            }])
+            .is_err());
+        }
+
+        Ok(())
+    }
 }
@@ -13,23 +13,62 @@ pub struct EprGenerator {
     tx_node_id: u32,
     master_node_id: u32,
     slave_node_id: u32,
-    rv: rand_distr::Exp<f64>,
+    /// Rate at which the EPR pairs are generated, in Hz.
+    capacity: f64,
     /// Pseudo-random number generator.
     rng: rand::rngs::StdRng,
+    /// True if all the physical links needed by the logical link are up.
+    active: bool,
+    /// True if the next EPR generation has been scheduled.
+    pending: bool,
 }
 
 impl EprGenerator {
-    /// Schedule the next EPR generation.
-    fn handle(&mut self) -> Event {
-        let next_epr_generation = self.rv.sample(&mut self.rng);
-        Event::new(
+    /// Schedule the next EPR generation, at a rate equal to the capacity
+    /// times the probability that the EPR pairs are successfully
+    /// established over the current physical links.
+    ///
+    /// The generator stops if the rate vanishes.
+    fn handle(
+        &mut self,
+        physical_topology: &crate::physical_topology::PhysicalTopology,
+    ) -> Option<Event> {
+        let rate = self.capacity
+            * physical_topology
+                .success_probability(self.tx_node_id, self.master_node_id, self.slave_node_id)
+                .unwrap_or_default();
+        self.pending = rate > 0.0;
+        if !self.pending {
+            return None;
+        }
+        let next_epr_generation = rand_distr::Exp::new(rate)
+            .expect("could not create an expo rv")
+            .sample(&mut self.rng);
+        Some(Event::new(
             next_epr_generation,
             EventType::NodeEvent(NodeEventData::EprGenerated(EprGeneratedData {
                 tx_node_id: self.tx_node_id,
                 master_node_id: self.master_node_id,
                 slave_node_id: self.slave_node_id,
             })),
-        )
+        ))
+    }
+
+    /// Return the physical links from the tx node to the end-points of the
+    /// logical link, excluding the tx node itself.
+    fn physical_links(&self) -> Vec<(u32, u32)> {
+        [self.master_node_id, self.slave_node_id]
+            .into_iter()
+            .filter(|node_id| *node_id != self.tx_node_id)
+            .map(|node_id| (self.tx_node_id, node_id))
+            .collect()
+    }
+
+    /// Return true if the logical link needs the physical link (u, v).
+    fn depends_on(&self, u: u32, v: u32) -> bool {
+        self.physical_links()
+            .iter()
+            .any(|link| *link == (u, v) || *link == (v, u))
     }
 }
 
@@ -44,13 +83,47 @@ pub struct Network {
     epr_register: crate::epr_register::EprRegister,
     /// The physical topology.
     physical_topology: crate::physical_topology::PhysicalTopology,
+    /// The logical topology, including the logical links that are down.
+    logical_graph: crate::logical_topology::Graph,
+    /// The quantum OS of every node, indexed by the node ID.
+    oss: Vec<crate::os::Os>,
+    /// The memory cells reserved by applications whose EPR pairs were lost
+    /// when a logical link went down, identified by node ID, peer node ID,
+    /// role, and index.
+    lost_cells: Vec<(u32, u32, crate::nic::Role, usize)>,
+    /// The client applications, indexed by node ID and port.
+    clients: std::collections::HashMap<(u32, u16), crate::client::Client>,
+    /// The purification protocol.
+    purification_protocol: crate::purification::Protocol,
+    /// The purification policy.
+    purification_policy: Box<dyn crate::purification_policy::PurificationPolicy>,
+    /// The quantum memory configuration.
+    memory_params: crate::nic::MemoryParams,
+    /// The memory cutoff timers, indexed by the EPR pair ID.
+    cutoff_timers: crate::timer::Timers,
+    /// The classical signalling configuration.
+    signalling_params: crate::signalling::SignallingParams,
+    /// The mobility configuration.
+    mobility_params: crate::physical_topology::MobilityParams,
+    /// Pseudo-random number generator.
+    rng: rand::rngs::StdRng,
 }
 
 impl Network {
     /// Create a network from the logical topology.
+    ///
+    /// A client application is created on every OGS for each other OGS with
+    /// a higher identifier.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         logical_topology: &super::logical_topology::LogicalTopology,
         physical_topology: crate::physical_topology::PhysicalTopology,
+        client_params: &crate::client::ClientParams,
+        purification_params: &crate::purification::PurificationParams,
+        memory_params: &crate::nic::MemoryParams,
+        signalling_params: &crate::signalling::SignallingParams,
+        mobility_params: &crate::physical_topology::MobilityParams,
+        backend: crate::epr_register::Backend,
         init_seed: u64,
     ) -> Self {
         // Create the nodes.
@@ -71,11 +144,13 @@ impl Network {
                 slave_node_id as u32,
                 super::nic::Role::Master,
                 num_qubits,
+                memory_params.replacement_policy,
             );
             nodes[slave_node_id].add_nic(
                 master_node_id as u32,
                 super::nic::Role::Slave,
                 num_qubits,
+                memory_params.replacement_policy,
             );
 
             let master_node_id = master_node_id as u32;
@@ -87,18 +162,68 @@ impl Network {
                     tx_node_id: edge.weight().tx,
                     master_node_id,
                     slave_node_id,
-                    rv: rand_distr::Exp::new(edge.weight().capacity)
-                        .expect("could not create an expo rv"),
+                    capacity: edge.weight().capacity,
                     rng: rand::rngs::StdRng::seed_from_u64(init_seed + cnt as u64),
+                    active: true,
+                    pending: false,
                 });
         }
 
-        let epr_register = crate::epr_register::EprRegister::default();
+        // Add the OSs.
+        let mut seed = init_seed + logical_topology.graph().edge_count() as u64;
+        let mut oss = vec![];
+        for node_id in 0..nodes.len() {
+            oss.push(crate::os::Os::new(node_id as u32, logical_topology, seed));
+            seed += 1;
+        }
+
+        // Add the clients, only if there is at least one logical link.
+        let mut clients = std::collections::HashMap::new();
+        if logical_topology.graph().edge_count() > 0 {
+            let ogs_indices = physical_topology.ogs_indices();
+            for (i, this_node_id) in ogs_indices.iter().enumerate() {
+                for (port, peer_node_id) in ogs_indices.iter().skip(i + 1).enumerate() {
+                    let port = port as u16;
+                    clients.insert(
+                        (*this_node_id, port),
+                        crate::client::Client::new(
+                            *this_node_id,
+                            port,
+                            *peer_node_id,
+                            port,
+                            client_params,
+                            seed,
+                        ),
+                    );
+                    seed += 1;
+                }
+            }
+        }
+
+        let epr_register = crate::epr_register::EprRegister::new(
+            physical_topology
+                .graph()
+                .node_weights()
+                .map(|weight| (weight.decay_rate, weight.dephasing_rate))
+                .collect(),
+            backend,
+        );
         Self {
             nodes,
             epr_generators,
             epr_register,
             physical_topology,
+            logical_graph: logical_topology.graph().clone(),
+            oss,
+            lost_cells: vec![],
+            clients,
+            purification_protocol: purification_params.protocol,
+            purification_policy: purification_params.policy(),
+            memory_params: memory_params.clone(),
+            cutoff_timers: crate::timer::Timers::default(),
+            signalling_params: signalling_params.clone(),
+            mobility_params: mobility_params.clone(),
+            rng: rand::rngs::StdRng::seed_from_u64(seed),
         }
     }
 
@@ -115,14 +240,37 @@ impl Network {
             if generator.master_node_id == data.master_node_id
                 && generator.slave_node_id == data.slave_node_id
             {
+                // The generator stops while the logical link is down.
+                generator.pending = false;
+                if !generator.active {
+                    return (vec![], vec![]);
+                }
+
                 let mut events = vec![];
                 let mut samples = vec![];
 
-                // Create a new EPR pair.
-                if let Ok(fidelity) = self.physical_topology.fidelity(
+                // Create a new EPR pair, unless the detection outcome cannot
+                // be notified to the ends.
+                let master_delay = self.signalling_params.heralding_delay(
+                    &mut self.physical_topology,
                     data.tx_node_id,
                     data.master_node_id,
                     data.slave_node_id,
+                );
+                let slave_delay = self.signalling_params.heralding_delay(
+                    &mut self.physical_topology,
+                    data.tx_node_id,
+                    data.slave_node_id,
+                    data.master_node_id,
+                );
+                if let (Ok(fidelity), Some(master_delay), Some(slave_delay)) = (
+                    self.physical_topology.fidelity(
+                        data.tx_node_id,
+                        data.master_node_id,
+                        data.slave_node_id,
+                    ),
+                    master_delay,
+                    slave_delay,
                 ) {
                     samples.push(Sample::Series(
                         "gen_fidelity".to_string(),
@@ -138,9 +286,10 @@ impl Network {
                     );
 
                     // Add events notifying the creation of the EPR pair
-                    // on the master/slave nodes.
+                    // on the master/slave nodes, when they receive the
+                    // detection outcome from the other end.
                     events.push(Event::new(
-                        0.0_f64,
+                        master_delay,
                         EventType::NodeEvent(NodeEventData::EprNotified(EprNotifiedData {
                             this_node_id: data.master_node_id,
                             peer_node_id: data.slave_node_id,
@@ -149,7 +298,7 @@ impl Network {
                         })),
                     ));
                     events.push(Event::new(
-                        0.0_f64,
+                        slave_delay,
                         EventType::NodeEvent(NodeEventData::EprNotified(EprNotifiedData {
                             this_node_id: data.slave_node_id,
                             peer_node_id: data.master_node_id,
@@ -160,7 +309,7 @@ impl Network {
                 }
 
                 // Add event to generate another EPR pair in the future.
-                events.push(generator.handle());
+                events.extend(generator.handle(&self.physical_topology));
 
                 return (events, samples);
             }
@@ -190,23 +339,146 @@ impl Network {
             self.nodes.len()
         );
 
-        let occupancy = self.nodes[data.this_node_id as usize].epr_established(
+        // The EPR pair may have been dropped already at the other end.
+        if !self.epr_register.contains(data.epr_pair_id) {
+            return (vec![], vec![]);
+        }
+
+        // The EPR pair is lost if the logical link went down in the meantime.
+        let (master_node_id, slave_node_id) = match data.role {
+            crate::nic::Role::Master => (data.this_node_id, data.peer_node_id),
+            crate::nic::Role::Slave => (data.peer_node_id, data.this_node_id),
+        };
+        if !self.is_active(master_node_id, slave_node_id) {
+            self.discard(data.epr_pair_id, data.this_node_id);
+            return (
+                vec![],
+                vec![Sample::Series(
+                    "link_down_lost".to_string(),
+                    format!("{}-{}", master_node_id, slave_node_id),
+                    1.0,
+                )],
+            );
+        }
+
+        let epr_register = &self.epr_register;
+        let (occupancy, admission) = self.nodes[data.this_node_id as usize].epr_established(
             now,
             data.peer_node_id,
-            data.role,
+            data.role.clone(),
             data.epr_pair_id,
+            &|epr_pair_id| epr_register.fidelity(epr_pair_id, now).unwrap_or(0.0),
         );
 
+        let label = format!("{}-{}", data.this_node_id, data.peer_node_id);
+        let mut samples = vec![Sample::Series(
+            "occupancy".to_string(),
+            label.clone(),
+            occupancy,
+        )];
+
+        // Free the other end of the EPR pair dropped or overwritten, if any.
+        let mut events = vec![];
+        match admission {
+            crate::nic::Admission::Stored => {}
+            crate::nic::Admission::Dropped => {
+                self.discard(data.epr_pair_id, data.this_node_id);
+                samples.push(Sample::Series("memory_drop".to_string(), label, 1.0));
+                return (vec![], samples);
+            }
+            crate::nic::Admission::Overwritten(epr_pair_id) => {
+                self.discard(epr_pair_id, data.this_node_id);
+                samples.push(Sample::Series("memory_overwrite".to_string(), label, 1.0));
+            }
+        }
+
+        // Start the cutoff timer once the EPR pair is stored at both ends.
+        if self.nodes[data.peer_node_id as usize].is_valid(data.epr_pair_id) {
+            if let Some(delay) = self.cutoff_delay(now, data.epr_pair_id) {
+                events.push(self.schedule_cutoff(data.epr_pair_id, delay));
+            }
+        }
+
+        // Try to purify the EPR pairs on the logical link.
+        let (mut purify_events, mut purify_samples) =
+            self.purify_link(now, master_node_id, slave_node_id);
+        events.append(&mut purify_events);
+        samples.append(&mut purify_samples);
+
+        (events, samples)
+    }
+
+    fn handle_purification_outcome(
+        &mut self,
+        now: u64,
+        data: PurificationOutcomeData,
+    ) -> (Vec<Event>, Vec<Sample>) {
+        if !crate::purification::complete_purification(
+            &data,
+            &mut self.nodes,
+            &mut self.epr_register,
+        ) {
+            self.cutoff_timers.cancel(data.epr_pair_id);
+            return (vec![], vec![]);
+        }
+
+        // The EPR pair purified is available again at both ends.
+        let mut events = vec![];
+        if let Some(delay) = self.cutoff_delay(now, data.epr_pair_id) {
+            events.push(self.schedule_cutoff(data.epr_pair_id, delay));
+        }
+        let (mut purify_events, samples) =
+            self.purify_link(now, data.master_node_id, data.slave_node_id);
+        events.append(&mut purify_events);
+        (events, samples)
+    }
+
+    fn handle_cutoff_expired(
+        &mut self,
+        now: u64,
+        data: CutoffExpiredData,
+    ) -> (Vec<Event>, Vec<Sample>) {
+        if !self.cutoff_timers.expire(data.epr_pair_id, data.timer_id) {
+            return (vec![], vec![]);
+        }
+
+        // Skip EPR pairs that have been consumed, even partially, or that
+        // are in use by an application.
+        let ends = match self.epr_register.ends(data.epr_pair_id) {
+            Some(ends) => ends,
+            None => return (vec![], vec![]),
+        };
+        if ends.len() != 2
+            || !ends
+                .iter()
+                .all(|node_id| self.nodes[*node_id as usize].is_valid(data.epr_pair_id))
+        {
+            return (vec![], vec![]);
+        }
+
+        // With fidelity cutoff, the EPR pair may have been purified since
+        // the timer was scheduled.
+        if self.memory_params.cutoff == crate::nic::Cutoff::Fidelity {
+            match self.cutoff_delay(now, data.epr_pair_id) {
+                Some(delay) if crate::utils::to_nanoseconds(delay) > 0 => {
+                    return (vec![self.schedule_cutoff(data.epr_pair_id, delay)], vec![]);
+                }
+                Some(_) => {}
+                None => return (vec![], vec![]),
+            }
+        }
+
+        for node_id in &ends {
+            self.nodes[*node_id as usize].discard(data.epr_pair_id);
+        }
+        self.epr_register.remove(data.epr_pair_id);
+
         (
             vec![],
             vec![Sample::Series(
-                "occupancy".to_string(),
-                format!(
-                    "{}-{}",
-                    data.this_node_id.to_string(),
-                    data.peer_node_id.to_string()
-                ),
-                occupancy,
+                "memory_cutoff".to_string(),
+                format!("{}-{}", ends[0], ends[1]),
+                1.0,
             )],
         )
     }
@@ -217,53 +489,370 @@ impl Network {
         data: EprFidelityData,
     ) -> (Vec<Event>, Vec<Sample>) {
         assert!(data.consume_node_id <= self.nodes.len() as u32);
-        let fidelity = if let Some((_creation_time, epr_pair_id)) = self.nodes
+
+        // The EPR pair was lost while in use by the application.
+        let cell = (
+            data.consume_node_id,
+            data.neighbor_node_id,
+            data.role.clone(),
+            data.index,
+        );
+        if let Some(pos) = self.lost_cells.iter().position(|lost| *lost == cell) {
+            self.lost_cells.swap_remove(pos);
+            return (vec![], vec![]);
+        }
+
+        let (fidelity, exact_fidelity) = if let Some((_creation_time, epr_pair_id)) = self.nodes
             [data.consume_node_id as usize]
-            .consume(data.consume_node_id, &data.role, data.index)
+            .consume(data.neighbor_node_id, &data.role, data.index)
         {
-            if let Some(weight) = self
-                .physical_topology
-                .graph()
-                .node_weight(data.consume_node_id.into())
-            {
-                if let Some((updated, fidelity)) =
-                    self.epr_register.consume(epr_pair_id, data.consume_node_id)
-                {
-                    assert!(now >= updated);
-                    crate::utils::fidelity(
-                        fidelity,
-                        weight.decay_rate,
-                        crate::utils::to_seconds(now - updated),
-                    )
-                } else {
-                    panic!("EPR pair not found {:?}", data);
-                }
-            } else {
-                panic!("no such node {:?}", data);
-            }
+            let exact_fidelity = self.epr_register.exact_fidelity(epr_pair_id, now);
+            let fidelity = self
+                .epr_register
+                .consume(epr_pair_id, data.consume_node_id, now)
+                .unwrap_or_else(|| panic!("EPR pair not found {:?}", data));
+            (fidelity, exact_fidelity)
         } else {
             panic!("no EPR found at {:?}", data);
         };
 
+        // The fidelity is always the closed-form Bell-diagonal one: with the
+        // density matrix backend, the exact fidelity and the difference
+        // between the two are reported in separate series.
+        let label = format!("{},{}", data.app_node_id, data.port);
+        let mut samples = vec![Sample::Series(
+            "fidelity-node,fidelity-port".to_string(),
+            label.clone(),
+            fidelity,
+        )];
+        if let Some(exact_fidelity) = exact_fidelity {
+            samples.push(Sample::Series(
+                "exact_fidelity-node,exact_fidelity-port".to_string(),
+                label.clone(),
+                exact_fidelity,
+            ));
+            samples.push(Sample::Series(
+                "fidelity_error-node,fidelity_error-port".to_string(),
+                label,
+                fidelity - exact_fidelity,
+            ));
+        }
+        (vec![], samples)
+    }
+
+    /// Move the nodes to their current positions and notify the physical
+    /// links that have appeared or disappeared.
+    fn handle_topology_update(&mut self, now: u64) -> (Vec<Event>, Vec<Sample>) {
+        let time = match self.physical_topology.constellation() {
+            Some(constellation) => constellation.time() + crate::utils::to_seconds(now),
+            None => return (vec![], vec![]),
+        };
+        let changes = self
+            .physical_topology
+            .set_time(time)
+            .expect("cannot update the physical topology");
+
+        let mut events = vec![];
+        for (u, v) in changes.down {
+            events.push(Event::new(
+                0.0,
+                EventType::TopologyEvent(TopologyEventData::LinkDown(PhysicalLinkData { u, v })),
+            ));
+        }
+        for (u, v) in changes.up {
+            events.push(Event::new(
+                0.0,
+                EventType::TopologyEvent(TopologyEventData::LinkUp(PhysicalLinkData { u, v })),
+            ));
+        }
+        events.push(Event::new(
+            self.mobility_params.update_interval,
+            EventType::TopologyEvent(TopologyEventData::Update),
+        ));
+
+        // Restart the generators stopped because of a vanishing rate.
+        for generator in self.epr_generators.values_mut().flatten() {
+            if generator.active && !generator.pending {
+                events.extend(generator.handle(&self.physical_topology));
+            }
+        }
+        (events, vec![])
+    }
+
+    /// Bring down the logical links that need a physical link that has
+    /// disappeared: their generators stop, the EPR pairs stored at either
+    /// end are lost, including those reserved, and the OSs stop routing
+    /// requests through them.
+    fn handle_link_down(&mut self, data: PhysicalLinkData) -> (Vec<Event>, Vec<Sample>) {
+        let mut samples = vec![Sample::Series(
+            "link_down".to_string(),
+            format!("{}-{}", data.u, data.v),
+            1.0,
+        )];
+
+        let mut logical_links = vec![];
+        for generator in self.epr_generators.values_mut().flatten() {
+            if generator.active && generator.depends_on(data.u, data.v) {
+                generator.active = false;
+                logical_links.push((generator.master_node_id, generator.slave_node_id));
+            }
+        }
+        if !logical_links.is_empty() {
+            self.update_paths();
+        }
+
+        for (master_node_id, slave_node_id) in logical_links {
+            let mut epr_pair_ids = vec![];
+            let mut reserved = vec![];
+            for (node_id, peer_node_id, role) in [
+                (master_node_id, slave_node_id, crate::nic::Role::Master),
+                (slave_node_id, master_node_id, crate::nic::Role::Slave),
+            ] {
+                let nic = self.nodes[node_id as usize]
+                    .nic(peer_node_id, &role)
+                    .expect("no NIC found for a logical link");
+                for index in nic.valid_indices() {
+                    let (_, epr_pair_id) = nic.get(index).unwrap();
+                    epr_pair_ids.push((node_id, epr_pair_id));
+                }
+                for index in nic.reserved_indices() {
+                    reserved.push((node_id, peer_node_id, role.clone(), index));
+                }
+            }
+            let mut num_lost = 0;
+            for (node_id, epr_pair_id) in epr_pair_ids {
+                if self.nodes[node_id as usize].discard(epr_pair_id) {
+                    self.discard(epr_pair_id, node_id);
+                    num_lost += 1;
+                }
+            }
+
+            // The EPR pairs reserved for purification are lost at both ends,
+            // while those reserved by an application, whose other end has
+            // been consumed already, are lost when the application
+            // consumes them.
+            for (node_id, peer_node_id, role, index) in reserved {
+                let (_, epr_pair_id) = self.nodes[node_id as usize]
+                    .consume(peer_node_id, &role, index)
+                    .expect("no EPR found in a reserved memory cell");
+                if self.epr_register.ends(epr_pair_id) == Some(vec![node_id]) {
+                    self.lost_cells.push((node_id, peer_node_id, role, index));
+                }
+                self.discard(epr_pair_id, node_id);
+                num_lost += 1;
+            }
+            samples.push(Sample::Series(
+                "link_down_lost".to_string(),
+                format!("{}-{}", master_node_id, slave_node_id),
+                num_lost as f64,
+            ));
+        }
+
+        (vec![], samples)
+    }
+
+    /// Bring up the logical links whose physical links are all up again,
+    /// by restarting their generators, and let the OSs route requests
+    /// through them again.
+    fn handle_link_up(&mut self, data: PhysicalLinkData) -> (Vec<Event>, Vec<Sample>) {
+        let mut events = vec![];
+        let mut activated = false;
+        let graph = self.physical_topology.graph();
+        for generator in self.epr_generators.values_mut().flatten() {
+            if !generator.active
+                && generator.depends_on(data.u, data.v)
+                && generator
+                    .physical_links()
+                    .iter()
+                    .all(|(u, v)| graph.find_edge((*u).into(), (*v).into()).is_some())
+            {
+                generator.active = true;
+                activated = true;
+                if !generator.pending {
+                    events.extend(generator.handle(&self.physical_topology));
+                }
+            }
+        }
+        if activated {
+            self.update_paths();
+        }
+
         (
-            vec![],
+            events,
             vec![Sample::Series(
-                "fidelity-node,fidelity-port".to_string(),
-                format!("{},{}", data.app_node_id, data.port),
-                fidelity,
+                "link_up".to_string(),
+                format!("{}-{}", data.u, data.v),
+                1.0,
             )],
         )
     }
+
+    fn handle_epr_request_app(&mut self, now: u64, epr: EprFiveTuple) -> (Vec<Event>, Vec<Sample>) {
+        let os = self
+            .oss
+            .get_mut(epr.source_node_id as usize)
+            .unwrap_or_else(|| panic!("no OS found for {}", epr));
+        os.handle_epr_request_app(
+            now,
+            epr,
+            &mut self.nodes,
+            &mut self.epr_register,
+            &mut self.physical_topology,
+            &self.signalling_params,
+        )
+    }
+
+    /// Purify the EPR pairs on a logical link, if possible, and notify the
+    /// outcome to both ends after the classical message delay between them.
+    /// If the ends cannot exchange classical messages, the purification
+    /// fails immediately.
+    fn purify_link(
+        &mut self,
+        now: u64,
+        master_node_id: u32,
+        slave_node_id: u32,
+    ) -> (Vec<Event>, Vec<Sample>) {
+        let (outcome, samples) = crate::purification::purify_link(
+            now,
+            master_node_id,
+            slave_node_id,
+            self.purification_protocol,
+            self.purification_policy.as_ref(),
+            &mut self.nodes,
+            &mut self.epr_register,
+            &mut self.rng,
+        );
+        let mut outcome = match outcome {
+            Some(outcome) => outcome,
+            None => return (vec![], samples),
+        };
+        match self.signalling_params.message_delay(
+            &mut self.physical_topology,
+            master_node_id,
+            slave_node_id,
+        ) {
+            Some(delay) => (
+                vec![Event::new(
+                    delay,
+                    EventType::NodeEvent(NodeEventData::PurificationOutcome(outcome)),
+                )],
+                samples,
+            ),
+            None => {
+                outcome.success = false;
+                crate::purification::complete_purification(
+                    &outcome,
+                    &mut self.nodes,
+                    &mut self.epr_register,
+                );
+                self.cutoff_timers.cancel(outcome.epr_pair_id);
+                (vec![], samples)
+            }
+        }
+    }
+
+    /// Recompute the paths used by the OSs on the logical links that are up.
+    fn update_paths(&mut self) {
+        let mut graph = self.logical_graph.clone();
+        graph.retain_edges(|graph, edge| {
+            let (master, slave) = graph.edge_endpoints(edge).unwrap();
+            self.is_active(master.index() as u32, slave.index() as u32)
+        });
+        let logical_topology = crate::logical_topology::LogicalTopology::from_graph(graph)
+            .expect("cannot compute the paths on the logical topology");
+        for os in &mut self.oss {
+            os.set_paths(&logical_topology);
+        }
+    }
+
+    /// Return true if the logical link between two nodes is up.
+    fn is_active(&self, master_node_id: u32, slave_node_id: u32) -> bool {
+        self.epr_generators.values().flatten().any(|generator| {
+            generator.active
+                && generator.master_node_id == master_node_id
+                && generator.slave_node_id == slave_node_id
+        })
+    }
+
+    /// Discard an EPR pair that is not stored anymore at a given node:
+    /// free the memory cell at the other end, if any, remove it from the
+    /// register, and cancel its cutoff timer.
+    fn discard(&mut self, epr_pair_id: u64, node_id: u32) {
+        if let Some(peer_node_id) = self.epr_register.peer(epr_pair_id, node_id) {
+            self.nodes[peer_node_id as usize].discard(epr_pair_id);
+        }
+        self.epr_register.remove(epr_pair_id);
+        self.cutoff_timers.cancel(epr_pair_id);
+    }
+
+    /// Return the time after which an EPR pair must be discarded, in s, or
+    /// None if it is never discarded.
+    fn cutoff_delay(&self, now: u64, epr_pair_id: u64) -> Option<f64> {
+        match self.memory_params.cutoff {
+            crate::nic::Cutoff::Disabled => None,
+            crate::nic::Cutoff::Age => Some(self.memory_params.cutoff_age),
+            crate::nic::Cutoff::Fidelity => self.epr_register.fidelity_time(
+                epr_pair_id,
+                now,
+                self.memory_params.cutoff_fidelity,
+            ),
+        }
+    }
+
+    /// Schedule the cutoff timer of an EPR pair after a given delay, in s,
+    /// cancelling the previous one, if any.
+    fn schedule_cutoff(&mut self, epr_pair_id: u64, delay: f64) -> Event {
+        let timer_id = self.cutoff_timers.schedule(epr_pair_id);
+        Event::new(
+            delay,
+            EventType::NodeEvent(NodeEventData::CutoffExpired(CutoffExpiredData {
+                epr_pair_id,
+                timer_id,
+            })),
+        )
+    }
+
+    /// Return the client application at a given node ID and port.
+    fn client(&mut self, node_id: u32, port: u16) -> &mut crate::client::Client {
+        self.clients
+            .get_mut(&(node_id, port))
+            .unwrap_or_else(|| panic!("no client found at {}:{}", node_id, port))
+    }
 }
 
 impl EventHandler for Network {
     fn handle(&mut self, event: Event) -> (Vec<Event>, Vec<Sample>) {
         let now = event.time();
+
+        // Application events are dispatched to the client.
+        if let EventType::AppEvent(data) = &event.event_type {
+            let (node_id, port) = match data {
+                AppEventData::EprRequest(node_id, port) => (*node_id, *port),
+                AppEventData::EprResponse(data) => (data.epr.source_node_id, data.epr.source_port),
+                AppEventData::LocalComplete(epr) | AppEventData::RemoteComplete(epr) => {
+                    (epr.source_node_id, epr.source_port)
+                }
+            };
+            return self.client(node_id, port).handle(event);
+        }
+
         match event.event_type {
+            EventType::TopologyEvent(data) => match data {
+                TopologyEventData::Update => self.handle_topology_update(now),
+                TopologyEventData::LinkUp(data) => self.handle_link_up(data),
+                TopologyEventData::LinkDown(data) => self.handle_link_down(data),
+            },
             EventType::NodeEvent(data) => match data {
                 NodeEventData::EprGenerated(data) => self.handle_epr_generated(now, data),
                 NodeEventData::EprNotified(data) => self.handle_epr_notified(now, data),
                 NodeEventData::EprFidelity(data) => self.handle_epr_fidelity(now, data),
+                NodeEventData::CutoffExpired(data) => self.handle_cutoff_expired(now, data),
+                NodeEventData::PurificationOutcome(data) => {
+                    self.handle_purification_outcome(now, data)
+                }
+            },
+            EventType::OsEvent(data) => match data {
+                OsEventData::EprRequestApp(epr) => self.handle_epr_request_app(now, epr),
             },
             _ => panic!(
                 "invalid event {:?} received by a Network object",
@@ -272,32 +861,671 @@ impl EventHandler for Network {
         }
     }
 
-    /// Kick start all the EPR generators.
+    /// Kick start all the EPR generators and client applications, and the
+    /// updates of the physical topology, if the nodes move.
     fn initial(&mut self) -> Vec<Event> {
         let mut events = vec![];
 
+        if !self.epr_generators.is_empty()
+            && self.physical_topology.constellation().is_some()
+            && self.mobility_params.update_interval > 0.0
+        {
+            events.push(Event::new(
+                self.mobility_params.update_interval,
+                EventType::TopologyEvent(TopologyEventData::Update),
+            ));
+        }
+
         for generators in self.epr_generators.values_mut() {
             for generator in generators {
-                events.push(generator.handle());
+                events.extend(generator.handle(&self.physical_topology));
             }
         }
 
+        for client in self.clients.values_mut() {
+            events.append(&mut client.initial());
+        }
+
         events
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use petgraph::visit::EdgeRef;
     use rand::SeedableRng;
     use rand_distr::Distribution;
 
     use super::Network;
+    use crate::event::{
+        AppEventData, EprFidelityData, EprFiveTuple, EprGeneratedData, EventHandler, EventType,
+        NodeEventData, OsEventData, PhysicalLinkData, TopologyEventData,
+    };
+    use crate::nic::Role;
 
     #[test]
     fn test_network_from_logical_topology() {
         let (physical_topology, logical_topology) = crate::tests::logical_topology_2_2();
-        let network = Network::new(&logical_topology, physical_topology, 42);
+        let network = Network::new(
+            &logical_topology,
+            physical_topology,
+            &crate::client::ClientParams::default(),
+            &crate::purification::PurificationParams::default(),
+            &crate::nic::MemoryParams::default(),
+            &crate::signalling::SignallingParams::default(),
+            &crate::physical_topology::MobilityParams::default(),
+            crate::epr_register::Backend::BellDiagonal,
+            42,
+        );
         assert_eq!(10, network.nodes.len());
+        assert_eq!(10, network.oss.len());
+        assert_eq!(15, network.clients.len());
+    }
+
+    /// Run the network for a given duration, in s, and return the series
+    /// samples collected as (name, label, value).
+    fn run(network: &mut Network, duration: f64) -> Vec<(String, String, f64)> {
+        let mut events = crate::event_queue::EventQueue::default();
+        for event in network.initial() {
+            events.push(event);
+        }
+        let mut ret = vec![];
+        while let Some(event) = events.pop() {
+            if crate::utils::to_seconds(event.time()) > duration {
+                break;
+            }
+            let (new_events, samples) = network.handle(event);
+            for event in new_events {
+                events.push(event);
+            }
+            for sample in samples {
+                if let crate::output::Sample::Series(name, label, value) = sample {
+                    ret.push((name, label, value));
+                }
+            }
+        }
+        ret
+    }
+
+    #[test]
+    fn test_network_serve_clients() {
+        let (physical_topology, logical_topology) = crate::tests::logical_topology_2_2();
+        let mut network = Network::new(
+            &logical_topology,
+            physical_topology,
+            &crate::client::ClientParams::default(),
+            &crate::purification::PurificationParams::default(),
+            &crate::nic::MemoryParams::default(),
+            &crate::signalling::SignallingParams::default(),
+            &crate::physical_topology::MobilityParams::default(),
+            crate::epr_register::Backend::BellDiagonal,
+            42,
+        );
+
+        let mut num_fidelity_samples = 0;
+        for (name, _label, value) in run(&mut network, 10.0) {
+            assert!(!name.starts_with("purification"));
+            assert!(!name.starts_with("exact_fidelity"));
+            if name.starts_with("fidelity-") {
+                assert!((0.25..=1.0).contains(&value));
+                num_fidelity_samples += 1;
+            }
+        }
+        assert!(num_fidelity_samples > 0);
+    }
+
+    #[test]
+    fn test_network_link_budget() {
+        let mut num_generated = vec![];
+        for link_budget in [false, true] {
+            let (mut physical_topology, logical_topology) = crate::tests::logical_topology_2_2();
+            if link_budget {
+                physical_topology
+                    .set_link_budget(crate::link_budget::LinkBudgetParams::default())
+                    .unwrap();
+            }
+            let mut network = Network::new(
+                &logical_topology,
+                physical_topology,
+                &crate::client::ClientParams::default(),
+                &crate::purification::PurificationParams::default(),
+                &crate::nic::MemoryParams::default(),
+                &crate::signalling::SignallingParams::default(),
+                &crate::physical_topology::MobilityParams::default(),
+                crate::epr_register::Backend::BellDiagonal,
+                42,
+            );
+
+            let mut cnt = 0;
+            for (name, _label, value) in run(&mut network, 10.0) {
+                if name == "gen_fidelity" {
+                    if link_budget {
+                        assert!(value > 0.25 && value < 0.99, "{}", value);
+                    } else {
+                        assert_eq!(1.0, value);
+                    }
+                    cnt += 1;
+                }
+            }
+            num_generated.push(cnt);
+        }
+
+        // Photons lost reduce the rate at which EPR pairs are established.
+        assert!(num_generated[1] > 0);
+        assert!(num_generated[1] < num_generated[0]);
+    }
+
+    #[test]
+    fn test_network_purification() {
+        let (physical_topology, logical_topology) = crate::tests::logical_topology_2_2();
+        let mut network = Network::new(
+            &logical_topology,
+            physical_topology,
+            &crate::client::ClientParams::default(),
+            &crate::purification::PurificationParams {
+                protocol: crate::purification::Protocol::Dejmps,
+                policy: crate::purification::Policy::Pumping,
+                ..Default::default()
+            },
+            &crate::nic::MemoryParams::default(),
+            &crate::signalling::SignallingParams::default(),
+            &crate::physical_topology::MobilityParams::default(),
+            crate::epr_register::Backend::BellDiagonal,
+            42,
+        );
+
+        let mut num_attempts = 0;
+        let mut num_successes = 0;
+        for (name, _label, value) in run(&mut network, 10.0) {
+            if name == "purification_success" {
+                num_attempts += 1;
+                num_successes += value as u32;
+            } else if name == "purification_gain" {
+                assert!(value <= 1.0);
+            }
+        }
+        assert!(num_attempts > 0);
+        assert!(num_successes <= num_attempts);
+    }
+
+    #[test]
+    fn test_network_density_matrix() {
+        let (physical_topology, logical_topology) = crate::tests::logical_topology_2_2();
+        let mut network = Network::new(
+            &logical_topology,
+            physical_topology,
+            &crate::client::ClientParams::default(),
+            &crate::purification::PurificationParams {
+                protocol: crate::purification::Protocol::Dejmps,
+                policy: crate::purification::Policy::Pumping,
+                ..Default::default()
+            },
+            &crate::nic::MemoryParams::default(),
+            &crate::signalling::SignallingParams::default(),
+            &crate::physical_topology::MobilityParams::default(),
+            crate::epr_register::Backend::DensityMatrix,
+            42,
+        );
+
+        // The exact fidelities match the closed-form ones.
+        let mut num_fidelity_samples = 0;
+        let mut num_exact_samples = 0;
+        let mut num_error_samples = 0;
+        for (name, _label, value) in run(&mut network, 30.0) {
+            if name.starts_with("fidelity-") {
+                assert!((0.25..=1.0 + 1e-9).contains(&value));
+                num_fidelity_samples += 1;
+            } else if name.starts_with("exact_fidelity-") {
+                assert!((0.25..=1.0 + 1e-9).contains(&value));
+                num_exact_samples += 1;
+            } else if name.starts_with("fidelity_error") {
+                assert!(value.abs() < 1e-9, "{}", value);
+                num_error_samples += 1;
+            }
+        }
+        assert!(num_fidelity_samples > 0);
+        assert_eq!(num_fidelity_samples, num_exact_samples);
+        assert_eq!(num_fidelity_samples, num_error_samples);
+    }
+
+    #[test]
+    fn test_network_signalling() {
+        let (physical_topology, logical_topology) = crate::tests::logical_topology_2_2();
+        let mut network = Network::new(
+            &logical_topology,
+            physical_topology,
+            &crate::client::ClientParams::default(),
+            &crate::purification::PurificationParams::default(),
+            &crate::nic::MemoryParams::default(),
+            &crate::signalling::SignallingParams {
+                enabled: true,
+                processing_delay: 0.5,
+                ..Default::default()
+            },
+            &crate::physical_topology::MobilityParams::default(),
+            crate::epr_register::Backend::BellDiagonal,
+            42,
+        );
+
+        // Both ends are notified of a new EPR pair after the heralding delay.
+        let event = network
+            .initial()
+            .into_iter()
+            .find(|event| {
+                matches!(
+                    event.event_type,
+                    EventType::NodeEvent(NodeEventData::EprGenerated(_))
+                )
+            })
+            .unwrap();
+        let (tx_node_id, master_node_id, slave_node_id) = match &event.event_type {
+            EventType::NodeEvent(NodeEventData::EprGenerated(data)) => {
+                (data.tx_node_id, data.master_node_id, data.slave_node_id)
+            }
+            _ => panic!("unexpected event"),
+        };
+        let (events, _) = network.handle(event);
+        let mut num_notified = 0;
+        for event in events {
+            if let EventType::NodeEvent(NodeEventData::EprNotified(data)) = &event.event_type {
+                let delay = network
+                    .signalling_params
+                    .heralding_delay(
+                        &mut network.physical_topology,
+                        tx_node_id,
+                        data.this_node_id,
+                        data.peer_node_id,
+                    )
+                    .unwrap();
+                assert!(delay >= 0.5);
+                assert!([master_node_id, slave_node_id].contains(&data.this_node_id));
+                assert_eq!(crate::utils::to_nanoseconds(delay), event.time());
+                num_notified += 1;
+            }
+        }
+        assert_eq!(2, num_notified);
+    }
+
+    #[test]
+    fn test_network_memory_replacement() {
+        for replacement_policy in [
+            crate::nic::ReplacementPolicy::OverwriteOldest,
+            crate::nic::ReplacementPolicy::DropNewest,
+            crate::nic::ReplacementPolicy::OverwriteLowestFidelity,
+            crate::nic::ReplacementPolicy::Lifo,
+        ] {
+            let (physical_topology, logical_topology) = crate::tests::logical_topology_2_2();
+            let mut network = Network::new(
+                &logical_topology,
+                physical_topology,
+                &crate::client::ClientParams::default(),
+                &crate::purification::PurificationParams::default(),
+                &crate::nic::MemoryParams {
+                    replacement_policy,
+                    ..Default::default()
+                },
+                &crate::signalling::SignallingParams::default(),
+                &crate::physical_topology::MobilityParams::default(),
+                crate::epr_register::Backend::BellDiagonal,
+                42,
+            );
+
+            let mut num_drops = 0;
+            let mut num_overwrites = 0;
+            for (name, _label, _value) in run(&mut network, 10.0) {
+                if name == "memory_drop" {
+                    num_drops += 1;
+                } else if name == "memory_overwrite" {
+                    num_overwrites += 1;
+                }
+            }
+            if replacement_policy == crate::nic::ReplacementPolicy::DropNewest {
+                assert!(num_drops > 0);
+                assert_eq!(0, num_overwrites);
+            } else {
+                assert!(num_overwrites > 0);
+            }
+
+            // All the EPR pairs in memory must be known to the register.
+            for edge in logical_topology.graph().edge_references() {
+                let master_node_id = edge.source().index();
+                let slave_node_id = edge.target().index();
+                for (node_id, peer_node_id, role) in [
+                    (master_node_id, slave_node_id, crate::nic::Role::Master),
+                    (slave_node_id, master_node_id, crate::nic::Role::Slave),
+                ] {
+                    let nic = network.nodes[node_id]
+                        .nic(peer_node_id as u32, &role)
+                        .unwrap();
+                    for index in nic.valid_indices() {
+                        let (_, epr_pair_id) = nic.get(index).unwrap();
+                        assert!(network.epr_register.contains(epr_pair_id));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_network_memory_cutoff() {
+        for (cutoff, expected) in [
+            (crate::nic::Cutoff::Disabled, false),
+            (crate::nic::Cutoff::Age, true),
+            (crate::nic::Cutoff::Fidelity, true),
+        ] {
+            let (physical_topology, logical_topology) = crate::tests::logical_topology_2_2();
+            let mut network = Network::new(
+                &logical_topology,
+                physical_topology,
+                &crate::client::ClientParams::default(),
+                &crate::purification::PurificationParams::default(),
+                &crate::nic::MemoryParams {
+                    cutoff,
+                    cutoff_age: 0.01,
+                    cutoff_fidelity: 0.85,
+                    ..Default::default()
+                },
+                &crate::signalling::SignallingParams::default(),
+                &crate::physical_topology::MobilityParams::default(),
+                crate::epr_register::Backend::BellDiagonal,
+                42,
+            );
+
+            let num_cutoffs = run(&mut network, 10.0)
+                .iter()
+                .filter(|(name, _label, _value)| name == "memory_cutoff")
+                .count();
+            assert_eq!(expected, num_cutoffs > 0, "{:?}", cutoff);
+        }
+    }
+
+    #[test]
+    fn test_network_link_down_up() {
+        let (physical_topology, logical_topology) = crate::tests::logical_topology_2_2();
+        let mut network = Network::new(
+            &logical_topology,
+            physical_topology,
+            &crate::client::ClientParams::default(),
+            &crate::purification::PurificationParams::default(),
+            &crate::nic::MemoryParams::default(),
+            &crate::signalling::SignallingParams::default(),
+            &crate::physical_topology::MobilityParams::default(),
+            crate::epr_register::Backend::BellDiagonal,
+            42,
+        );
+        run(&mut network, 1.0);
+
+        // Take down the physical link between the tx and the slave of a
+        // logical link: all the EPR pairs stored are lost.
+        let generator = network.epr_generators.values().flatten().next().unwrap();
+        let (tx_node_id, master_node_id, slave_node_id) = (
+            generator.tx_node_id,
+            generator.master_node_id,
+            generator.slave_node_id,
+        );
+        let end_node_id = if slave_node_id != tx_node_id {
+            slave_node_id
+        } else {
+            master_node_id
+        };
+        let mut stored = std::collections::HashSet::new();
+        for (node_id, peer_node_id, role) in [
+            (master_node_id, slave_node_id, crate::nic::Role::Master),
+            (slave_node_id, master_node_id, crate::nic::Role::Slave),
+        ] {
+            let nic = network.nodes[node_id as usize]
+                .nic(peer_node_id, &role)
+                .unwrap();
+            for index in nic.valid_indices() {
+                stored.insert(nic.get(index).unwrap().1);
+            }
+        }
+        let (events, samples) = network.handle(crate::event::Event::new(
+            0.0,
+            EventType::TopologyEvent(TopologyEventData::LinkDown(PhysicalLinkData {
+                u: end_node_id,
+                v: tx_node_id,
+            })),
+        ));
+        assert!(events.is_empty());
+        let mut num_lost = 0.0;
+        for sample in samples {
+            if let crate::output::Sample::Series(name, label, value) = sample {
+                if name == "link_down_lost"
+                    && label == format!("{}-{}", master_node_id, slave_node_id)
+                {
+                    num_lost += value;
+                }
+            }
+        }
+        assert_eq!(stored.len() as f64, num_lost);
+        assert!(!network.is_active(master_node_id, slave_node_id));
+        for (node_id, peer_node_id, role) in [
+            (master_node_id, slave_node_id, crate::nic::Role::Master),
+            (slave_node_id, master_node_id, crate::nic::Role::Slave),
+        ] {
+            assert!(network.nodes[node_id as usize]
+                .nic(peer_node_id, &role)
+                .unwrap()
+                .valid_indices()
+                .is_empty());
+        }
+
+        // The generator stops.
+        let generated = |network: &mut Network| {
+            network.handle(crate::event::Event::new(
+                0.0,
+                EventType::NodeEvent(NodeEventData::EprGenerated(EprGeneratedData {
+                    tx_node_id,
+                    master_node_id,
+                    slave_node_id,
+                })),
+            ))
+        };
+        let (events, samples) = generated(&mut network);
+        assert!(events.is_empty());
+        assert!(samples.is_empty());
+
+        // Bring the link up again: the generator restarts.
+        let (events, _samples) = network.handle(crate::event::Event::new(
+            0.0,
+            EventType::TopologyEvent(TopologyEventData::LinkUp(PhysicalLinkData {
+                u: tx_node_id,
+                v: end_node_id,
+            })),
+        ));
+        assert_eq!(1, events.len());
+        assert!(network.is_active(master_node_id, slave_node_id));
+        let (events, _samples) = generated(&mut network);
+        assert!(!events.is_empty());
+    }
+
+    #[test]
+    fn test_network_link_down_reroute() {
+        // Physical links 0-1, 1-3, 0-2, and 2-3. On the logical topology,
+        // the path from 0 to 3 through 1 is shorter than that through 2.
+        let physical_topology = crate::physical_topology::PhysicalTopology::from_distances(
+            vec![(0, 1, 1.0), (1, 3, 1.0), (0, 2, 1.0), (2, 3, 1.0)],
+            crate::physical_topology::StaticFidelities::default(),
+        );
+        let mut graph = crate::logical_topology::Graph::new();
+        for _ in 0..4 {
+            graph.add_node(crate::logical_topology::NodeWeight {});
+        }
+        for (u, v, cost) in [(0, 1, 1), (1, 3, 1), (0, 2, 2), (2, 3, 1)] {
+            graph.add_edge(
+                u.into(),
+                v.into(),
+                crate::logical_topology::EdgeWeight {
+                    tx: u,
+                    memory_qubits: 2,
+                    capacity: 10.0,
+                    cost,
+                },
+            );
+        }
+        let logical_topology = crate::logical_topology::LogicalTopology::from_graph(graph).unwrap();
+        let mut network = Network::new(
+            &logical_topology,
+            physical_topology,
+            &crate::client::ClientParams::default(),
+            &crate::purification::PurificationParams::default(),
+            &crate::nic::MemoryParams::default(),
+            &crate::signalling::SignallingParams::default(),
+            &crate::physical_topology::MobilityParams::default(),
+            crate::epr_register::Backend::BellDiagonal,
+            42,
+        );
+        run(&mut network, 5.0);
+
+        // Return the swapping node and the memory cell reserved, if the
+        // request from 0 to 3 succeeds.
+        let request = |network: &mut Network, request_id| {
+            let (events, samples) = network.handle(crate::event::Event::new(
+                5.0,
+                EventType::OsEvent(OsEventData::EprRequestApp(EprFiveTuple {
+                    source_node_id: 0,
+                    source_port: 0,
+                    target_node_id: 3,
+                    target_port: 0,
+                    request_id,
+                })),
+            ));
+            let swapping_node_id = samples.iter().find_map(|sample| match sample {
+                crate::output::Sample::Series(name, label, _) if name == "swap_success" => {
+                    Some(label.parse::<u32>().unwrap())
+                }
+                _ => None,
+            });
+            let memory_cell = events.into_iter().find_map(|event| match event.event_type {
+                EventType::AppEvent(AppEventData::EprResponse(data)) => data.memory_cell,
+                _ => None,
+            });
+            (swapping_node_id, memory_cell)
+        };
+        let link_down = |network: &mut Network, u, v| {
+            let (_, samples) = network.handle(crate::event::Event::new(
+                5.0,
+                EventType::TopologyEvent(TopologyEventData::LinkDown(PhysicalLinkData { u, v })),
+            ));
+            samples
+        };
+
+        // The shortest path is used while all the links are up.
+        let (swapping_node_id, memory_cell) = request(&mut network, 0);
+        assert_eq!(Some(1), swapping_node_id);
+        let (neighbor_node_id, role, index) = memory_cell.unwrap();
+        assert_eq!(1, neighbor_node_id);
+
+        // With the link 0-1 down, the EPR pair reserved by the application
+        // is lost as well and the requests are routed through 2.
+        let samples = link_down(&mut network, 0, 1);
+        assert!(samples.iter().any(|sample| matches!(
+            sample,
+            crate::output::Sample::Series(name, label, value)
+                if name == "link_down_lost" && label == "0-1" && *value >= 1.0
+        )));
+        let nic = network.nodes[0].nic(1, &Role::Master).unwrap();
+        assert!(nic.valid_indices().is_empty());
+        assert!(nic.reserved_indices().is_empty());
+        let (events, samples) = network.handle(crate::event::Event::new(
+            5.0,
+            EventType::NodeEvent(NodeEventData::EprFidelity(EprFidelityData {
+                app_node_id: 0,
+                port: 0,
+                consume_node_id: 0,
+                neighbor_node_id,
+                role,
+                index,
+            })),
+        ));
+        assert!(events.is_empty());
+        assert!(samples.is_empty());
+        assert_eq!(Some(2), request(&mut network, 1).0);
+
+        // With the link 0-2 down, too, there is no path left.
+        link_down(&mut network, 0, 2);
+        assert_eq!((None, None), request(&mut network, 2));
+    }
+
+    #[test]
+    fn test_network_constellation() {
+        let mut sat_weight = crate::physical_topology::NodeWeight::default_sat();
+        sat_weight.memory_qubits = 10;
+        sat_weight.detectors = 10;
+        sat_weight.transmitters = 10;
+        sat_weight.capacity = 1.0;
+        let mut ogs_weight = crate::physical_topology::NodeWeight::default_ogs();
+        ogs_weight.memory_qubits = 10;
+        ogs_weight.detectors = 10;
+        let physical_topology = crate::physical_topology::PhysicalTopology::from_constellation(
+            crate::constellation::Constellation::new(
+                crate::constellation::WalkerParams::default(),
+                vec![
+                    crate::constellation::GroundStation {
+                        latitude: 43.72,
+                        longitude: 10.40,
+                    },
+                    crate::constellation::GroundStation {
+                        latitude: 41.90,
+                        longitude: 12.50,
+                    },
+                ],
+            )
+            .unwrap(),
+            sat_weight,
+            ogs_weight,
+            crate::physical_topology::StaticFidelities::default(),
+        )
+        .unwrap();
+        let logical_topology = crate::logical_topology::LogicalTopology::from_physical_topology(
+            &crate::logical_topology::PhysicalToLogicalPolicy::RandomGreedy,
+            &crate::topology_search::SearchParams::default(),
+            &crate::logical_topology::Allocation::default(),
+            &physical_topology,
+            &mut rand::rngs::StdRng::seed_from_u64(42),
+        )
+        .expect("cannot find a random greedy logical topology for the constellation at t=0");
+        let mut network = Network::new(
+            &logical_topology,
+            physical_topology,
+            &crate::client::ClientParams::default(),
+            &crate::purification::PurificationParams::default(),
+            &crate::nic::MemoryParams::default(),
+            &crate::signalling::SignallingParams::default(),
+            &crate::physical_topology::MobilityParams {
+                update_interval: 10.0,
+            },
+            crate::epr_register::Backend::BellDiagonal,
+            42,
+        );
+
+        // The ground links change as the satellites pass over the OGSs.
+        let samples = run(&mut network, 300.0);
+        let count = |name: &str| samples.iter().filter(|sample| sample.0 == name).count();
+        assert!(count("link_up") > 0);
+        assert!(count("link_down") > 0);
+        assert!(count("link_down_lost") > 0);
+
+        // Only the logical links whose physical links are all up are active,
+        // and those that are down have no EPR pairs stored.
+        for generator in network.epr_generators.values().flatten() {
+            let up = generator.physical_links().iter().all(|(u, v)| {
+                network
+                    .physical_topology
+                    .graph()
+                    .find_edge((*u).into(), (*v).into())
+                    .is_some()
+            });
+            assert_eq!(up, generator.active);
+            if !generator.active {
+                assert!(network.nodes[generator.master_node_id as usize]
+                    .nic(generator.slave_node_id, &crate::nic::Role::Master)
+                    .unwrap()
+                    .valid_indices()
+                    .is_empty());
+            }
+        }
     }
 
     #[test]
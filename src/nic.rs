@@ -8,35 +8,125 @@ pub enum MemoryCell {
     /// The memory cell contains half of a valid EPR, with given creation time
     /// and identifier.
     Valid(u64, u64),
+    /// The memory cell contains half of a valid EPR, with given creation time
+    /// and identifier, which has been reserved by the OS for an application.
+    Reserved(u64, u64),
 }
 
 impl PartialOrd for MemoryCell {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MemoryCell {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         match self {
             MemoryCell::Empty => match other {
-                MemoryCell::Empty => Some(std::cmp::Ordering::Equal),
-                MemoryCell::Valid(_other_time, _) => Some(std::cmp::Ordering::Less),
+                MemoryCell::Empty => std::cmp::Ordering::Equal,
+                _ => std::cmp::Ordering::Less,
             },
             MemoryCell::Valid(self_time, _) => match other {
-                MemoryCell::Empty => Some(std::cmp::Ordering::Greater),
-                MemoryCell::Valid(other_time, _) => self_time.partial_cmp(other_time),
+                MemoryCell::Empty => std::cmp::Ordering::Greater,
+                MemoryCell::Valid(other_time, _) => self_time.cmp(other_time),
+                MemoryCell::Reserved(_, _) => std::cmp::Ordering::Less,
+            },
+            MemoryCell::Reserved(self_time, _) => match other {
+                MemoryCell::Reserved(other_time, _) => self_time.cmp(other_time),
+                _ => std::cmp::Ordering::Greater,
             },
         }
     }
 }
 
-impl Ord for MemoryCell {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
-    }
-}
-
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Role {
     Master,
     Slave,
 }
 
+/// Policy used to store a fresh EPR pair when all the memory cells are full.
+#[derive(Debug, Default, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum ReplacementPolicy {
+    /// Overwrite the oldest valid EPR pair.
+    #[default]
+    OverwriteOldest,
+    /// Drop the fresh EPR pair.
+    DropNewest,
+    /// Overwrite the valid EPR pair with lowest fidelity.
+    OverwriteLowestFidelity,
+    /// Overwrite the most recent valid EPR pair.
+    Lifo,
+}
+
+/// Policy used to discard stale EPR pairs from the memory.
+#[derive(Debug, Default, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Cutoff {
+    /// EPR pairs are never discarded.
+    #[default]
+    Disabled,
+    /// Discard EPR pairs stored for longer than `cutoff_age`.
+    Age,
+    /// Discard EPR pairs whose fidelity drops below `cutoff_fidelity`.
+    Fidelity,
+}
+
+/// Quantum memory configuration.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MemoryParams {
+    /// Policy used to store fresh EPR pairs when the memory is full.
+    pub replacement_policy: ReplacementPolicy,
+    /// Policy used to discard stale EPR pairs.
+    pub cutoff: Cutoff,
+    /// Maximum storage time of an EPR pair, in s, only used with the
+    /// age cutoff.
+    pub cutoff_age: f64,
+    /// Minimum fidelity of an EPR pair, only used with the fidelity cutoff.
+    pub cutoff_fidelity: f64,
+}
+
+impl Default for MemoryParams {
+    fn default() -> Self {
+        Self {
+            replacement_policy: ReplacementPolicy::default(),
+            cutoff: Cutoff::default(),
+            cutoff_age: 1.0,
+            cutoff_fidelity: 0.5,
+        }
+    }
+}
+
+impl MemoryParams {
+    pub fn valid(&self) -> anyhow::Result<()> {
+        let mut errors = vec![];
+        if self.cutoff_age <= 0.0 {
+            errors.push(format!("non-positive cutoff age ({})", self.cutoff_age));
+        }
+        if self.cutoff_fidelity < 0.0 || self.cutoff_fidelity > 1.0 {
+            errors.push(format!(
+                "invalid cutoff fidelity ({})",
+                self.cutoff_fidelity
+            ));
+        }
+        if !errors.is_empty() {
+            anyhow::bail!("invalid memory parameters: {}", errors.join(","))
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of the addition of a fresh EPR pair to a NIC.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Admission {
+    /// The EPR pair has been stored in an empty memory cell.
+    Stored,
+    /// The EPR pair has been dropped.
+    Dropped,
+    /// The EPR pair has been stored by overwriting the one with given
+    /// identifier.
+    Overwritten(u64),
+}
+
 /// Quantum network interface card associated with a single peer.
 #[derive(Debug)]
 pub struct Nic {
@@ -44,45 +134,184 @@ pub struct Nic {
     role: Role,
     /// Quantum memory cells assigned to this NIC.
     memory_cells: Vec<MemoryCell>,
+    /// Policy used when all the memory cells are full.
+    replacement_policy: ReplacementPolicy,
 }
 
 impl Nic {
-    /// Create a NIC with a given role and number of quantum memory cells.
-    pub fn new(role: Role, num_qubits: u32) -> Self {
+    /// Create a NIC with a given role, number of quantum memory cells, and
+    /// replacement policy.
+    pub fn new(role: Role, num_qubits: u32, replacement_policy: ReplacementPolicy) -> Self {
         let mut memory_cells = vec![];
         for _ in 0..num_qubits {
             memory_cells.push(MemoryCell::Empty);
         }
-        Self { role, memory_cells }
+        Self {
+            role,
+            memory_cells,
+            replacement_policy,
+        }
+    }
+
+    /// Return the role of this NIC.
+    pub fn role(&self) -> &Role {
+        &self.role
     }
 
     /// Add a fresh EPR pair to an empty memory cell or, if not available,
-    /// overwrite the oldest non-empty memory cell.
-    pub fn add_epr_pair(&mut self, now: u64, epr_pair_id: u64) {
-        let index_of_oldest = self
+    /// proceed according to the replacement policy. Reserved memory cells are
+    /// never overwritten: if all of them are reserved the EPR pair is dropped.
+    ///
+    /// `fidelity` returns the current fidelity of an EPR pair given its
+    /// identifier and it is only used to overwrite the lowest-fidelity one.
+    pub fn add_epr_pair(
+        &mut self,
+        now: u64,
+        epr_pair_id: u64,
+        fidelity: &dyn Fn(u64) -> f64,
+    ) -> Admission {
+        if let Some(index) = self
+            .memory_cells
+            .iter()
+            .position(|cell| matches!(cell, MemoryCell::Empty))
+        {
+            self.memory_cells[index] = MemoryCell::Valid(now, epr_pair_id);
+            return Admission::Stored;
+        }
+
+        let valid_indices = self.valid_indices();
+        let index = match self.replacement_policy {
+            ReplacementPolicy::OverwriteOldest => valid_indices.last().cloned(),
+            ReplacementPolicy::DropNewest => None,
+            ReplacementPolicy::OverwriteLowestFidelity => valid_indices
+                .iter()
+                .rev()
+                .map(|index| {
+                    let (_, id) = self.get(*index).unwrap();
+                    (*index, fidelity(id))
+                })
+                .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(index, _)| index),
+            ReplacementPolicy::Lifo => valid_indices.first().cloned(),
+        };
+
+        match index {
+            Some(index) => {
+                let (_, old_epr_pair_id) = self.get(index).unwrap();
+                self.memory_cells[index] = MemoryCell::Valid(now, epr_pair_id);
+                Admission::Overwritten(old_epr_pair_id)
+            }
+            None => Admission::Dropped,
+        }
+    }
+
+    /// Return the indices of all the memory cells holding a valid, i.e.,
+    /// non-reserved, EPR pair, from the most recent to the oldest one.
+    pub fn valid_indices(&self) -> Vec<usize> {
+        let mut indices = self
             .memory_cells
             .iter()
             .enumerate()
-            .min_by(|(_, a), (_, b)| a.cmp(b))
-            .map(|(index, _)| index);
+            .filter(|(_, cell)| matches!(cell, MemoryCell::Valid(_, _)))
+            .map(|(index, _)| index)
+            .collect::<Vec<usize>>();
+        indices.sort_by(|a, b| self.memory_cells[*b].cmp(&self.memory_cells[*a]));
+        indices
+    }
 
-        if let Some(index) = index_of_oldest {
-            self.memory_cells[index] = MemoryCell::Valid(now, epr_pair_id);
+    /// Return the indices of all the memory cells holding a reserved EPR pair.
+    pub fn reserved_indices(&self) -> Vec<usize> {
+        self.memory_cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| matches!(cell, MemoryCell::Reserved(_, _)))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Return the creation time and identifier of the EPR pair in a memory
+    /// cell, which can be either valid or reserved.
+    pub fn get(&self, index: usize) -> Option<(u64, u64)> {
+        match self.memory_cells.get(index) {
+            Some(MemoryCell::Valid(creation_time, id))
+            | Some(MemoryCell::Reserved(creation_time, id)) => Some((*creation_time, *id)),
+            _ => None,
         }
     }
 
-    /// Consume an EPR pair. Return empty if the index is invalid or the memory
-    /// cell is empty, otherwise return the creation time and EPR pair ID.
-    pub fn consume(&mut self, index: usize) -> Option<(u64, u64)> {
-        let ret = if index < self.memory_cells.len() {
-            if let MemoryCell::Valid(creation_time, id) = self.memory_cells[index] {
-                Some((creation_time, id))
-            } else {
-                None
+    /// Return the index of the memory cell holding the given EPR pair, if any.
+    pub fn find(&self, epr_pair_id: u64) -> Option<usize> {
+        self.memory_cells.iter().position(|cell| match cell {
+            MemoryCell::Valid(_, id) | MemoryCell::Reserved(_, id) => *id == epr_pair_id,
+            MemoryCell::Empty => false,
+        })
+    }
+
+    /// Return true if the EPR pair is stored in a valid, i.e., non-reserved,
+    /// memory cell.
+    pub fn is_valid(&self, epr_pair_id: u64) -> bool {
+        self.memory_cells
+            .iter()
+            .any(|cell| matches!(cell, MemoryCell::Valid(_, id) if *id == epr_pair_id))
+    }
+
+    /// Discard a valid, i.e., non-reserved, EPR pair given its identifier.
+    /// Return false if not found.
+    pub fn discard(&mut self, epr_pair_id: u64) -> bool {
+        match self
+            .memory_cells
+            .iter()
+            .position(|cell| matches!(cell, MemoryCell::Valid(_, id) if *id == epr_pair_id))
+        {
+            Some(index) => {
+                self.memory_cells[index] = MemoryCell::Empty;
+                true
             }
+            None => false,
+        }
+    }
+
+    /// Reserve a valid EPR pair. Return empty if the index is invalid or the
+    /// memory cell is not valid, otherwise return the creation time and
+    /// EPR pair ID.
+    pub fn reserve(&mut self, index: usize) -> Option<(u64, u64)> {
+        if let Some(MemoryCell::Valid(creation_time, id)) = self.memory_cells.get(index) {
+            let ret = (*creation_time, *id);
+            self.memory_cells[index] = MemoryCell::Reserved(ret.0, ret.1);
+            Some(ret)
         } else {
             None
-        };
+        }
+    }
+
+    /// Release a reserved EPR pair, which becomes valid again. Return false
+    /// if the index is invalid or the memory cell is not reserved.
+    pub fn release(&mut self, index: usize) -> bool {
+        if let Some(MemoryCell::Reserved(creation_time, id)) = self.memory_cells.get(index) {
+            self.memory_cells[index] = MemoryCell::Valid(*creation_time, *id);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Replace the identifier of the EPR pair in a memory cell, e.g., after
+    /// entanglement swapping. Return false if the memory cell is empty or the
+    /// index is invalid.
+    pub fn replace(&mut self, index: usize, epr_pair_id: u64) -> bool {
+        match self.memory_cells.get_mut(index) {
+            Some(MemoryCell::Valid(_, id)) | Some(MemoryCell::Reserved(_, id)) => {
+                *id = epr_pair_id;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Consume an EPR pair. Return empty if the index is invalid or the memory
+    /// cell is empty, otherwise return the creation time and EPR pair ID.
+    pub fn consume(&mut self, index: usize) -> Option<(u64, u64)> {
+        let ret = self.get(index);
 
         // If the memory cell was valid, make it empty.
         if ret.is_some() {
@@ -94,13 +323,13 @@ impl Nic {
 
     /// Return the occupancy of the NIC, i.e., the number of non-empty memory
     /// cells divided by the total number of cells.
-    pub fn occupancy(&mut self) -> f64 {
-        if self.memory_cells.len() == 0 {
+    pub fn occupancy(&self) -> f64 {
+        if self.memory_cells.is_empty() {
             0.0
         } else {
             self.memory_cells
                 .iter()
-                .map(|cell| matches!(cell, MemoryCell::Valid(_, _)) as u32)
+                .map(|cell| !matches!(cell, MemoryCell::Empty) as u32)
                 .sum::<u32>() as f64
                 / self.memory_cells.len() as f64
         }
@@ -111,11 +340,11 @@ impl Nic {
 mod tests {
     use crate::nic::MemoryCell;
 
-    use super::{Nic, Role};
+    use super::{Admission, Nic, ReplacementPolicy, Role};
 
     #[test]
     fn test_nic_add_consume_epr_pairs() {
-        let mut nic = Nic::new(Role::Master, 10);
+        let mut nic = Nic::new(Role::Master, 10, ReplacementPolicy::OverwriteOldest);
 
         for cell in &nic.memory_cells {
             assert!(matches!(cell, MemoryCell::Empty));
@@ -124,7 +353,7 @@ mod tests {
         assert_float_eq::assert_f64_near!(0.0, nic.occupancy());
 
         for i in 0..10 {
-            nic.add_epr_pair(i + 100, i);
+            nic.add_epr_pair(i + 100, i, &|_| 1.0);
             assert_float_eq::assert_f64_near!(0.1 * (i + 1) as f64, nic.occupancy());
         }
 
@@ -151,15 +380,18 @@ mod tests {
 
         // Re-add them all.
         for i in 0..10 {
-            nic.add_epr_pair(i + 100, i);
+            nic.add_epr_pair(i + 100, i, &|_| 1.0);
         }
 
         // Plus a new one.
-        nic.add_epr_pair(999, 42);
+        assert_eq!(
+            Admission::Overwritten(0),
+            nic.add_epr_pair(999, 42, &|_| 1.0)
+        );
 
         for cell in &nic.memory_cells {
             match cell {
-                MemoryCell::Empty => panic!("invalid empty cell"),
+                MemoryCell::Empty | MemoryCell::Reserved(_, _) => panic!("invalid cell"),
                 MemoryCell::Valid(created, identifier) => {
                     assert!((*created >= 101 && *created <= 109) || *created == 999);
                     assert!((*identifier >= 1 && *identifier <= 9) || *identifier == 42);
@@ -169,6 +401,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::nonminimal_bool)]
     fn test_nic_memory_cell_order() {
         assert!(MemoryCell::Empty == MemoryCell::Empty);
         assert!(MemoryCell::Empty <= MemoryCell::Empty);
@@ -193,5 +426,124 @@ mod tests {
         assert!(!(MemoryCell::Valid(100, 0) == MemoryCell::Valid(200, 0)));
         assert!(MemoryCell::Valid(100, 0) <= MemoryCell::Valid(200, 0));
         assert!(MemoryCell::Valid(100, 0) < MemoryCell::Valid(200, 0));
+
+        assert!(MemoryCell::Empty < MemoryCell::Reserved(100, 0));
+        assert!(MemoryCell::Valid(200, 0) < MemoryCell::Reserved(100, 0));
+        assert!(MemoryCell::Reserved(100, 0) < MemoryCell::Reserved(200, 0));
+    }
+
+    #[test]
+    fn test_nic_reserve_epr_pairs() {
+        let mut nic = Nic::new(Role::Slave, 3, ReplacementPolicy::OverwriteOldest);
+        assert!(nic.valid_indices().is_empty());
+        assert!(nic.reserve(0).is_none());
+
+        for i in 0..3 {
+            nic.add_epr_pair(i + 100, i, &|_| 1.0);
+        }
+        assert_eq!(vec![2, 1, 0], nic.valid_indices());
+        assert_eq!(Some(1), nic.find(1));
+        assert!(nic.find(42).is_none());
+
+        // Reserve the newest and oldest EPR pairs.
+        assert_eq!(Some((102, 2)), nic.reserve(2));
+        assert!(nic.reserve(2).is_none());
+        assert_eq!(Some((100, 0)), nic.reserve(0));
+        assert_eq!(vec![1], nic.valid_indices());
+        assert_eq!(Some(2), nic.find(2));
+
+        // A new EPR pair can only overwrite the non-reserved one.
+        assert_eq!(
+            Admission::Overwritten(1),
+            nic.add_epr_pair(200, 10, &|_| 1.0)
+        );
+        assert_eq!(Some((200, 10)), nic.get(1));
+        assert_eq!(Some((100, 0)), nic.get(0));
+        assert_eq!(Some((102, 2)), nic.get(2));
+
+        // When all the cells are reserved, new EPR pairs are dropped.
+        assert!(nic.reserve(1).is_some());
+        assert_eq!(Admission::Dropped, nic.add_epr_pair(300, 11, &|_| 1.0));
+        assert!(nic.find(11).is_none());
+        assert_float_eq::assert_f64_near!(1.0, nic.occupancy());
+
+        // The EPR pair identifier can be replaced, both in valid and reserved
+        // memory cells.
+        assert!(nic.replace(1, 20));
+        assert_eq!(Some((200, 20)), nic.get(1));
+        assert!(nic.replace(0, 21));
+        assert_eq!(Some((100, 21)), nic.get(0));
+        nic.consume(0);
+        assert!(!nic.replace(0, 22));
+        assert!(!nic.replace(99, 22));
+
+        // Reserved EPR pairs can be consumed.
+        assert_eq!(Some((200, 20)), nic.consume(1));
+        assert!(nic.get(1).is_none());
+
+        // Reserved EPR pairs can be released.
+        assert!(!nic.release(1));
+        assert!(!nic.release(99));
+        assert!(nic.release(2));
+        assert!(!nic.release(2));
+        assert_eq!(vec![2], nic.valid_indices());
+        assert!(nic.is_valid(2));
+    }
+
+    #[test]
+    fn test_nic_replacement_policies() {
+        let fill = |policy| {
+            let mut nic = Nic::new(Role::Master, 3, policy);
+            for i in 0..3 {
+                assert_eq!(Admission::Stored, nic.add_epr_pair(i + 100, i, &|_| 1.0));
+            }
+            nic
+        };
+        // The fidelity is lowest for the EPR pair in the middle.
+        let fidelity = |id| if id == 1 { 0.5 } else { 0.9 };
+
+        let mut nic = fill(ReplacementPolicy::OverwriteOldest);
+        assert_eq!(
+            Admission::Overwritten(0),
+            nic.add_epr_pair(200, 10, &fidelity)
+        );
+        assert_eq!(Some(0), nic.find(10));
+
+        let mut nic = fill(ReplacementPolicy::DropNewest);
+        assert_eq!(Admission::Dropped, nic.add_epr_pair(200, 10, &fidelity));
+        assert!(nic.find(10).is_none());
+
+        let mut nic = fill(ReplacementPolicy::OverwriteLowestFidelity);
+        assert_eq!(
+            Admission::Overwritten(1),
+            nic.add_epr_pair(200, 10, &fidelity)
+        );
+        assert_eq!(Some(1), nic.find(10));
+        assert_eq!(
+            Admission::Overwritten(0),
+            nic.add_epr_pair(201, 11, &|_| 0.9)
+        );
+
+        let mut nic = fill(ReplacementPolicy::Lifo);
+        assert_eq!(
+            Admission::Overwritten(2),
+            nic.add_epr_pair(200, 10, &fidelity)
+        );
+        assert_eq!(
+            Admission::Overwritten(10),
+            nic.add_epr_pair(201, 11, &fidelity)
+        );
+        assert_eq!(Some(2), nic.find(11));
+
+        // Reserved EPR pairs cannot be discarded.
+        assert!(nic.is_valid(0));
+        assert!(nic.reserve(0).is_some());
+        assert!(!nic.is_valid(0));
+        assert_eq!(vec![nic.find(0).unwrap()], nic.reserved_indices());
+        assert!(!nic.discard(0));
+        assert!(nic.discard(1));
+        assert!(!nic.discard(1));
+        assert!(!nic.is_valid(1));
+        assert!(nic.get(1).is_none());
     }
 }
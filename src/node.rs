@@ -28,26 +28,59 @@ impl Node {
     /// - `peer_node_id`: the identifier of the peer node
     /// - `role`: the role of this node in the logical link
     /// - `num_qubits`: how many quantum memory cells there will be
+    /// - `replacement_policy`: what to do when the memory cells are full
     ///
     /// Return true if `peer_node_id` was already present with same role for
     /// this node.
-    pub fn add_nic(&mut self, peer_node_id: u32, role: super::nic::Role, num_qubits: u32) -> bool {
+    pub fn add_nic(
+        &mut self,
+        peer_node_id: u32,
+        role: super::nic::Role,
+        num_qubits: u32,
+        replacement_policy: super::nic::ReplacementPolicy,
+    ) -> bool {
         self.nics(&role)
-            .insert(peer_node_id, super::nic::Nic::new(role, num_qubits))
+            .insert(
+                peer_node_id,
+                super::nic::Nic::new(role, num_qubits, replacement_policy),
+            )
             .is_none()
     }
 
-    /// Notify that a new EPR has been established. Return the occupancy ratio.
+    /// Notify that a new EPR has been established. Return the occupancy ratio
+    /// and the outcome of the addition to the memory.
+    ///
+    /// `fidelity` returns the current fidelity of an EPR pair given its
+    /// identifier.
     pub fn epr_established(
         &mut self,
         now: u64,
         peer_node_id: u32,
         role: super::nic::Role,
         epr_pair_id: u64,
-    ) -> f64 {
+        fidelity: &dyn Fn(u64) -> f64,
+    ) -> (f64, super::nic::Admission) {
         let nic = self.get_nic(peer_node_id, &role);
-        nic.add_epr_pair(now, epr_pair_id);
-        nic.occupancy()
+        let admission = nic.add_epr_pair(now, epr_pair_id, fidelity);
+        (nic.occupancy(), admission)
+    }
+
+    /// Return true if the EPR pair is stored in a valid memory cell in any of
+    /// the NICs.
+    pub fn is_valid(&self, epr_pair_id: u64) -> bool {
+        self.nics_master
+            .values()
+            .chain(self.nics_slave.values())
+            .any(|nic| nic.is_valid(epr_pair_id))
+    }
+
+    /// Discard the qubit of an EPR pair stored in a valid memory cell in any
+    /// of the NICs. Return false if not found.
+    pub fn discard(&mut self, epr_pair_id: u64) -> bool {
+        self.nics_master
+            .values_mut()
+            .chain(self.nics_slave.values_mut())
+            .any(|nic| nic.discard(epr_pair_id))
     }
 
     /// Consume the qubit of an EPR stored in a memory cell in one of the NICs.
@@ -61,6 +94,43 @@ impl Node {
         self.get_nic(peer_node_id, role).consume(index)
     }
 
+    /// Reserve the qubit of an EPR stored in a memory cell in one of the NICs.
+    /// Return the creation time and identifier.
+    pub fn reserve(
+        &mut self,
+        peer_node_id: u32,
+        role: &super::nic::Role,
+        index: usize,
+    ) -> Option<(u64, u64)> {
+        self.get_nic(peer_node_id, role).reserve(index)
+    }
+
+    /// Release the qubit of an EPR reserved in a memory cell in one of the
+    /// NICs. Return false if the memory cell is not reserved.
+    pub fn release(&mut self, peer_node_id: u32, role: &super::nic::Role, index: usize) -> bool {
+        self.get_nic(peer_node_id, role).release(index)
+    }
+
+    /// Replace the identifier of the EPR pair stored in a memory cell in one of
+    /// the NICs. Return false if the memory cell is empty.
+    pub fn replace(
+        &mut self,
+        peer_node_id: u32,
+        role: &super::nic::Role,
+        index: usize,
+        epr_pair_id: u64,
+    ) -> bool {
+        self.get_nic(peer_node_id, role).replace(index, epr_pair_id)
+    }
+
+    /// Return the NIC for a given peer node and role, if any.
+    pub fn nic(&self, peer_node_id: u32, role: &super::nic::Role) -> Option<&super::nic::Nic> {
+        match role {
+            super::nic::Role::Master => self.nics_master.get(&peer_node_id),
+            super::nic::Role::Slave => self.nics_slave.get(&peer_node_id),
+        }
+    }
+
     /// Return the right set of NICs depending on the role.
     fn nics(
         &mut self,
@@ -74,8 +144,12 @@ impl Node {
 
     /// Return the NIC for a given peer node and role.
     fn get_nic(&mut self, peer_node_id: u32, role: &super::nic::Role) -> &mut super::nic::Nic {
-        self.nics(role)
-            .get_mut(&peer_node_id)
-            .unwrap_or_else(|| panic!("could not find NIC for peer {} ({:?})", peer_node_id, role))
+        let node_id = self.node_id;
+        self.nics(role).get_mut(&peer_node_id).unwrap_or_else(|| {
+            panic!(
+                "could not find NIC for peer {} ({:?}) at node {}",
+                peer_node_id, role, node_id
+            )
+        })
     }
 }
@@ -0,0 +1,520 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+use rand::{Rng, SeedableRng};
+
+use crate::event::*;
+use crate::output::Sample;
+
+/// Quantum operating system running on a node.
+///
+/// The OS serves the EPR requests of the applications running on its node.
+/// For every request it selects the path towards the target node on the
+/// logical topology, then it looks for an EPR pair on every logical link of
+/// the path: if any is missing the request fails immediately, otherwise:
+///
+/// - the memory cell at the source node is reserved for the application;
+/// - entanglement swapping is performed at every intermediate node, which
+///   succeeds with the swapping success probability of the node: if any
+///   swap fails then all the EPR pairs along the path are lost and the
+///   request fails;
+/// - the qubit at the target node is consumed, since it is handed over to the
+///   remote application.
+///
+/// The end-points learn the outcome of the swaps when they receive the
+/// classical messages from all the intermediate nodes: until then the
+/// response to the application is delayed and their qubits keep decaying.
+#[derive(Debug)]
+pub struct Os {
+    /// Identifier of the node where the OS runs.
+    node_id: u32,
+    /// Paths towards the other nodes, indexed by the target node ID.
+    /// Each path includes both the end-points.
+    paths: std::collections::HashMap<u32, Vec<u32>>,
+    /// Pseudo-random number generator.
+    rng: rand::rngs::StdRng,
+}
+
+impl Os {
+    /// Create the OS of a node using the paths of the logical topology.
+    pub fn new(
+        node_id: u32,
+        logical_topology: &crate::logical_topology::LogicalTopology,
+        seed: u64,
+    ) -> Self {
+        let mut os = Self {
+            node_id,
+            paths: std::collections::HashMap::new(),
+            rng: rand::rngs::StdRng::seed_from_u64(seed),
+        };
+        os.set_paths(logical_topology);
+        os
+    }
+
+    /// Replace the paths towards the other nodes with those of a logical
+    /// topology, e.g., after some of its logical links went down or up.
+    pub fn set_paths(&mut self, logical_topology: &crate::logical_topology::LogicalTopology) {
+        self.paths.clear();
+        for target in logical_topology.graph().node_indices() {
+            let target = target.index() as u32;
+            if let Some(path) = logical_topology.path(self.node_id, target) {
+                self.paths.insert(target, path);
+            }
+        }
+    }
+
+    /// Serve an EPR request from an application running on this node.
+    pub fn handle_epr_request_app(
+        &mut self,
+        now: u64,
+        epr: EprFiveTuple,
+        nodes: &mut [crate::node::Node],
+        epr_register: &mut crate::epr_register::EprRegister,
+        physical_topology: &mut crate::physical_topology::PhysicalTopology,
+        signalling_params: &crate::signalling::SignallingParams,
+    ) -> (Vec<Event>, Vec<Sample>) {
+        assert_eq!(self.node_id, epr.source_node_id);
+
+        let label = format!("{}-{}", epr.source_node_id, epr.target_node_id);
+        let hops = match self.paths.get(&epr.target_node_id) {
+            Some(path) => find_epr_pairs(path, nodes),
+            None => None,
+        };
+
+        let mut samples = vec![];
+        let hops = match hops {
+            Some(hops) => hops,
+            None => return failure(epr, label, samples, 0.0),
+        };
+
+        // The outcome of every swap is notified to both end-points: the
+        // request fails if a swapping node cannot reach them.
+        let first_hop = hops.first().expect("empty path");
+        let last_hop = hops.last().expect("empty path");
+        let delays = hops
+            .iter()
+            .skip(1)
+            .map(|hop| {
+                Some((
+                    signalling_params.message_delay(
+                        physical_topology,
+                        hop.master,
+                        first_hop.master,
+                    )?,
+                    signalling_params.message_delay(
+                        physical_topology,
+                        hop.master,
+                        last_hop.slave,
+                    )?,
+                ))
+            })
+            .collect::<Option<Vec<(f64, f64)>>>();
+        let delays = match delays {
+            Some(delays) => delays,
+            None => return failure(epr, label, samples, 0.0),
+        };
+
+        // Reserve the qubit at the source.
+        let res = nodes[first_hop.master as usize].reserve(
+            first_hop.slave,
+            &crate::nic::Role::Master,
+            first_hop.master_index,
+        );
+        assert!(res.is_some());
+
+        // Swap the EPR pair from the source with those of the next hops.
+        let mut epr_pair_id = first_hop.epr_pair_id;
+        let mut source_delay = 0.0_f64;
+        let mut target_delay = 0.0_f64;
+        for ((prev_hop, hop), (hop_source_delay, hop_target_delay)) in
+            hops.iter().zip(hops.iter().skip(1)).zip(delays)
+        {
+            let swapping_node_id = hop.master;
+            let swapping_success_prob = physical_topology
+                .graph()
+                .node_weight(swapping_node_id.into())
+                .unwrap_or_else(|| panic!("no such node {}", swapping_node_id))
+                .swapping_success_prob;
+            source_delay = source_delay.max(hop_source_delay);
+            target_delay = target_delay.max(hop_target_delay);
+
+            // The qubits at the swapping node are consumed in any case.
+            nodes[swapping_node_id as usize]
+                .consume(
+                    prev_hop.master,
+                    &crate::nic::Role::Slave,
+                    prev_hop.slave_index,
+                )
+                .expect("no EPR found at the swapping node");
+            nodes[swapping_node_id as usize]
+                .consume(hop.slave, &crate::nic::Role::Master, hop.master_index)
+                .expect("no EPR found at the swapping node");
+
+            let success = self.rng.gen_bool(swapping_success_prob);
+            samples.push(Sample::Series(
+                "swap_success".to_string(),
+                swapping_node_id.to_string(),
+                success as u32 as f64,
+            ));
+
+            if success {
+                let (new_epr_pair_id, fidelity) = epr_register
+                    .swap(epr_pair_id, hop.epr_pair_id, swapping_node_id, now)
+                    .unwrap_or_else(|| panic!("invalid swap at node {}", swapping_node_id));
+                samples.push(Sample::Series(
+                    "swap_fidelity".to_string(),
+                    swapping_node_id.to_string(),
+                    fidelity,
+                ));
+                epr_pair_id = new_epr_pair_id;
+
+                // Update the identifiers at the end-points of the new pair.
+                let res = nodes[first_hop.master as usize].replace(
+                    first_hop.slave,
+                    &crate::nic::Role::Master,
+                    first_hop.master_index,
+                    epr_pair_id,
+                );
+                assert!(res);
+                let res = nodes[hop.slave as usize].replace(
+                    hop.master,
+                    &crate::nic::Role::Slave,
+                    hop.slave_index,
+                    epr_pair_id,
+                );
+                assert!(res);
+            } else {
+                // Free the memory cells at the end-points and forget the
+                // EPR pairs.
+                nodes[first_hop.master as usize].consume(
+                    first_hop.slave,
+                    &crate::nic::Role::Master,
+                    first_hop.master_index,
+                );
+                nodes[hop.slave as usize].consume(
+                    hop.master,
+                    &crate::nic::Role::Slave,
+                    hop.slave_index,
+                );
+                epr_register.remove(epr_pair_id);
+                epr_register.remove(hop.epr_pair_id);
+                return failure(epr, label, samples, source_delay);
+            }
+        }
+
+        // Consume the qubit at the target, when it is handed over to the
+        // remote application.
+        let (_, target_epr_pair_id) = nodes[last_hop.slave as usize]
+            .consume(
+                last_hop.master,
+                &crate::nic::Role::Slave,
+                last_hop.slave_index,
+            )
+            .expect("no EPR found at the target node");
+        assert_eq!(epr_pair_id, target_epr_pair_id);
+        let res = epr_register.consume(
+            epr_pair_id,
+            last_hop.slave,
+            now + crate::utils::to_nanoseconds(target_delay),
+        );
+        assert!(res.is_some(), "EPR pair not found {}", epr_pair_id);
+
+        samples.push(Sample::Series("os_success".to_string(), label.clone(), 1.0));
+        samples.push(Sample::Series(
+            "os_path_length".to_string(),
+            label,
+            hops.len() as f64,
+        ));
+        (
+            vec![
+                Event::new(
+                    target_delay,
+                    EventType::AppEvent(AppEventData::RemoteComplete(epr.clone())),
+                ),
+                Event::new(
+                    source_delay,
+                    EventType::AppEvent(AppEventData::EprResponse(EprResponseData {
+                        epr,
+                        memory_cell: Some((
+                            first_hop.slave,
+                            crate::nic::Role::Master,
+                            first_hop.master_index,
+                        )),
+                    })),
+                ),
+            ],
+            samples,
+        )
+    }
+
+    /// Create the OS of a node with given paths.
+    #[cfg(test)]
+    fn from_paths(node_id: u32, paths: Vec<Vec<u32>>, seed: u64) -> Self {
+        Self {
+            node_id,
+            paths: paths
+                .into_iter()
+                .map(|path| (*path.last().unwrap(), path))
+                .collect(),
+            rng: rand::rngs::StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+/// Return the response to a failed EPR request, after a given delay, in s.
+fn failure(
+    epr: EprFiveTuple,
+    label: String,
+    mut samples: Vec<Sample>,
+    delay: f64,
+) -> (Vec<Event>, Vec<Sample>) {
+    samples.push(Sample::Series("os_success".to_string(), label, 0.0));
+    (
+        vec![Event::new(
+            delay,
+            EventType::AppEvent(AppEventData::EprResponse(EprResponseData {
+                epr,
+                memory_cell: None,
+            })),
+        )],
+        samples,
+    )
+}
+
+/// EPR pair available on a logical link.
+#[derive(Debug)]
+struct Hop {
+    /// Master node ID.
+    master: u32,
+    /// Slave node ID.
+    slave: u32,
+    /// Index of the memory cell in the master NIC.
+    master_index: usize,
+    /// Index of the memory cell in the slave NIC.
+    slave_index: usize,
+    /// Identifier of the EPR pair.
+    epr_pair_id: u64,
+}
+
+/// Find one EPR pair on every logical link of the given path, where each
+/// node is the master of the link towards the next one. Return None if
+/// there is no EPR pair available on some link.
+fn find_epr_pairs(path: &[u32], nodes: &[crate::node::Node]) -> Option<Vec<Hop>> {
+    let mut hops = vec![];
+    for hop in path.windows(2) {
+        let master = hop[0];
+        let slave = hop[1];
+        let master_nic = nodes[master as usize].nic(slave, &crate::nic::Role::Master)?;
+        let slave_nic = nodes[slave as usize].nic(master, &crate::nic::Role::Slave)?;
+
+        // Use the most recent EPR pair that is available at both ends.
+        let mut found = None;
+        for master_index in master_nic.valid_indices() {
+            let (_, epr_pair_id) = master_nic.get(master_index)?;
+            if let Some(slave_index) = slave_nic.find(epr_pair_id) {
+                if slave_nic.valid_indices().contains(&slave_index) {
+                    found = Some(Hop {
+                        master,
+                        slave,
+                        master_index,
+                        slave_index,
+                        epr_pair_id,
+                    });
+                    break;
+                }
+            }
+        }
+        hops.push(found?);
+    }
+    Some(hops)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Os;
+    use crate::epr_register::EprRegister;
+    use crate::event::*;
+    use crate::nic::{ReplacementPolicy, Role};
+    use crate::node::Node;
+    use crate::physical_topology::{ChainParams, NodeWeight, PhysicalTopology, StaticFidelities};
+    use crate::signalling::SignallingParams;
+
+    /// Chain OGS 0 -> SAT 2 -> OGS 1 with two memory cells per NIC.
+    fn chain(swapping_success_prob: f64) -> (Vec<Node>, EprRegister, PhysicalTopology) {
+        let mut nodes = vec![Node::new(0), Node::new(1), Node::new(2)];
+        for (master, slave) in [(0, 2), (2, 1)] {
+            nodes[master as usize].add_nic(
+                slave,
+                Role::Master,
+                2,
+                ReplacementPolicy::OverwriteOldest,
+            );
+            nodes[slave as usize].add_nic(
+                master,
+                Role::Slave,
+                2,
+                ReplacementPolicy::OverwriteOldest,
+            );
+        }
+        let mut sat_weight = NodeWeight::default_sat();
+        sat_weight.swapping_success_prob = swapping_success_prob;
+        let physical_topology = PhysicalTopology::from_chain_static(
+            ChainParams::default(),
+            sat_weight,
+            NodeWeight::default_ogs(),
+            StaticFidelities::default(),
+        )
+        .unwrap();
+        (nodes, EprRegister::default(), physical_topology)
+    }
+
+    fn new_epr_pair(
+        nodes: &mut [Node],
+        epr_register: &mut EprRegister,
+        master: u32,
+        slave: u32,
+        now: u64,
+        fidelity: f64,
+    ) -> u64 {
+        let epr_pair_id = epr_register.new_epr_pair(master, slave, now, fidelity);
+        nodes[master as usize].epr_established(now, slave, Role::Master, epr_pair_id, &|_| 1.0);
+        nodes[slave as usize].epr_established(now, master, Role::Slave, epr_pair_id, &|_| 1.0);
+        epr_pair_id
+    }
+
+    fn request(request_id: u64) -> EprFiveTuple {
+        EprFiveTuple {
+            source_node_id: 0,
+            source_port: 0,
+            target_node_id: 1,
+            target_port: 0,
+            request_id,
+        }
+    }
+
+    #[test]
+    fn test_os_epr_request_app() {
+        let (mut nodes, mut epr_register, mut physical_topology) = chain(1.0);
+        let mut os = Os::from_paths(0, vec![vec![0, 2], vec![0, 2, 1]], 42);
+        let params = SignallingParams {
+            enabled: true,
+            ..Default::default()
+        };
+        let source_delay = params.message_delay(&mut physical_topology, 2, 0).unwrap();
+        let target_delay = params.message_delay(&mut physical_topology, 2, 1).unwrap();
+        let mut handle = |request_id, nodes: &mut [Node], epr_register: &mut EprRegister| {
+            os.handle_epr_request_app(
+                1000,
+                request(request_id),
+                nodes,
+                epr_register,
+                &mut physical_topology,
+                &params,
+            )
+        };
+
+        // No EPR pairs: the request fails.
+        let (events, _) = handle(0, &mut nodes, &mut epr_register);
+        assert_eq!(1, events.len());
+        assert!(matches!(
+            &events[0].event_type,
+            EventType::AppEvent(AppEventData::EprResponse(EprResponseData {
+                memory_cell: None,
+                ..
+            }))
+        ));
+
+        // EPR pairs only on the first link: the request fails.
+        new_epr_pair(&mut nodes, &mut epr_register, 0, 2, 100, 0.9);
+        let (events, _) = handle(1, &mut nodes, &mut epr_register);
+        assert_eq!(1, events.len());
+
+        // EPR pairs on both links: the request succeeds.
+        new_epr_pair(&mut nodes, &mut epr_register, 2, 1, 200, 0.8);
+        new_epr_pair(&mut nodes, &mut epr_register, 0, 2, 300, 0.9);
+        let (events, samples) = handle(2, &mut nodes, &mut epr_register);
+        assert_eq!(2, events.len());
+        assert_eq!(4, samples.len());
+        assert_eq!(
+            EventType::AppEvent(AppEventData::EprResponse(EprResponseData {
+                epr: request(2),
+                memory_cell: Some((2, Role::Master, 1)),
+            })),
+            events[1].event_type
+        );
+
+        // The end-points learn the outcome of the swap after the delay of
+        // the classical messages from the intermediate node.
+        assert!(source_delay > 0.0);
+        assert_eq!(crate::utils::to_nanoseconds(target_delay), events[0].time());
+        assert_eq!(crate::utils::to_nanoseconds(source_delay), events[1].time());
+
+        // The most recent EPR pair at the source is reserved and now refers
+        // to the end-to-end EPR pair, the other qubits along the path have
+        // been consumed.
+        let nic = nodes[0].nic(2, &Role::Master).unwrap();
+        assert_eq!(vec![0], nic.valid_indices());
+        let (created, epr_pair_id) = nic.get(1).unwrap();
+        assert_eq!(300, created);
+        assert_eq!(3, epr_pair_id);
+        assert!(nodes[2].nic(0, &Role::Slave).unwrap().find(2).is_none());
+        assert!(nodes[2].nic(1, &Role::Master).unwrap().get(0).is_none());
+        assert!(nodes[1].nic(2, &Role::Slave).unwrap().get(0).is_none());
+        assert!(epr_register.consume(3, 1, 1000).is_none());
+        let fidelity = epr_register.consume(3, 0, 1000).unwrap();
+        assert_float_eq::assert_f64_near!(0.9 * 0.8 + 0.1 * 0.2 / 3.0, fidelity);
+
+        // The remaining EPR pair on the first link is not enough.
+        let (events, _) = handle(3, &mut nodes, &mut epr_register);
+        assert_eq!(1, events.len());
+    }
+
+    #[test]
+    fn test_os_epr_request_app_swap_failure() {
+        let (mut nodes, mut epr_register, mut physical_topology) = chain(0.0);
+        let mut os = Os::from_paths(0, vec![vec![0, 2, 1]], 42);
+
+        let id_a = new_epr_pair(&mut nodes, &mut epr_register, 0, 2, 100, 1.0);
+        let id_b = new_epr_pair(&mut nodes, &mut epr_register, 2, 1, 100, 1.0);
+        let (events, samples) = os.handle_epr_request_app(
+            1000,
+            request(0),
+            &mut nodes,
+            &mut epr_register,
+            &mut physical_topology,
+            &SignallingParams::default(),
+        );
+        assert_eq!(1, events.len());
+        assert_eq!(2, samples.len());
+
+        // All the memory cells and EPR pairs are freed.
+        for (node_id, peer_node_id, role) in [
+            (0, 2, Role::Master),
+            (2, 0, Role::Slave),
+            (2, 1, Role::Master),
+            (1, 2, Role::Slave),
+        ] {
+            let nic = nodes[node_id].nic(peer_node_id, &role).unwrap();
+            assert_float_eq::assert_f64_near!(0.0, nic.occupancy());
+        }
+        assert!(!epr_register.remove(id_a));
+        assert!(!epr_register.remove(id_b));
+    }
+
+    #[test]
+    fn test_os_epr_request_app_unknown_target() {
+        let (mut nodes, mut epr_register, mut physical_topology) = chain(1.0);
+        let mut os = Os::from_paths(0, vec![vec![0, 2, 1]], 42);
+        let mut unknown = request(0);
+        unknown.target_node_id = 99;
+        let (events, _) = os.handle_epr_request_app(
+            0,
+            unknown,
+            &mut nodes,
+            &mut epr_register,
+            &mut physical_topology,
+            &SignallingParams::default(),
+        );
+        assert_eq!(1, events.len());
+    }
+}
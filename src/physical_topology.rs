@@ -1,12 +1,24 @@
 // SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
 // SPDX-License-Identifier: MIT
 
+use petgraph::algo::FloatMeasure;
+use petgraph::visit::EdgeRef;
+
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum NodeType {
     /// Satellite node.
     SAT,
     /// On ground station.
     OGS,
+    /// Ground repeater, connected to other ground nodes via fiber links.
+    GR,
+}
+
+impl NodeType {
+    /// Return true if the node is on the ground.
+    pub fn on_ground(&self) -> bool {
+        matches!(self, NodeType::OGS | NodeType::GR)
+    }
 }
 
 impl std::fmt::Display for NodeType {
@@ -17,6 +29,7 @@ impl std::fmt::Display for NodeType {
             match self {
                 NodeType::SAT => "SAT",
                 NodeType::OGS => "OGS",
+                NodeType::GR => "GR",
             }
         )
     }
@@ -28,8 +41,11 @@ pub struct NodeWeight {
     pub node_type: NodeType,
     /// Number of memory qubits.
     pub memory_qubits: u32,
-    /// Fidelity decay rate of a qubit in memory.
+    /// Fidelity decay rate of a qubit in memory due to depolarizing noise.
     pub decay_rate: f64,
+    /// Dephasing rate of a qubit in memory, zero by default.
+    #[serde(default)]
+    pub dephasing_rate: f64,
     /// Entanglement swapping success probability.
     pub swapping_success_prob: f64,
     /// Number of detectors.
@@ -59,6 +75,7 @@ impl NodeWeight {
             node_type: NodeType::SAT,
             memory_qubits: 1,
             decay_rate: 0.0,
+            dephasing_rate: 0.0,
             swapping_success_prob: 1.0,
             detectors: 1,
             transmitters: 1,
@@ -71,6 +88,20 @@ impl NodeWeight {
             node_type: NodeType::OGS,
             memory_qubits: 1,
             decay_rate: 0.0,
+            dephasing_rate: 0.0,
+            swapping_success_prob: 1.0,
+            detectors: 1,
+            transmitters: 0,
+            capacity: 0.0,
+        }
+    }
+
+    pub fn default_gr() -> Self {
+        Self {
+            node_type: NodeType::GR,
+            memory_qubits: 1,
+            decay_rate: 0.0,
+            dephasing_rate: 0.0,
             swapping_success_prob: 1.0,
             detectors: 1,
             transmitters: 0,
@@ -95,6 +126,9 @@ impl NodeWeight {
         if self.decay_rate < 0.0 {
             errors.push(format!("decay rate ({}) < 0", self.decay_rate))
         }
+        if self.dephasing_rate < 0.0 {
+            errors.push(format!("dephasing rate ({}) < 0", self.dephasing_rate))
+        }
         if self.swapping_success_prob < 0.0 || self.swapping_success_prob > 1.0 {
             errors.push(format!(
                 "invalid swapping success probability ({})",
@@ -119,38 +153,76 @@ impl NodeWeight {
 pub struct EdgeWeight {
     /// Distance between two nodes, in m.
     distance: f64,
+    /// Attenuation, in dB/km, if this is a fiber link, otherwise it is a
+    /// free-space optical link.
+    fiber_attenuation: Option<f64>,
 }
 
 impl std::fmt::Display for EdgeWeight {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.distance)
+        match self.fiber_attenuation {
+            Some(attenuation) => write!(f, "{} ({} dB/km)", self.distance, attenuation),
+            None => write!(f, "{}", self.distance),
+        }
     }
 }
 
-impl petgraph::algo::FloatMeasure for EdgeWeight {
-    fn zero() -> Self {
+impl EdgeWeight {
+    /// Free-space optical link with given length, in m.
+    fn free_space(distance: f64) -> Self {
         Self {
-            distance: f64::zero(),
+            distance,
+            fiber_attenuation: None,
         }
     }
 
-    fn infinite() -> Self {
+    /// Fiber link with given length, in m, and attenuation, in dB/km.
+    fn fiber(distance: f64, attenuation: f64) -> Self {
         Self {
-            distance: f64::infinite(),
+            distance,
+            fiber_attenuation: Some(attenuation),
         }
     }
 }
 
+impl petgraph::algo::FloatMeasure for EdgeWeight {
+    fn zero() -> Self {
+        Self::free_space(f64::zero())
+    }
+
+    fn infinite() -> Self {
+        Self::free_space(f64::infinite())
+    }
+}
+
 impl std::ops::Add for EdgeWeight {
     type Output = EdgeWeight;
 
     fn add(self, rhs: Self) -> Self::Output {
-        EdgeWeight {
-            distance: self.distance + rhs.distance,
-        }
+        EdgeWeight::free_space(self.distance + rhs.distance)
     }
 }
 
+/// Selector of the nodes whose characteristics are overridden.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum NodeSelector {
+    /// The node with the given index.
+    Index(u32),
+    /// All the satellites in the given orbit.
+    Orbit(u32),
+    /// Every k-th satellite, starting from the first one.
+    EveryKth(u32),
+    /// The node with the given name, e.g., an OGS.
+    Name(String),
+}
+
+/// Characteristics replacing those of the nodes selected.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct NodeWeightOverride {
+    pub selector: NodeSelector,
+    pub weight: NodeWeight,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct StaticFidelities {
     /// One hop, orbit-to-orbit.
@@ -224,7 +296,7 @@ impl Default for GridParams {
     }
 }
 
-fn err_if_not_empty(errors: &Vec<String>) -> anyhow::Result<()> {
+fn err_if_not_empty(errors: &[String]) -> anyhow::Result<()> {
     if !errors.is_empty() {
         anyhow::bail!(
             "invalid physical topology grid parameters: {}",
@@ -234,21 +306,34 @@ fn err_if_not_empty(errors: &Vec<String>) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Check the distances between satellites and from OGSs to satellites.
+fn valid_distances(
+    errors: &mut Vec<String>,
+    orbit_to_orbit_distance: f64,
+    ground_to_orbit_distance: f64,
+) {
+    if orbit_to_orbit_distance < 0.0 {
+        errors.push(format!(
+            "orbit-to-orbit distance ({}) < 0",
+            orbit_to_orbit_distance
+        ))
+    }
+    if ground_to_orbit_distance < 0.0 {
+        errors.push(format!(
+            "ground-to-orbit distance ({}) < 0",
+            ground_to_orbit_distance
+        ))
+    }
+}
+
 impl GridParams {
     fn valid(&self) -> anyhow::Result<()> {
         let mut errors = vec![];
-        if self.orbit_to_orbit_distance < 0.0 {
-            errors.push(format!(
-                "orbit-to-orbit distance ({}) < 0",
-                self.orbit_to_orbit_distance
-            ))
-        }
-        if self.ground_to_orbit_distance < 0.0 {
-            errors.push(format!(
-                "ground-to-orbit distance ({}) < 0",
-                self.ground_to_orbit_distance
-            ))
-        }
+        valid_distances(
+            &mut errors,
+            self.orbit_to_orbit_distance,
+            self.ground_to_orbit_distance,
+        );
         if self.num_orbits == 0 {
             errors.push(String::from("vanishing number of orbits"));
         }
@@ -259,6 +344,104 @@ impl GridParams {
     }
 }
 
+/// Placement of the OGSs in a grid topology.
+///
+/// The cells of the grid are identified by (row, column), with row in
+/// 0..=num_orbits and column in 0..orbit_length: the OGS in cell (i, j) is
+/// connected to satellites j-1 and j of the orbits i-1 (if any) and i (if any).
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum OgsPlacement {
+    /// One OGS in every cell.
+    #[default]
+    All,
+    /// One OGS in each of the given cells.
+    Cells(Vec<(u32, u32)>),
+    /// One OGS in each of a given number of cells drawn at random.
+    Random(u32),
+    /// One OGS in every cell, except those in the given rows.
+    SkipRows(Vec<u32>),
+}
+
+impl OgsPlacement {
+    /// Return the name of the placement.
+    pub fn name(&self) -> &'static str {
+        match self {
+            OgsPlacement::All => "All",
+            OgsPlacement::Cells(_) => "Cells",
+            OgsPlacement::Random(_) => "Random",
+            OgsPlacement::SkipRows(_) => "SkipRows",
+        }
+    }
+
+    /// Return the number of OGSs placed in the given grid.
+    pub fn num_ogs(&self, grid_params: &GridParams) -> u32 {
+        let num_rows = grid_params.num_orbits + 1;
+        match self {
+            OgsPlacement::All => grid_params.orbit_length * num_rows,
+            OgsPlacement::Cells(cells) => cells.len() as u32,
+            OgsPlacement::Random(count) => *count,
+            OgsPlacement::SkipRows(rows) => {
+                let skipped = (0..num_rows).filter(|i| rows.contains(i)).count() as u32;
+                grid_params.orbit_length * (num_rows - skipped)
+            }
+        }
+    }
+
+    /// Return the cells of the given grid where to place the OGSs, sorted
+    /// by row and column. The seed is only used with random placement.
+    fn cells(&self, grid_params: &GridParams, seed: u64) -> anyhow::Result<Vec<(u32, u32)>> {
+        let num_rows = grid_params.num_orbits + 1;
+        let all_cells = (0..num_rows)
+            .flat_map(|i| (0..grid_params.orbit_length).map(move |j| (i, j)))
+            .collect::<Vec<_>>();
+        let mut errors = vec![];
+        let cells = match self {
+            OgsPlacement::All => all_cells,
+            OgsPlacement::Cells(cells) => {
+                let mut unique = std::collections::BTreeSet::new();
+                for (i, j) in cells {
+                    if *i >= num_rows || *j >= grid_params.orbit_length {
+                        errors.push(format!("OGS cell ({},{}) outside the grid", i, j));
+                    } else if !unique.insert((*i, *j)) {
+                        errors.push(format!("duplicate OGS cell ({},{})", i, j));
+                    }
+                }
+                unique.into_iter().collect()
+            }
+            OgsPlacement::Random(count) => {
+                if *count as usize > all_cells.len() {
+                    errors.push(format!(
+                        "number of OGSs ({}) > number of cells ({})",
+                        count,
+                        all_cells.len()
+                    ));
+                    vec![]
+                } else {
+                    let mut rng = <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(seed);
+                    let mut selected =
+                        rand::seq::index::sample(&mut rng, all_cells.len(), *count as usize)
+                            .into_vec();
+                    selected.sort();
+                    selected.into_iter().map(|ndx| all_cells[ndx]).collect()
+                }
+            }
+            OgsPlacement::SkipRows(rows) => {
+                for i in rows {
+                    if *i >= num_rows {
+                        errors.push(format!("OGS row ({}) outside the grid", i));
+                    }
+                }
+                all_cells
+                    .into_iter()
+                    .filter(|(i, _j)| !rows.contains(i))
+                    .collect()
+            }
+        };
+        err_if_not_empty(&errors)?;
+        Ok(cells)
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ChainParams {
     /// Distance between two neighbor satellites, in m.
@@ -282,25 +465,268 @@ impl Default for ChainParams {
 impl ChainParams {
     fn valid(&self) -> anyhow::Result<()> {
         let mut errors = vec![];
-        if self.orbit_to_orbit_distance < 0.0 {
-            errors.push(format!(
-                "orbit-to-orbit distance ({}) < 0",
-                self.orbit_to_orbit_distance
-            ))
+        valid_distances(
+            &mut errors,
+            self.orbit_to_orbit_distance,
+            self.ground_to_orbit_distance,
+        );
+        if self.num_repeaters == 0 {
+            errors.push(String::from("vanishing number of repeaters"));
+        }
+        err_if_not_empty(&errors)
+    }
+}
+
+/// Check the parameters of the OGSs attached to `num_satellites` satellites.
+fn valid_ogs_attachment(errors: &mut Vec<String>, num_satellites: u32, num_ogs: u32, degree: u32) {
+    if num_ogs == 0 {
+        errors.push(String::from("vanishing number of OGSs"));
+    }
+    if degree == 0 {
+        errors.push(String::from("vanishing OGS degree"));
+    }
+    if degree > num_satellites {
+        errors.push(format!(
+            "OGS degree ({}) > number of satellites ({})",
+            degree, num_satellites
+        ));
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RingParams {
+    /// Distance between two neighbor satellites, in m.
+    pub orbit_to_orbit_distance: f64,
+    /// Distance between an OGS and a satellite, in m.
+    pub ground_to_orbit_distance: f64,
+    /// Number of satellites in the ring.
+    pub num_satellites: u32,
+    /// Number of OGSs, evenly spaced along the ring.
+    pub num_ogs: u32,
+    /// Number of consecutive satellites to which every OGS is attached.
+    pub ogs_degree: u32,
+}
+
+impl Default for RingParams {
+    fn default() -> Self {
+        Self {
+            orbit_to_orbit_distance: 3000.0,
+            ground_to_orbit_distance: 1000.0,
+            num_satellites: 8,
+            num_ogs: 4,
+            ogs_degree: 2,
         }
-        if self.ground_to_orbit_distance < 0.0 {
+    }
+}
+
+impl RingParams {
+    fn valid(&self) -> anyhow::Result<()> {
+        let mut errors = vec![];
+        valid_distances(
+            &mut errors,
+            self.orbit_to_orbit_distance,
+            self.ground_to_orbit_distance,
+        );
+        if self.num_satellites < 3 {
             errors.push(format!(
-                "ground-to-orbit distance ({}) < 0",
-                self.ground_to_orbit_distance
-            ))
+                "too few satellites in the ring ({})",
+                self.num_satellites
+            ));
         }
-        if self.num_repeaters == 0 {
-            errors.push(String::from("vanishing number of repeaters"));
+        valid_ogs_attachment(
+            &mut errors,
+            self.num_satellites,
+            self.num_ogs,
+            self.ogs_degree,
+        );
+        err_if_not_empty(&errors)
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct StarParams {
+    /// Distance between the hub and every other satellite, in m.
+    pub orbit_to_orbit_distance: f64,
+    /// Distance between an OGS and a satellite, in m.
+    pub ground_to_orbit_distance: f64,
+    /// Number of satellites around the hub.
+    pub num_leaves: u32,
+    /// Number of OGSs, evenly spaced among the leaves.
+    pub num_ogs: u32,
+    /// Number of consecutive leaves to which every OGS is attached.
+    pub ogs_degree: u32,
+}
+
+impl Default for StarParams {
+    fn default() -> Self {
+        Self {
+            orbit_to_orbit_distance: 3000.0,
+            ground_to_orbit_distance: 1000.0,
+            num_leaves: 6,
+            num_ogs: 6,
+            ogs_degree: 1,
+        }
+    }
+}
+
+impl StarParams {
+    fn valid(&self) -> anyhow::Result<()> {
+        let mut errors = vec![];
+        valid_distances(
+            &mut errors,
+            self.orbit_to_orbit_distance,
+            self.ground_to_orbit_distance,
+        );
+        if self.num_leaves == 0 {
+            errors.push(String::from("vanishing number of leaves"));
+        }
+        valid_ogs_attachment(&mut errors, self.num_leaves, self.num_ogs, self.ogs_degree);
+        err_if_not_empty(&errors)
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct RandomGeometricParams {
+    /// Side of the square area where satellites and OGSs are dropped, in m.
+    pub area_side: f64,
+    /// Maximum distance between two satellites to have a link, in m.
+    pub radius: f64,
+    /// Altitude of the satellites, in m.
+    pub altitude: f64,
+    /// Number of satellites.
+    pub num_satellites: u32,
+    /// Number of OGSs.
+    pub num_ogs: u32,
+    /// Number of nearest satellites to which every OGS is attached.
+    pub ogs_degree: u32,
+}
+
+impl Default for RandomGeometricParams {
+    fn default() -> Self {
+        Self {
+            area_side: 10000.0,
+            radius: 4000.0,
+            altitude: 1000.0,
+            num_satellites: 20,
+            num_ogs: 4,
+            ogs_degree: 2,
+        }
+    }
+}
+
+impl RandomGeometricParams {
+    fn valid(&self) -> anyhow::Result<()> {
+        let mut errors = vec![];
+        if self.area_side <= 0.0 {
+            errors.push(format!("area side ({}) <= 0", self.area_side));
+        }
+        if self.radius <= 0.0 {
+            errors.push(format!("radius ({}) <= 0", self.radius));
+        }
+        if self.altitude < 0.0 {
+            errors.push(format!("altitude ({}) < 0", self.altitude));
+        }
+        if self.num_satellites == 0 {
+            errors.push(String::from("vanishing number of satellites"));
+        }
+        valid_ogs_attachment(
+            &mut errors,
+            self.num_satellites,
+            self.num_ogs,
+            self.ogs_degree,
+        );
+        err_if_not_empty(&errors)
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WaxmanParams {
+    /// Side of the square area where satellites and OGSs are dropped, in m.
+    pub area_side: f64,
+    /// Ratio between the characteristic length of the links and the
+    /// diagonal of the area.
+    pub alpha: f64,
+    /// Probability of a link between two satellites at vanishing distance.
+    pub beta: f64,
+    /// Altitude of the satellites, in m.
+    pub altitude: f64,
+    /// Number of satellites.
+    pub num_satellites: u32,
+    /// Number of OGSs.
+    pub num_ogs: u32,
+    /// Number of nearest satellites to which every OGS is attached.
+    pub ogs_degree: u32,
+}
+
+impl Default for WaxmanParams {
+    fn default() -> Self {
+        Self {
+            area_side: 10000.0,
+            alpha: 0.4,
+            beta: 0.4,
+            altitude: 1000.0,
+            num_satellites: 20,
+            num_ogs: 4,
+            ogs_degree: 2,
+        }
+    }
+}
+
+impl WaxmanParams {
+    fn valid(&self) -> anyhow::Result<()> {
+        let mut errors = vec![];
+        if self.area_side <= 0.0 {
+            errors.push(format!("area side ({}) <= 0", self.area_side));
+        }
+        if self.alpha <= 0.0 {
+            errors.push(format!("alpha ({}) <= 0", self.alpha));
+        }
+        if self.beta <= 0.0 || self.beta > 1.0 {
+            errors.push(format!("invalid beta ({})", self.beta));
+        }
+        if self.altitude < 0.0 {
+            errors.push(format!("altitude ({}) < 0", self.altitude));
         }
+        if self.num_satellites == 0 {
+            errors.push(String::from("vanishing number of satellites"));
+        }
+        valid_ogs_attachment(
+            &mut errors,
+            self.num_satellites,
+            self.num_ogs,
+            self.ogs_degree,
+        );
         err_if_not_empty(&errors)
     }
 }
 
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MobilityParams {
+    /// Interval between consecutive updates of the positions of the nodes,
+    /// in s. Only used with constellations, 0 means never updated.
+    pub update_interval: f64,
+}
+
+impl Default for MobilityParams {
+    fn default() -> Self {
+        Self {
+            update_interval: 1.0,
+        }
+    }
+}
+
+impl MobilityParams {
+    pub fn valid(&self) -> anyhow::Result<()> {
+        if self.update_interval < 0.0 {
+            anyhow::bail!(
+                "invalid mobility parameters: update interval ({}) < 0",
+                self.update_interval
+            )
+        }
+        Ok(())
+    }
+}
+
 macro_rules! valid_node {
     ($node:expr, $graph:expr) => {
         anyhow::ensure!(
@@ -318,22 +744,99 @@ macro_rules! valid_node {
 
 type Graph = petgraph::Graph<NodeWeight, EdgeWeight, petgraph::Undirected, u32>;
 
+/// Return the shortest paths from a source node to all the others, with
+/// Dijkstra's algorithm since distances are non-negative.
+fn shortest_paths(
+    graph: &Graph,
+    source: petgraph::graph::NodeIndex,
+) -> petgraph::algo::bellman_ford::Paths<petgraph::graph::NodeIndex, EdgeWeight> {
+    let mut distances = vec![EdgeWeight::infinite(); graph.node_count()];
+    let mut predecessors = vec![None; graph.node_count()];
+    let mut visited = vec![false; graph.node_count()];
+    let mut heap = std::collections::BinaryHeap::new();
+    distances[source.index()] = EdgeWeight::zero();
+    // The bit representation of non-negative floats preserves their order.
+    heap.push(std::cmp::Reverse((0.0_f64.to_bits(), source.index())));
+    while let Some(std::cmp::Reverse((_, node))) = heap.pop() {
+        if visited[node] {
+            continue;
+        }
+        visited[node] = true;
+        for edge in graph.edges(petgraph::graph::NodeIndex::new(node)) {
+            let other = edge.target().index();
+            let distance = distances[node] + *edge.weight();
+            if distance < distances[other] {
+                distances[other] = distance;
+                predecessors[other] = Some(petgraph::graph::NodeIndex::new(node));
+                heap.push(std::cmp::Reverse((distance.distance.to_bits(), other)));
+            }
+        }
+    }
+    petgraph::algo::bellman_ford::Paths {
+        distances,
+        predecessors,
+    }
+}
+
+/// Add `num_ogs` OGSs evenly spaced along the given satellites, each
+/// attached to `degree` consecutive satellites at the given distance.
+fn attach_ogs_evenly(
+    graph: &mut Graph,
+    sats: &[u32],
+    ogs_weight: &NodeWeight,
+    num_ogs: u32,
+    degree: u32,
+    distance: f64,
+) {
+    for i in 0..num_ogs as usize {
+        let ogs = graph.add_node(ogs_weight.clone());
+        let first = i * sats.len() / num_ogs as usize;
+        for k in 0..degree as usize {
+            graph.add_edge(
+                ogs,
+                sats[(first + k) % sats.len()].into(),
+                EdgeWeight::free_space(distance),
+            );
+        }
+    }
+}
+
 /// Undirected graph representing the physical topology of the network.
 ///
 /// An edge is present if two nodes can establish a quantum/classical link
 /// with one another.
 ///
-/// A simple fidelity model for the EPR pairs generated is used, with fixed
-/// values depending only on whether the generation is one or two hops and
-/// if it is STA-STA or STA-OGS.
+/// The initial fidelity of the EPR pairs generated is given by a pluggable
+/// model, by default with fixed values depending only on whether the
+/// generation is one or two hops and if it is STA-STA or STA-OGS.
 #[derive(Debug, Default)]
 pub struct PhysicalTopology {
     graph: Graph,
-    fidelities: StaticFidelities,
+    fidelity_model: Box<dyn crate::fidelity_model::FidelityModel>,
     paths: std::collections::HashMap<
         u32,
         petgraph::algo::bellman_ford::Paths<petgraph::graph::NodeIndex, EdgeWeight>,
     >,
+    constellation: Option<crate::constellation::Constellation>,
+    link_budget: Option<crate::link_budget::LinkBudgetParams>,
+    /// Current time since the epoch of the constellation, in s.
+    time: f64,
+    /// Names of the nodes, if any, indexed by the node identifier.
+    names: Vec<Option<String>>,
+    /// Cartesian coordinates of the nodes, in m, if any.
+    coordinates: Vec<Option<[f64; 3]>>,
+    /// Satellites in every orbit, if the topology is organized in orbits.
+    orbits: Vec<Vec<u32>>,
+}
+
+/// Physical links that have appeared or disappeared after the topology
+/// has been updated.
+#[derive(Debug, Default, PartialEq)]
+pub struct LinkChanges {
+    /// New links, as (u, v) with u < v.
+    pub up: Vec<(u32, u32)>,
+    /// Links removed, as (u, v) with u < v.
+    pub down: Vec<(u32, u32)>,
 }
 
 impl PhysicalTopology {
@@ -348,15 +851,41 @@ impl PhysicalTopology {
     /// Exactly one station is assigned to each square of 4 satellites (if in
     /// the middle) or pair of satellites (if at the top/bottom).
     ///
-    /// All the satellite and ground nodes have the same given characteristics.
-    /// and static fidelities.
+    /// All the satellites have the characteristics in `sat_weight` and all
+    /// the OGSs those in `ogs_weight`, until replaced for selected nodes with
+    /// `override_node_weights`. The fidelities of the EPR pairs generated
+    /// are static.
     pub fn from_grid_static(
         grid_params: GridParams,
         sat_weight: NodeWeight,
         ogs_weight: NodeWeight,
         fidelities: StaticFidelities,
+    ) -> anyhow::Result<Self> {
+        Self::from_grid_placement_static(
+            grid_params,
+            OgsPlacement::All,
+            sat_weight,
+            ogs_weight,
+            fidelities,
+            0,
+        )
+    }
+
+    /// Build a physical topology consisting of a grid as in `from_grid_static`,
+    /// but with at most one station in the squares of satellites selected by
+    /// `ogs_placement`. The seed is used only with random placement.
+    ///
+    /// The OGSs follow the satellites in the order of their cells.
+    pub fn from_grid_placement_static(
+        grid_params: GridParams,
+        ogs_placement: OgsPlacement,
+        sat_weight: NodeWeight,
+        ogs_weight: NodeWeight,
+        fidelities: StaticFidelities,
+        seed: u64,
     ) -> anyhow::Result<Self> {
         grid_params.valid()?;
+        let cells = ogs_placement.cells(&grid_params, seed)?;
         sat_weight.valid()?;
         assert!(sat_weight.node_type == NodeType::SAT);
         ogs_weight.valid()?;
@@ -372,15 +901,13 @@ impl PhysicalTopology {
         }
 
         // Add OGS nodes.
-        let num_ogs = grid_params.orbit_length * (1 + grid_params.num_orbits);
+        let num_ogs = cells.len() as u32;
         for _ in 0..num_ogs {
             graph.add_node(ogs_weight.clone());
         }
 
         // Add orbit-to-orbit edges.
-        let orbit_weight = EdgeWeight {
-            distance: grid_params.orbit_to_orbit_distance,
-        };
+        let orbit_weight = EdgeWeight::free_space(grid_params.orbit_to_orbit_distance);
         for i in 0..grid_params.num_orbits {
             for j in 0..grid_params.orbit_length {
                 let ndx = j + i * grid_params.orbit_length;
@@ -411,51 +938,69 @@ impl PhysicalTopology {
         }
 
         // Add ground-to-orbit edges.
-        let ground_weight = EdgeWeight {
-            distance: grid_params.ground_to_orbit_distance,
-        };
-        for i in 0..=grid_params.num_orbits {
-            for j in 0..grid_params.orbit_length {
-                let ndx = num_sat + j + i * grid_params.orbit_length;
-                assert!(ndx < num_sat + num_ogs);
-                let mut sats = std::collections::HashSet::new();
-                // Up
-                if i != 0 {
-                    sats.insert((i - 1) * grid_params.orbit_length + j);
-                    sats.insert(
-                        (i - 1) * grid_params.orbit_length
-                            + (grid_params.orbit_length + j - 1) % grid_params.orbit_length,
-                    );
-                }
-                // Down
-                if i != grid_params.num_orbits {
-                    sats.insert(i * grid_params.orbit_length + j);
-                    sats.insert(
-                        i * grid_params.orbit_length
-                            + (grid_params.orbit_length + j - 1) % grid_params.orbit_length,
-                    );
-                }
-                for sat_ndx in sats {
-                    assert!(sat_ndx < num_sat);
-                    if !graph.contains_edge(sat_ndx.into(), ndx.into()) {
-                        graph.add_edge(ndx.into(), sat_ndx.into(), ground_weight);
-                    }
+        let ground_weight = EdgeWeight::free_space(grid_params.ground_to_orbit_distance);
+        for (ogs_ndx, (i, j)) in cells.iter().copied().enumerate() {
+            let ndx = num_sat + ogs_ndx as u32;
+            assert!(ndx < num_sat + num_ogs);
+            let mut sats = std::collections::HashSet::new();
+            // Up
+            if i != 0 {
+                sats.insert((i - 1) * grid_params.orbit_length + j);
+                sats.insert(
+                    (i - 1) * grid_params.orbit_length
+                        + (grid_params.orbit_length + j - 1) % grid_params.orbit_length,
+                );
+            }
+            // Down
+            if i != grid_params.num_orbits {
+                sats.insert(i * grid_params.orbit_length + j);
+                sats.insert(
+                    i * grid_params.orbit_length
+                        + (grid_params.orbit_length + j - 1) % grid_params.orbit_length,
+                );
+            }
+            for sat_ndx in sats {
+                assert!(sat_ndx < num_sat);
+                if !graph.contains_edge(sat_ndx.into(), ndx.into()) {
+                    graph.add_edge(ndx.into(), sat_ndx.into(), ground_weight);
                 }
             }
         }
 
+        // Name the nodes after their position in the grid.
+        let mut names = vec![];
+        for i in 0..grid_params.num_orbits {
+            for j in 0..grid_params.orbit_length {
+                names.push(Some(format!("sat-{}-{}", i, j)));
+            }
+        }
+        for (i, j) in cells {
+            names.push(Some(format!("ogs-{}-{}", i, j)));
+        }
+        let orbits = (0..grid_params.num_orbits)
+            .map(|i| (i * grid_params.orbit_length..(i + 1) * grid_params.orbit_length).collect())
+            .collect();
+
         Ok(Self {
             graph,
-            fidelities,
+            fidelity_model: Box::new(fidelities),
             paths: std::collections::HashMap::new(),
+            constellation: None,
+            link_budget: None,
+            time: 0.0,
+            names,
+            coordinates: vec![],
+            orbits,
         })
     }
 
     /// Build a physical topology consisting of an linear chain of repeaters,
     /// with one OGS at each end.
-
-    /// All the satellite and ground nodes have the same given characteristics.
-    /// and static fidelities.
+    ///
+    /// All the satellites have the characteristics in `sat_weight` and all
+    /// the OGSs those in `ogs_weight`, until replaced for selected nodes with
+    /// `override_node_weights`. The fidelities of the EPR pairs generated
+    /// are static.
     pub fn from_chain_static(
         chain_params: ChainParams,
         sat_weight: NodeWeight,
@@ -491,9 +1036,7 @@ impl PhysicalTopology {
                 graph.add_edge(
                     ndx.into(),
                     0.into(),
-                    EdgeWeight {
-                        distance: chain_params.ground_to_orbit_distance,
-                    },
+                    EdgeWeight::free_space(chain_params.ground_to_orbit_distance),
                 );
             }
 
@@ -503,38 +1046,690 @@ impl PhysicalTopology {
                 graph.add_edge(
                     ndx.into(),
                     1.into(),
-                    EdgeWeight {
-                        distance: chain_params.ground_to_orbit_distance,
-                    },
+                    EdgeWeight::free_space(chain_params.ground_to_orbit_distance),
                 );
             } else {
                 // Connect to right-hand satellite.
                 graph.add_edge(
                     ndx.into(),
                     (ndx + 1).into(),
-                    EdgeWeight {
-                        distance: chain_params.orbit_to_orbit_distance,
-                    },
+                    EdgeWeight::free_space(chain_params.orbit_to_orbit_distance),
                 );
             }
         }
 
+        // Name the nodes after their position in the chain, all the
+        // satellites belonging to the same orbit.
+        let names = [String::from("ogs-0"), String::from("ogs-1")]
+            .into_iter()
+            .chain((0..chain_params.num_repeaters).map(|i| format!("sat-{}", i)))
+            .map(Some)
+            .collect();
+
         Ok(Self {
             graph,
-            fidelities,
+            fidelity_model: Box::new(fidelities),
             paths: std::collections::HashMap::new(),
+            constellation: None,
+            link_budget: None,
+            time: 0.0,
+            names,
+            coordinates: vec![],
+            orbits: vec![(2..2 + chain_params.num_repeaters).collect()],
         })
     }
 
-    /// Return the indices of the in-orbit satelites.
-    pub fn sat_indices(&self) -> Vec<u32> {
-        self.node_indices(NodeType::SAT)
-    }
-
-    /// Return the indices of the on-ground stations.
-    pub fn ogs_indices(&self) -> Vec<u32> {
-        self.node_indices(NodeType::OGS)
-    }
+    /// Build a physical topology consisting of a ring of satellites.
+    ///
+    /// The OGSs are evenly spaced along the ring and each is attached to a
+    /// given number of consecutive satellites. The node identifiers are
+    /// those of the satellites followed by the OGSs.
+    ///
+    /// All the satellites have the characteristics in `sat_weight` and all
+    /// the OGSs those in `ogs_weight`. The fidelities of the EPR pairs
+    /// generated are static.
+    pub fn from_ring_static(
+        ring_params: RingParams,
+        sat_weight: NodeWeight,
+        ogs_weight: NodeWeight,
+        fidelities: StaticFidelities,
+    ) -> anyhow::Result<Self> {
+        ring_params.valid()?;
+        sat_weight.valid()?;
+        assert!(sat_weight.node_type == NodeType::SAT);
+        ogs_weight.valid()?;
+        assert!(ogs_weight.node_type == NodeType::OGS);
+        fidelities.valid()?;
+
+        let mut graph = petgraph::Graph::new_undirected();
+        let sats = (0..ring_params.num_satellites)
+            .map(|_| graph.add_node(sat_weight.clone()).index() as u32)
+            .collect::<Vec<_>>();
+        for (i, sat) in sats.iter().enumerate() {
+            graph.add_edge(
+                (*sat).into(),
+                sats[(i + 1) % sats.len()].into(),
+                EdgeWeight::free_space(ring_params.orbit_to_orbit_distance),
+            );
+        }
+        attach_ogs_evenly(
+            &mut graph,
+            &sats,
+            &ogs_weight,
+            ring_params.num_ogs,
+            ring_params.ogs_degree,
+            ring_params.ground_to_orbit_distance,
+        );
+
+        Ok(Self {
+            graph,
+            fidelity_model: Box::new(fidelities),
+            ..Default::default()
+        })
+    }
+
+    /// Build a physical topology consisting of a hub satellite connected to
+    /// a number of other satellites, i.e., the leaves.
+    ///
+    /// The OGSs are evenly spaced among the leaves and each is attached to
+    /// a given number of consecutive leaves. The node identifiers are the
+    /// hub, then the leaves, then the OGSs.
+    ///
+    /// All the satellites have the characteristics in `sat_weight` and all
+    /// the OGSs those in `ogs_weight`. The fidelities of the EPR pairs
+    /// generated are static.
+    pub fn from_star_static(
+        star_params: StarParams,
+        sat_weight: NodeWeight,
+        ogs_weight: NodeWeight,
+        fidelities: StaticFidelities,
+    ) -> anyhow::Result<Self> {
+        star_params.valid()?;
+        sat_weight.valid()?;
+        assert!(sat_weight.node_type == NodeType::SAT);
+        ogs_weight.valid()?;
+        assert!(ogs_weight.node_type == NodeType::OGS);
+        fidelities.valid()?;
+
+        let mut graph = petgraph::Graph::new_undirected();
+        let hub = graph.add_node(sat_weight.clone());
+        let leaves = (0..star_params.num_leaves)
+            .map(|_| graph.add_node(sat_weight.clone()).index() as u32)
+            .collect::<Vec<_>>();
+        for leaf in &leaves {
+            graph.add_edge(
+                hub,
+                (*leaf).into(),
+                EdgeWeight::free_space(star_params.orbit_to_orbit_distance),
+            );
+        }
+        attach_ogs_evenly(
+            &mut graph,
+            &leaves,
+            &ogs_weight,
+            star_params.num_ogs,
+            star_params.ogs_degree,
+            star_params.ground_to_orbit_distance,
+        );
+
+        Ok(Self {
+            graph,
+            fidelity_model: Box::new(fidelities),
+            ..Default::default()
+        })
+    }
+
+    /// Build a random geometric graph: the satellites are dropped uniformly
+    /// at random in a square area, at the same altitude, and two satellites
+    /// are connected if their distance is not greater than a radius.
+    ///
+    /// The OGSs are dropped uniformly at random in the same area, on the
+    /// ground, and each is attached to a given number of nearest satellites.
+    /// The node identifiers are those of the satellites followed by the OGSs.
+    /// The topology may be disconnected.
+    ///
+    /// All the satellites have the characteristics in `sat_weight` and all
+    /// the OGSs those in `ogs_weight`. The fidelities of the EPR pairs
+    /// generated are static.
+    pub fn from_random_geometric_static(
+        random_geometric_params: RandomGeometricParams,
+        sat_weight: NodeWeight,
+        ogs_weight: NodeWeight,
+        fidelities: StaticFidelities,
+        seed: u64,
+    ) -> anyhow::Result<Self> {
+        random_geometric_params.valid()?;
+        let params = random_geometric_params;
+        Self::from_random_positions(
+            params.area_side,
+            params.altitude,
+            params.num_satellites,
+            params.num_ogs,
+            params.ogs_degree,
+            sat_weight,
+            ogs_weight,
+            fidelities,
+            seed,
+            |distance, _rng| distance <= params.radius,
+        )
+    }
+
+    /// Build a Waxman graph: the satellites are dropped uniformly at random
+    /// in a square area, at the same altitude, and two satellites at
+    /// distance d are connected with probability beta * exp(-d / (alpha * L)),
+    /// where L is the diagonal of the area.
+    ///
+    /// The OGSs are dropped uniformly at random in the same area, on the
+    /// ground, and each is attached to a given number of nearest satellites.
+    /// The node identifiers are those of the satellites followed by the OGSs.
+    /// The topology may be disconnected.
+    ///
+    /// All the satellites have the characteristics in `sat_weight` and all
+    /// the OGSs those in `ogs_weight`. The fidelities of the EPR pairs
+    /// generated are static.
+    pub fn from_waxman_static(
+        waxman_params: WaxmanParams,
+        sat_weight: NodeWeight,
+        ogs_weight: NodeWeight,
+        fidelities: StaticFidelities,
+        seed: u64,
+    ) -> anyhow::Result<Self> {
+        waxman_params.valid()?;
+        let params = waxman_params;
+        let max_distance = params.area_side * std::f64::consts::SQRT_2;
+        Self::from_random_positions(
+            params.area_side,
+            params.altitude,
+            params.num_satellites,
+            params.num_ogs,
+            params.ogs_degree,
+            sat_weight,
+            ogs_weight,
+            fidelities,
+            seed,
+            |distance, rng| {
+                rand::Rng::gen::<f64>(rng)
+                    < params.beta * (-distance / (params.alpha * max_distance)).exp()
+            },
+        )
+    }
+
+    /// Drop satellites and OGSs at random positions and connect:
+    /// - every pair of satellites for which `connect` returns true, given
+    ///   their distance;
+    /// - every OGS to its nearest `ogs_degree` satellites.
+    #[allow(clippy::too_many_arguments)]
+    fn from_random_positions(
+        area_side: f64,
+        altitude: f64,
+        num_satellites: u32,
+        num_ogs: u32,
+        ogs_degree: u32,
+        sat_weight: NodeWeight,
+        ogs_weight: NodeWeight,
+        fidelities: StaticFidelities,
+        seed: u64,
+        mut connect: impl FnMut(f64, &mut rand::rngs::StdRng) -> bool,
+    ) -> anyhow::Result<Self> {
+        sat_weight.valid()?;
+        assert!(sat_weight.node_type == NodeType::SAT);
+        ogs_weight.valid()?;
+        assert!(ogs_weight.node_type == NodeType::OGS);
+        fidelities.valid()?;
+
+        let mut rng = <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(seed);
+        let mut position = |z: f64| {
+            [
+                rand::Rng::gen_range(&mut rng, 0.0..area_side),
+                rand::Rng::gen_range(&mut rng, 0.0..area_side),
+                z,
+            ]
+        };
+        let mut coordinates = vec![];
+        for _ in 0..num_satellites {
+            coordinates.push(position(altitude));
+        }
+        for _ in 0..num_ogs {
+            coordinates.push(position(0.0));
+        }
+        let distance = |u: usize, v: usize| -> f64 {
+            coordinates[u]
+                .iter()
+                .zip(coordinates[v].iter())
+                .map(|(a, b)| (a - b) * (a - b))
+                .sum::<f64>()
+                .sqrt()
+        };
+
+        let mut graph = petgraph::Graph::new_undirected();
+        for _ in 0..num_satellites {
+            graph.add_node(sat_weight.clone());
+        }
+        for _ in 0..num_ogs {
+            graph.add_node(ogs_weight.clone());
+        }
+
+        // Add orbit-to-orbit edges.
+        for u in 0..num_satellites as usize {
+            for v in (u + 1)..num_satellites as usize {
+                let distance = distance(u, v);
+                if connect(distance, &mut rng) {
+                    graph.add_edge(
+                        (u as u32).into(),
+                        (v as u32).into(),
+                        EdgeWeight::free_space(distance),
+                    );
+                }
+            }
+        }
+
+        // Add ground-to-orbit edges.
+        for ogs in num_satellites as usize..(num_satellites + num_ogs) as usize {
+            let mut sats = (0..num_satellites as usize)
+                .map(|sat| (distance(ogs, sat), sat))
+                .collect::<Vec<_>>();
+            sats.sort_by(|a, b| a.0.total_cmp(&b.0));
+            for (distance, sat) in sats.into_iter().take(ogs_degree as usize) {
+                graph.add_edge(
+                    (ogs as u32).into(),
+                    (sat as u32).into(),
+                    EdgeWeight::free_space(distance),
+                );
+            }
+        }
+
+        Ok(Self {
+            graph,
+            fidelity_model: Box::new(fidelities),
+            coordinates: coordinates.into_iter().map(Some).collect(),
+            ..Default::default()
+        })
+    }
+
+    /// Build a physical topology from a constellation, with the links at the
+    /// time specified in its parameters.
+    ///
+    /// The node identifiers are those of the satellites in the constellation,
+    /// followed by the OGSs. All the satellite and ground nodes have the same
+    /// given characteristics and static fidelities.
+    pub fn from_constellation(
+        constellation: crate::constellation::Constellation,
+        sat_weight: NodeWeight,
+        ogs_weight: NodeWeight,
+        fidelities: StaticFidelities,
+    ) -> anyhow::Result<Self> {
+        sat_weight.valid()?;
+        assert!(sat_weight.node_type == NodeType::SAT);
+        ogs_weight.valid()?;
+        assert!(ogs_weight.node_type == NodeType::OGS);
+        fidelities.valid()?;
+
+        let mut graph = petgraph::Graph::new_undirected();
+        for _ in 0..constellation.num_satellites() {
+            graph.add_node(sat_weight.clone());
+        }
+        for _ in 0..constellation.num_ground_stations() {
+            graph.add_node(ogs_weight.clone());
+        }
+
+        let time = constellation.time();
+        let mut topology = Self {
+            graph,
+            fidelity_model: Box::new(fidelities),
+            paths: std::collections::HashMap::new(),
+            constellation: Some(constellation),
+            link_budget: None,
+            time,
+            names: vec![],
+            coordinates: vec![],
+            orbits: vec![],
+        };
+        topology.set_time(time)?;
+        Ok(topology)
+    }
+
+    /// Build a physical topology from the nodes and edges read from a file.
+    ///
+    /// The node identifiers follow the order in the file. The length of the
+    /// edges without a distance is that between the coordinates of their
+    /// end-points, which must be both available. Edges with an attenuation
+    /// are fiber links, which can only connect ground nodes.
+    pub fn from_topology_file(
+        topology_file: crate::topology_file::TopologyFile,
+        fidelities: StaticFidelities,
+    ) -> anyhow::Result<Self> {
+        fidelities.valid()?;
+
+        let mut graph = petgraph::Graph::new_undirected();
+        let mut names = vec![];
+        let mut coordinates = vec![];
+        for node in topology_file.nodes {
+            node.weight.valid()?;
+            graph.add_node(node.weight);
+            names.push(node.name);
+            coordinates.push(node.coordinates);
+        }
+
+        for edge in topology_file.edges {
+            valid_node!(edge.u, graph);
+            valid_node!(edge.v, graph);
+            anyhow::ensure!(edge.u != edge.v, "self-loop on node {}", edge.u);
+            anyhow::ensure!(
+                graph.find_edge(edge.u.into(), edge.v.into()).is_none(),
+                "duplicate edge between nodes {} and {}",
+                edge.u,
+                edge.v
+            );
+            let distance = match (
+                edge.distance,
+                coordinates[edge.u as usize],
+                coordinates[edge.v as usize],
+            ) {
+                (Some(distance), _, _) => distance,
+                (None, Some(u), Some(v)) => u
+                    .iter()
+                    .zip(v.iter())
+                    .map(|(a, b)| (a - b) * (a - b))
+                    .sum::<f64>()
+                    .sqrt(),
+                _ => anyhow::bail!(
+                    "no distance nor coordinates for the edge between nodes {} and {}",
+                    edge.u,
+                    edge.v
+                ),
+            };
+            anyhow::ensure!(
+                distance >= 0.0,
+                "negative distance ({}) between nodes {} and {}",
+                distance,
+                edge.u,
+                edge.v
+            );
+            let weight = match edge.attenuation {
+                Some(attenuation) => {
+                    anyhow::ensure!(
+                        attenuation >= 0.0,
+                        "negative attenuation ({}) between nodes {} and {}",
+                        attenuation,
+                        edge.u,
+                        edge.v
+                    );
+                    anyhow::ensure!(
+                        graph[petgraph::graph::NodeIndex::from(edge.u)]
+                            .node_type
+                            .on_ground()
+                            && graph[petgraph::graph::NodeIndex::from(edge.v)]
+                                .node_type
+                                .on_ground(),
+                        "fiber link between nodes {} and {} not on the ground",
+                        edge.u,
+                        edge.v
+                    );
+                    EdgeWeight::fiber(distance, attenuation)
+                }
+                None => EdgeWeight::free_space(distance),
+            };
+            graph.add_edge(edge.u.into(), edge.v.into(), weight);
+        }
+
+        Ok(Self {
+            graph,
+            fidelity_model: Box::new(fidelities),
+            paths: std::collections::HashMap::new(),
+            constellation: None,
+            link_budget: None,
+            time: 0.0,
+            names,
+            coordinates,
+            orbits: vec![],
+        })
+    }
+
+    /// Replace the characteristics of the nodes selected by the overrides,
+    /// applied in order, so that later overrides take precedence.
+    ///
+    /// Return error if a selector does not match any node or if a node
+    /// would change its type or have invalid characteristics.
+    pub fn override_node_weights(
+        &mut self,
+        overrides: &[NodeWeightOverride],
+    ) -> anyhow::Result<()> {
+        for node_override in overrides {
+            let nodes: Vec<u32> = match &node_override.selector {
+                NodeSelector::Index(ndx) => vec![*ndx],
+                NodeSelector::Orbit(orbit) => match self.orbits.get(*orbit as usize) {
+                    Some(sats) => sats.clone(),
+                    None => anyhow::bail!("there's no orbit {} in the graph", orbit),
+                },
+                NodeSelector::EveryKth(k) => {
+                    anyhow::ensure!(*k > 0, "cannot select every 0-th satellite");
+                    self.sat_indices()
+                        .into_iter()
+                        .step_by(*k as usize)
+                        .collect()
+                }
+                NodeSelector::Name(name) => (0..self.names.len() as u32)
+                    .filter(|node| self.name(*node) == Some(name.as_str()))
+                    .collect(),
+            };
+            anyhow::ensure!(
+                !nodes.is_empty(),
+                "no nodes selected by {:?}",
+                node_override.selector
+            );
+            for node in nodes {
+                valid_node!(node, self.graph);
+                let weight = &mut self.graph[petgraph::graph::NodeIndex::from(node)];
+                anyhow::ensure!(
+                    weight.node_type == node_override.weight.node_type,
+                    "cannot override node {} of type {} with type {}",
+                    node,
+                    weight.node_type,
+                    node_override.weight.node_type
+                );
+                node_override
+                    .weight
+                    .valid()
+                    .map_err(|err| anyhow::anyhow!("node {}: {}", node, err))?;
+                *weight = node_override.weight.clone();
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the name of a node, if known.
+    pub fn name(&self, node: u32) -> Option<&str> {
+        self.names
+            .get(node as usize)
+            .and_then(|name| name.as_deref())
+    }
+
+    /// Return the Cartesian coordinates of a node, in m, if known.
+    pub fn coordinates(&self, node: u32) -> Option<[f64; 3]> {
+        self.coordinates.get(node as usize).copied().flatten()
+    }
+
+    /// Return the constellation, if the topology has been built from one.
+    pub fn constellation(&self) -> Option<&crate::constellation::Constellation> {
+        self.constellation.as_ref()
+    }
+
+    /// Move the satellites to their positions at the given time since the
+    /// epoch, in s: update the distances and add/remove the links that
+    /// have become available/unavailable.
+    ///
+    /// Return error if the topology has not been built from a constellation.
+    pub fn set_time(&mut self, time: f64) -> anyhow::Result<LinkChanges> {
+        let constellation = match &self.constellation {
+            Some(constellation) => constellation,
+            None => anyhow::bail!("the physical topology is static"),
+        };
+        let links = constellation.links(time);
+        let current = links
+            .iter()
+            .map(|(u, v, _)| (*u, *v))
+            .collect::<std::collections::HashSet<(u32, u32)>>();
+
+        let mut changes = LinkChanges::default();
+        for edge in self.graph.edge_indices().collect::<Vec<_>>() {
+            let (u, v) = self.graph.edge_endpoints(edge).unwrap();
+            let (u, v) = (
+                u.index().min(v.index()) as u32,
+                u.index().max(v.index()) as u32,
+            );
+            if !current.contains(&(u, v)) {
+                changes.down.push((u, v));
+            }
+        }
+        for (u, v) in &changes.down {
+            let edge = self.graph.find_edge((*u).into(), (*v).into()).unwrap();
+            self.graph.remove_edge(edge);
+        }
+        for (u, v, distance) in links {
+            match self.graph.find_edge(u.into(), v.into()) {
+                Some(edge) => self.graph[edge].distance = distance,
+                None => {
+                    self.graph
+                        .add_edge(u.into(), v.into(), EdgeWeight::free_space(distance));
+                    changes.up.push((u, v));
+                }
+            }
+        }
+        changes.down.sort();
+
+        self.paths.clear();
+        self.time = time;
+        Ok(changes)
+    }
+
+    /// Replace the model used to compute the initial fidelity of the EPR
+    /// pairs generated.
+    pub fn set_fidelity_model(
+        &mut self,
+        fidelity_model: Box<dyn crate::fidelity_model::FidelityModel>,
+    ) {
+        self.fidelity_model = fidelity_model;
+    }
+
+    /// Use a free-space optical link budget, instead of static values, to
+    /// derive the success probability and fidelity of the EPR pairs
+    /// generated.
+    pub fn set_link_budget(
+        &mut self,
+        link_budget: crate::link_budget::LinkBudgetParams,
+    ) -> anyhow::Result<()> {
+        link_budget.valid()?;
+        self.fidelity_model = Box::new(link_budget.clone());
+        self.link_budget = Some(link_budget);
+        Ok(())
+    }
+
+    /// Return the link budget parameters, if set.
+    pub fn link_budget(&self) -> Option<&crate::link_budget::LinkBudgetParams> {
+        self.link_budget.as_ref()
+    }
+
+    /// Return the elevation, in degrees, at which the satellite `sat` is
+    /// seen from the OGS `ogs`. Without a constellation the satellite is
+    /// assumed to be at zenith.
+    fn elevation(&self, ogs: u32, sat: u32) -> anyhow::Result<f64> {
+        match &self.constellation {
+            Some(constellation) => {
+                constellation.elevation(ogs - constellation.num_satellites(), sat, self.time)
+            }
+            None => Ok(90.0),
+        }
+    }
+
+    /// Return the physical link crossed by a photon from `tx` to `rx`, or
+    /// none if they are the same node.
+    fn hop(
+        &self,
+        tx: petgraph::graph::NodeIndex,
+        rx: petgraph::graph::NodeIndex,
+    ) -> anyhow::Result<Option<crate::fidelity_model::Hop>> {
+        if tx == rx {
+            return Ok(None);
+        }
+        let edge = match self.graph.find_edge(tx, rx) {
+            Some(edge) => edge,
+            None => anyhow::bail!(
+                "there is no edge between nodes {} and {}",
+                tx.index(),
+                rx.index()
+            ),
+        };
+        let weight = self.graph[edge];
+        let elevation = match (
+            weight.fiber_attenuation,
+            &self.graph[tx].node_type,
+            &self.graph[rx].node_type,
+        ) {
+            (None, NodeType::SAT, rx_type) if rx_type.on_ground() => {
+                Some(self.elevation(rx.index() as u32, tx.index() as u32)?)
+            }
+            (None, tx_type, NodeType::SAT) if tx_type.on_ground() => {
+                Some(self.elevation(tx.index() as u32, rx.index() as u32)?)
+            }
+            _ => None,
+        };
+        Ok(Some(crate::fidelity_model::Hop {
+            distance: weight.distance,
+            elevation,
+            fiber_attenuation: weight.fiber_attenuation,
+        }))
+    }
+
+    /// Return the physical path followed by the photons of the EPR pairs
+    /// generated by `tx` towards `u` and `v`.
+    fn physical_path(
+        &self,
+        tx: petgraph::graph::NodeIndex,
+        u: petgraph::graph::NodeIndex,
+        v: petgraph::graph::NodeIndex,
+    ) -> anyhow::Result<crate::fidelity_model::PhysicalPath> {
+        Ok(crate::fidelity_model::PhysicalPath {
+            to_u: self.hop(tx, u)?,
+            to_v: self.hop(tx, v)?,
+        })
+    }
+
+    /// Return the probability that an EPR pair generated by the given
+    /// transmitter towards the two nodes specified is successfully
+    /// established. Without a link budget, this only accounts for the
+    /// losses along fiber links.
+    /// Return error if there is no edge between `tx` and `u` or `v`.
+    pub fn success_probability(&self, tx: u32, u: u32, v: u32) -> anyhow::Result<f64> {
+        valid_node!(tx, self.graph);
+        valid_node!(u, self.graph);
+        valid_node!(v, self.graph);
+        let tx = petgraph::graph::NodeIndex::from(tx);
+        let u = petgraph::graph::NodeIndex::from(u);
+        let v = petgraph::graph::NodeIndex::from(v);
+        let path = self.physical_path(tx, u, v)?;
+        match &self.link_budget {
+            Some(link_budget) => Ok(link_budget.success_probability(
+                link_budget.hop_transmittance(&self.graph[tx], &self.graph[u], path.to_u.as_ref()),
+                link_budget.hop_transmittance(&self.graph[tx], &self.graph[v], path.to_v.as_ref()),
+            )),
+            None => Ok(path.fiber_transmittance()),
+        }
+    }
+
+    /// Return the indices of the in-orbit satelites.
+    pub fn sat_indices(&self) -> Vec<u32> {
+        self.node_indices(NodeType::SAT)
+    }
+
+    /// Return the indices of the on-ground stations.
+    pub fn ogs_indices(&self) -> Vec<u32> {
+        self.node_indices(NodeType::OGS)
+    }
+
+    /// Return the indices of the ground repeaters.
+    pub fn gr_indices(&self) -> Vec<u32> {
+        self.node_indices(NodeType::GR)
+    }
 
     fn node_indices(&self, node_type: NodeType) -> Vec<u32> {
         let mut ret = vec![];
@@ -552,24 +1747,53 @@ impl PhysicalTopology {
         valid_node!(u, self.graph);
         valid_node!(v, self.graph);
         if let Some(paths) = self.paths.get(&u) {
-            if let Some(_pred) = paths.predecessors[v as usize] {
+            if u == v || paths.predecessors[v as usize].is_some() {
                 Ok(paths.distances[v as usize].distance)
             } else {
                 anyhow::bail!("no connection between {:?} and {:?}", u, v);
             }
         } else {
-            match petgraph::algo::bellman_ford(&self.graph, u.into()) {
-                Ok(paths) => {
-                    self.paths.insert(u, paths);
-                    self.distance(u, v)
-                }
-                Err(_err) => anyhow::bail!(
-                    "cannot compute distance from {:?} to {:?}: negative cycle",
-                    u,
-                    v
-                ),
-            }
+            let paths = shortest_paths(&self.graph, u.into());
+            self.paths.insert(u, paths);
+            self.distance(u, v)
+        }
+    }
+
+    /// Return the number of hops along the shortest path from node u to
+    /// node v, i.e., the one used to compute their distance.
+    pub fn hops(&mut self, u: u32, v: u32) -> anyhow::Result<u32> {
+        self.distance(u, v)?;
+        let paths = self.paths.get(&u).expect("paths not computed");
+        let mut hops = 0;
+        let mut node = v as usize;
+        while let Some(pred) = paths.predecessors[node] {
+            hops += 1;
+            node = pred.index();
+        }
+        Ok(hops)
+    }
+
+    /// Return the nodes along the shortest path from node u to node v,
+    /// including both end-points, and its length, in m.
+    /// Unlike `distance`, the paths are not cached.
+    pub fn shortest_path(&self, u: u32, v: u32) -> anyhow::Result<(Vec<u32>, f64)> {
+        valid_node!(u, self.graph);
+        valid_node!(v, self.graph);
+        let paths = shortest_paths(&self.graph, u.into());
+        anyhow::ensure!(
+            u == v || paths.predecessors[v as usize].is_some(),
+            "no connection between {:?} and {:?}",
+            u,
+            v
+        );
+        let mut path = vec![v];
+        let mut node = v as usize;
+        while let Some(pred) = paths.predecessors[node] {
+            node = pred.index();
+            path.push(node as u32);
         }
+        path.reverse();
+        Ok((path, paths.distances[v as usize].distance))
     }
 
     /// Return the initial fidelity of the EPR pairs generated by the given
@@ -598,85 +1822,46 @@ impl PhysicalTopology {
             u.index(),
             v.index()
         );
-        anyhow::ensure!(
-            matches!(self.graph.node_weight(tx).unwrap().node_type, NodeType::SAT),
-            "node is an OGS and cannot be a transmitter: {}",
-            tx.index()
-        );
-
-        if tx == u {
-            anyhow::ensure!(
-                self.graph.find_edge(tx, v).is_some(),
-                "there is no edge between nodes {} and {}",
-                tx.index(),
-                v.index()
-            );
-            match self.graph.node_weight(v).unwrap().node_type {
-                NodeType::SAT => Ok(self.fidelities.f_o),
-                NodeType::OGS => Ok(self.fidelities.f_g),
-            }
-        } else if tx == v {
-            anyhow::ensure!(
-                self.graph.find_edge(tx, u).is_some(),
-                "there is no edge between nodes {} and {}",
-                tx.index(),
-                u.index()
-            );
-            match self.graph.node_weight(u).unwrap().node_type {
-                NodeType::SAT => Ok(self.fidelities.f_o),
-                NodeType::OGS => Ok(self.fidelities.f_g),
-            }
-        } else {
-            anyhow::ensure!(
-                self.graph.find_edge(tx, u).is_some(),
-                "there is no edge between nodes {} and {}",
-                tx.index(),
-                u.index()
-            );
-            anyhow::ensure!(
-                self.graph.find_edge(tx, v).is_some(),
-                "there is no edge between nodes {} and {}",
-                tx.index(),
-                v.index()
-            );
-            match self.graph.node_weight(u).unwrap().node_type {
-                NodeType::SAT => match self.graph.node_weight(v).unwrap().node_type {
-                    NodeType::SAT => Ok(self.fidelities.f_oo),
-                    NodeType::OGS => Ok(self.fidelities.f_og),
-                },
-                NodeType::OGS => match self.graph.node_weight(v).unwrap().node_type {
-                    NodeType::SAT => Ok(self.fidelities.f_og),
-                    NodeType::OGS => Ok(self.fidelities.f_gg),
-                },
-            }
-        }
+        let path = self.physical_path(tx, u, v)?;
+        Ok(self
+            .fidelity_model
+            .fidelity(&self.graph[tx], &self.graph[u], &self.graph[v], &path))
     }
 
     /// Create a topology of default nodes with given distances.
     #[cfg(test)]
-    fn from_distances(edges: Vec<(u32, u32, f64)>, fidelities: StaticFidelities) -> Self {
+    pub(crate) fn from_distances(
+        edges: Vec<(u32, u32, f64)>,
+        fidelities: StaticFidelities,
+    ) -> Self {
         let mut graph = petgraph::Graph::new_undirected();
 
-        graph.extend_with_edges(edges.iter().map(|(u, v, distance)| {
-            (
-                *u,
-                *v,
-                EdgeWeight {
-                    distance: *distance,
-                },
-            )
-        }));
+        graph.extend_with_edges(
+            edges
+                .iter()
+                .map(|(u, v, distance)| (*u, *v, EdgeWeight::free_space(*distance))),
+        );
         Self {
             graph,
-            fidelities,
+            fidelity_model: Box::new(fidelities),
             paths: std::collections::HashMap::new(),
+            constellation: None,
+            link_budget: None,
+            time: 0.0,
+            names: vec![],
+            coordinates: vec![],
+            orbits: vec![],
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::physical_topology::{ChainParams, GridParams, NodeWeight};
+    use crate::physical_topology::{
+        ChainParams, GridParams, NodeSelector, NodeWeight, NodeWeightOverride, OgsPlacement,
+        RandomGeometricParams, RingParams, StarParams, WaxmanParams,
+    };
+    use petgraph::visit::EdgeRef;
 
     use super::{NodeType, PhysicalTopology, StaticFidelities};
 
@@ -723,6 +1908,21 @@ mod tests {
         assert_float_eq::assert_f64_near!(graph.distance(0, 5).unwrap(), 300.0);
         assert_float_eq::assert_f64_near!(graph.distance(1, 3).unwrap(), 150.0);
         assert_float_eq::assert_f64_near!(graph.distance(3, 1).unwrap(), 150.0);
+        assert_float_eq::assert_f64_near!(graph.distance(3, 3).unwrap(), 0.0);
+
+        assert_eq!(1, graph.hops(0, 1).unwrap());
+        assert_eq!(2, graph.hops(0, 2).unwrap());
+        assert_eq!(3, graph.hops(0, 5).unwrap());
+        assert_eq!(1, graph.hops(1, 3).unwrap());
+        assert_eq!(0, graph.hops(3, 3).unwrap());
+        assert!(graph.hops(0, 99).is_err());
+
+        let (path, distance) = graph.shortest_path(0, 5)?;
+        assert_eq!(4, path.len());
+        assert_eq!((Some(&0), Some(&5)), (path.first(), path.last()));
+        assert_float_eq::assert_f64_near!(300.0, distance);
+        assert_eq!((vec![3], 0.0), graph.shortest_path(3, 3)?);
+        assert!(graph.shortest_path(0, 99).is_err());
 
         assert!(graph.distance(0, 99).is_err());
         assert!(graph.distance(99, 0).is_err());
@@ -879,6 +2079,87 @@ mod tests {
         assert_float_eq::assert_f64_near!(6000.0, graph.distance(12, 26).unwrap());
     }
 
+    #[test]
+    fn test_physical_topology_from_grid_placement() -> anyhow::Result<()> {
+        let grid_params = GridParams {
+            orbit_to_orbit_distance: 3000.0,
+            ground_to_orbit_distance: 1000.0,
+            num_orbits: 3,
+            orbit_length: 4,
+        };
+        let build = |ogs_placement: OgsPlacement, seed: u64| {
+            PhysicalTopology::from_grid_placement_static(
+                grid_params.clone(),
+                ogs_placement,
+                NodeWeight::default_sat(),
+                NodeWeight::default_ogs(),
+                StaticFidelities::default(),
+                seed,
+            )
+        };
+
+        // Invalid placements.
+        for ogs_placement in [
+            OgsPlacement::Cells(vec![(4, 0)]),
+            OgsPlacement::Cells(vec![(0, 4)]),
+            OgsPlacement::Cells(vec![(1, 1), (1, 1)]),
+            OgsPlacement::Random(17),
+            OgsPlacement::SkipRows(vec![4]),
+        ] {
+            assert!(build(ogs_placement, 0).is_err());
+        }
+
+        // All cells, same as the default grid.
+        let graph = build(OgsPlacement::All, 0)?;
+        assert_eq!(16, graph.ogs_indices().len());
+        assert_eq!(16, OgsPlacement::All.num_ogs(&grid_params));
+
+        // Explicit cells, sorted by row and column.
+        let ogs_placement = OgsPlacement::Cells(vec![(3, 2), (0, 0), (1, 1)]);
+        assert_eq!(3, ogs_placement.num_ogs(&grid_params));
+        let mut graph = build(ogs_placement, 0)?;
+        assert_eq!(vec![12, 13, 14], graph.ogs_indices());
+        assert_eq!(15, graph.graph().node_count());
+        assert_eq!(Some("ogs-0-0"), graph.name(12));
+        assert_eq!(Some("ogs-1-1"), graph.name(13));
+        assert_eq!(Some("ogs-3-2"), graph.name(14));
+        assert_eq!(2, graph.graph().neighbors(12.into()).count());
+        assert_eq!(4, graph.graph().neighbors(13.into()).count());
+        assert_eq!(2, graph.graph().neighbors(14.into()).count());
+        assert_float_eq::assert_f64_near!(1000.0, graph.distance(12, 0).unwrap());
+        assert_float_eq::assert_f64_near!(1000.0, graph.distance(12, 3).unwrap());
+        assert_float_eq::assert_f64_near!(1000.0, graph.distance(14, 9).unwrap());
+        assert_float_eq::assert_f64_near!(1000.0, graph.distance(14, 10).unwrap());
+        assert_float_eq::assert_f64_near!(2000.0, graph.distance(12, 13).unwrap());
+
+        // Skip the top and bottom rows.
+        let ogs_placement = OgsPlacement::SkipRows(vec![0, 3]);
+        assert_eq!(8, ogs_placement.num_ogs(&grid_params));
+        let graph = build(ogs_placement, 0)?;
+        assert_eq!(8, graph.ogs_indices().len());
+        for ogs in graph.ogs_indices() {
+            assert_eq!(4, graph.graph().neighbors(ogs.into()).count());
+        }
+
+        // Random cells, reproducible with the same seed.
+        let names = |graph: &PhysicalTopology| {
+            graph
+                .ogs_indices()
+                .iter()
+                .map(|ogs| graph.name(*ogs).unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        let graph = build(OgsPlacement::Random(5), 42)?;
+        assert_eq!(5, graph.ogs_indices().len());
+        assert_eq!(names(&graph), names(&build(OgsPlacement::Random(5), 42)?));
+        assert!((0..10).any(|seed| {
+            names(&build(OgsPlacement::Random(5), seed).unwrap()) != names(&graph)
+        }));
+        assert_eq!(0, build(OgsPlacement::Random(0), 0)?.ogs_indices().len());
+
+        Ok(())
+    }
+
     #[test]
     fn test_physical_topology_from_chain() {
         // Invalid params.
@@ -918,6 +2199,586 @@ mod tests {
         assert_float_eq::assert_f64_near!(10000.0, graph.distance(0, 5).unwrap());
     }
 
+    #[test]
+    fn test_physical_topology_override_node_weights() -> anyhow::Result<()> {
+        let hub = NodeWeight {
+            memory_qubits: 100,
+            detectors: 100,
+            ..NodeWeight::default_sat()
+        };
+        let station = NodeWeight {
+            memory_qubits: 1000,
+            detectors: 100,
+            ..NodeWeight::default_ogs()
+        };
+        let grid = || {
+            PhysicalTopology::from_grid_static(
+                GridParams::default(),
+                NodeWeight::default_sat(),
+                NodeWeight::default_ogs(),
+                StaticFidelities::default(),
+            )
+        };
+        let memory_qubits = |graph: &PhysicalTopology| {
+            graph
+                .graph()
+                .node_weights()
+                .map(|weight| weight.memory_qubits)
+                .collect::<Vec<_>>()
+        };
+
+        // Orbit 1 of a 3x4 grid, then every 5-th satellite, then one OGS
+        // by name and another one by index.
+        let mut graph = grid()?;
+        assert_eq!(Some("sat-1-2"), graph.name(6));
+        assert_eq!(Some("ogs-0-1"), graph.name(13));
+        graph.override_node_weights(&[
+            NodeWeightOverride {
+                selector: NodeSelector::Orbit(1),
+                weight: hub.clone(),
+            },
+            NodeWeightOverride {
+                selector: NodeSelector::EveryKth(5),
+                weight: NodeWeight {
+                    memory_qubits: 50,
+                    ..hub.clone()
+                },
+            },
+            NodeWeightOverride {
+                selector: NodeSelector::Name(String::from("ogs-0-1")),
+                weight: station.clone(),
+            },
+            NodeWeightOverride {
+                selector: NodeSelector::Index(27),
+                weight: station.clone(),
+            },
+        ])?;
+        let mut expected = vec![1; 28];
+        expected[4..8].fill(100);
+        for sat in [0, 5, 10] {
+            expected[sat] = 50;
+        }
+        expected[13] = 1000;
+        expected[27] = 1000;
+        assert_eq!(expected, memory_qubits(&graph));
+
+        // Invalid overrides leave the topology unchanged up to the error.
+        for (selector, weight) in [
+            (NodeSelector::Orbit(3), hub.clone()),
+            (NodeSelector::EveryKth(0), hub.clone()),
+            (NodeSelector::Name(String::from("ogs-9-9")), station.clone()),
+            (NodeSelector::Index(28), hub.clone()),
+            (NodeSelector::Index(12), hub.clone()),
+            (
+                NodeSelector::Index(0),
+                NodeWeight {
+                    capacity: -1.0,
+                    ..hub.clone()
+                },
+            ),
+        ] {
+            let mut graph = grid()?;
+            assert!(graph
+                .override_node_weights(&[NodeWeightOverride { selector, weight }])
+                .is_err());
+        }
+
+        // A chain has only one orbit and OGSs at both ends.
+        let mut graph = PhysicalTopology::from_chain_static(
+            ChainParams {
+                num_repeaters: 3,
+                ..Default::default()
+            },
+            NodeWeight::default_sat(),
+            NodeWeight::default_ogs(),
+            StaticFidelities::default(),
+        )?;
+        graph.override_node_weights(&[
+            NodeWeightOverride {
+                selector: NodeSelector::Orbit(0),
+                weight: hub.clone(),
+            },
+            NodeWeightOverride {
+                selector: NodeSelector::Name(String::from("ogs-1")),
+                weight: station.clone(),
+            },
+        ])?;
+        assert_eq!(vec![1, 1000, 100, 100, 100], memory_qubits(&graph));
+        assert!(graph
+            .override_node_weights(&[NodeWeightOverride {
+                selector: NodeSelector::Orbit(1),
+                weight: hub,
+            }])
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_physical_topology_from_ring_star() -> anyhow::Result<()> {
+        // Invalid params.
+        for ring_params in [
+            RingParams {
+                num_satellites: 2,
+                ..Default::default()
+            },
+            RingParams {
+                num_ogs: 0,
+                ..Default::default()
+            },
+            RingParams {
+                ogs_degree: 9,
+                ..Default::default()
+            },
+        ] {
+            assert!(PhysicalTopology::from_ring_static(
+                ring_params,
+                NodeWeight::default_sat(),
+                NodeWeight::default_ogs(),
+                StaticFidelities::default(),
+            )
+            .is_err());
+        }
+        assert!(PhysicalTopology::from_star_static(
+            StarParams {
+                num_leaves: 0,
+                ..Default::default()
+            },
+            NodeWeight::default_sat(),
+            NodeWeight::default_ogs(),
+            StaticFidelities::default(),
+        )
+        .is_err());
+
+        // Ring of 8 satellites with 4 OGSs, each attached to 2 satellites.
+        let mut graph = PhysicalTopology::from_ring_static(
+            RingParams::default(),
+            NodeWeight::default_sat(),
+            NodeWeight::default_ogs(),
+            StaticFidelities::default(),
+        )?;
+        assert_eq!((0..8).collect::<Vec<u32>>(), graph.sat_indices());
+        assert_eq!((8..12).collect::<Vec<u32>>(), graph.ogs_indices());
+        assert_eq!(8 + 4 * 2, graph.graph().edge_count());
+        assert_float_eq::assert_f64_near!(10000.0, graph.distance(0, 4)?);
+        assert_float_eq::assert_f64_near!(1000.0, graph.distance(8, 1)?);
+        assert_float_eq::assert_f64_near!(5000.0, graph.distance(8, 9)?);
+
+        // Star with 6 leaves, each with its own OGS.
+        let mut graph = PhysicalTopology::from_star_static(
+            StarParams::default(),
+            NodeWeight::default_sat(),
+            NodeWeight::default_ogs(),
+            StaticFidelities::default(),
+        )?;
+        assert_eq!((0..7).collect::<Vec<u32>>(), graph.sat_indices());
+        assert_eq!((7..13).collect::<Vec<u32>>(), graph.ogs_indices());
+        assert_eq!(6 + 6, graph.graph().edge_count());
+        assert_eq!(6, graph.graph().neighbors(0.into()).count());
+        assert_float_eq::assert_f64_near!(8000.0, graph.distance(7, 12)?);
+        assert_eq!(4, graph.hops(7, 12)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_physical_topology_random() -> anyhow::Result<()> {
+        let random_geometric = |params: RandomGeometricParams, seed| {
+            PhysicalTopology::from_random_geometric_static(
+                params,
+                NodeWeight::default_sat(),
+                NodeWeight::default_ogs(),
+                StaticFidelities::default(),
+                seed,
+            )
+        };
+        let waxman = |params: WaxmanParams, seed| {
+            PhysicalTopology::from_waxman_static(
+                params,
+                NodeWeight::default_sat(),
+                NodeWeight::default_ogs(),
+                StaticFidelities::default(),
+                seed,
+            )
+        };
+        let edges = |graph: &PhysicalTopology| {
+            graph
+                .graph()
+                .edge_references()
+                .map(|e| (e.source().index(), e.target().index(), e.weight().distance))
+                .collect::<Vec<_>>()
+        };
+
+        // Invalid params.
+        assert!(random_geometric(
+            RandomGeometricParams {
+                radius: 0.0,
+                ..Default::default()
+            },
+            42
+        )
+        .is_err());
+        assert!(waxman(
+            WaxmanParams {
+                beta: 1.5,
+                ..Default::default()
+            },
+            42
+        )
+        .is_err());
+
+        for (graph, other_seed, same_seed) in [
+            (
+                random_geometric(RandomGeometricParams::default(), 42)?,
+                random_geometric(RandomGeometricParams::default(), 43)?,
+                random_geometric(RandomGeometricParams::default(), 42)?,
+            ),
+            (
+                waxman(WaxmanParams::default(), 42)?,
+                waxman(WaxmanParams::default(), 43)?,
+                waxman(WaxmanParams::default(), 42)?,
+            ),
+        ] {
+            assert_eq!((0..20).collect::<Vec<u32>>(), graph.sat_indices());
+            assert_eq!((20..24).collect::<Vec<u32>>(), graph.ogs_indices());
+
+            // The OGSs are attached to their 2 nearest satellites.
+            for ogs in graph.ogs_indices() {
+                let neighbors = graph.graph().neighbors(ogs.into()).collect::<Vec<_>>();
+                assert_eq!(2, neighbors.len());
+                let max_distance = neighbors
+                    .iter()
+                    .map(|sat| {
+                        graph.graph()[graph.graph().find_edge(ogs.into(), *sat).unwrap()].distance
+                    })
+                    .fold(0.0, f64::max);
+                for sat in graph.sat_indices() {
+                    if !neighbors.contains(&sat.into()) {
+                        let [x, y, z] = graph.coordinates(sat).unwrap();
+                        let [a, b, c] = graph.coordinates(ogs).unwrap();
+                        let distance = ((x - a).powi(2) + (y - b).powi(2) + (z - c).powi(2)).sqrt();
+                        assert!(distance >= max_distance);
+                    }
+                }
+            }
+
+            // Satellites are above the ground and within the area.
+            for sat in graph.sat_indices() {
+                let [x, y, z] = graph.coordinates(sat).unwrap();
+                assert!((0.0..10000.0).contains(&x) && (0.0..10000.0).contains(&y));
+                assert_eq!(1000.0, z);
+            }
+
+            // The topology is reproducible with the same seed.
+            assert_eq!(edges(&graph), edges(&same_seed));
+            assert_ne!(edges(&graph), edges(&other_seed));
+        }
+
+        // Satellites are connected if within the radius.
+        let graph = random_geometric(RandomGeometricParams::default(), 42)?;
+        for edge in graph.graph().edge_references() {
+            if (edge.source().index() as u32) < 20 && (edge.target().index() as u32) < 20 {
+                assert!(edge.weight().distance <= 4000.0);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_physical_topology_from_constellation() -> anyhow::Result<()> {
+        let constellation = crate::constellation::Constellation::new(
+            crate::constellation::WalkerParams::default(),
+            vec![
+                crate::constellation::GroundStation {
+                    latitude: 43.72,
+                    longitude: 10.40,
+                },
+                crate::constellation::GroundStation {
+                    latitude: 41.90,
+                    longitude: 12.50,
+                },
+            ],
+        )?;
+        let mut graph = PhysicalTopology::from_constellation(
+            constellation,
+            NodeWeight::default_sat(),
+            NodeWeight::default_ogs(),
+            StaticFidelities::default(),
+        )?;
+        assert_eq!((0..240).collect::<Vec<u32>>(), graph.sat_indices());
+        assert_eq!((240..242).collect::<Vec<u32>>(), graph.ogs_indices());
+        assert!(graph.constellation().is_some());
+
+        // Static topologies cannot move.
+        assert!(
+            PhysicalTopology::from_distances(vec![(0, 1, 1.0)], StaticFidelities::default())
+                .set_time(1.0)
+                .is_err()
+        );
+
+        // Setting the same time again does not change anything.
+        let num_edges = graph.graph().edge_count();
+        assert_eq!(super::LinkChanges::default(), graph.set_time(0.0)?);
+        assert_eq!(num_edges, graph.graph().edge_count());
+
+        // Over one orbit the ground links change and the distances are
+        // updated, while the inter-satellite links remain.
+        let mut num_up = 0;
+        let mut num_down = 0;
+        let mut distances = vec![];
+        for step in 1..=60 {
+            let changes = graph.set_time(step as f64 * 100.0)?;
+            for (u, v) in changes.up.iter().chain(changes.down.iter()) {
+                assert!(u < v);
+                assert!(*v >= 240);
+            }
+            num_up += changes.up.len();
+            num_down += changes.down.len();
+            assert_eq!(
+                graph.graph().edge_count(),
+                graph
+                    .constellation()
+                    .unwrap()
+                    .links(step as f64 * 100.0)
+                    .len()
+            );
+            if graph.hops(240, 241).is_ok() {
+                distances.push(graph.distance(240, 241)?);
+            }
+        }
+        assert!(num_up > 0);
+        assert!(num_down > 0);
+        assert!(!distances.is_empty());
+        assert!(distances.windows(2).any(|w| w[0] != w[1]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_physical_topology_from_tle() -> anyhow::Result<()> {
+        let path = std::env::temp_dir().join("qnet_ll_sim_test_physical_topology_from_tle.txt");
+        std::fs::write(&path, crate::tests::tle_constellation(12, 20))?;
+        let tles = crate::tle::Tle::from_file(path.to_str().unwrap())?;
+        std::fs::remove_file(&path)?;
+
+        let constellation = crate::constellation::Constellation::from_tle(
+            crate::constellation::TleParams::default(),
+            &tles,
+            vec![
+                crate::constellation::GroundStation {
+                    latitude: 43.72,
+                    longitude: 10.40,
+                },
+                crate::constellation::GroundStation {
+                    latitude: 41.90,
+                    longitude: 12.50,
+                },
+            ],
+        )?;
+        let mut graph = PhysicalTopology::from_constellation(
+            constellation,
+            NodeWeight::default_sat(),
+            NodeWeight::default_ogs(),
+            StaticFidelities::default(),
+        )?;
+        assert_eq!((0..240).collect::<Vec<u32>>(), graph.sat_indices());
+        assert_eq!((240..242).collect::<Vec<u32>>(), graph.ogs_indices());
+
+        // Replay one hour: the OGSs are connected through the constellation
+        // at least some of the time.
+        let mut num_connected = 0;
+        for step in 0..60 {
+            graph.set_time(step as f64 * 60.0)?;
+            if let Ok(distance) = graph.distance(240, 241) {
+                assert!(distance > 2.0 * 550e3);
+                num_connected += 1;
+            }
+        }
+        assert!(num_connected > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_physical_topology_fidelity_model() -> anyhow::Result<()> {
+        let mut topo = PhysicalTopology::from_distances(
+            vec![(0, 1, 100.0), (0, 2, 300.0)],
+            StaticFidelities::default(),
+        );
+        assert_eq!(1.0, topo.fidelity(0, 1, 2)?);
+
+        topo.set_fidelity_model(Box::new(crate::fidelity_model::ExponentialFidelity {
+            f0: 0.9,
+            decay_length: 400.0,
+        }));
+        assert_float_eq::assert_f64_near!(0.25 + 0.65 * (-0.25_f64).exp(), topo.fidelity(0, 0, 1)?);
+        assert_float_eq::assert_f64_near!(0.25 + 0.65 * (-1.0_f64).exp(), topo.fidelity(0, 1, 2)?);
+        assert!(topo.fidelity(0, 1, 3).is_err());
+        assert!(topo.fidelity(1, 0, 2).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_physical_topology_link_budget() -> anyhow::Result<()> {
+        let mut topo = PhysicalTopology::from_distances(
+            vec![(0, 1, 1000e3), (0, 2, 500e3), (0, 3, 2000e3)],
+            StaticFidelities::default(),
+        );
+        topo.graph.node_weight_mut(1.into()).unwrap().node_type = NodeType::OGS;
+        topo.graph.node_weight_mut(2.into()).unwrap().node_type = NodeType::OGS;
+
+        // Static model.
+        assert_eq!(1.0, topo.success_probability(0, 1, 2)?);
+        assert_eq!(1.0, topo.fidelity(0, 1, 2)?);
+
+        // Link budget model.
+        assert!(topo
+            .set_link_budget(crate::link_budget::LinkBudgetParams {
+                wavelength: -1.0,
+                ..Default::default()
+            })
+            .is_err());
+        let params = crate::link_budget::LinkBudgetParams::default();
+        topo.set_link_budget(params.clone())?;
+        assert!(topo.link_budget().is_some());
+
+        let eta_1 =
+            params.transmittance(1000e3, params.sat_aperture, params.ogs_aperture, Some(90.0));
+        let eta_2 =
+            params.transmittance(500e3, params.sat_aperture, params.ogs_aperture, Some(90.0));
+        let eta_3 = params.transmittance(2000e3, params.sat_aperture, params.sat_aperture, None);
+        assert_float_eq::assert_f64_near!(
+            params.success_probability(eta_1, eta_2),
+            topo.success_probability(0, 1, 2)?
+        );
+        assert_float_eq::assert_f64_near!(
+            params.pair_fidelity(eta_1, eta_2),
+            topo.fidelity(0, 1, 2)?
+        );
+        assert_float_eq::assert_f64_near!(
+            params.success_probability(params.detector_efficiency, eta_3),
+            topo.success_probability(0, 0, 3)?
+        );
+
+        // Longer links lose more photons.
+        assert!(topo.success_probability(0, 0, 1)? < topo.success_probability(0, 0, 2)?);
+        assert!(topo.fidelity(0, 1, 3)? < topo.fidelity(0, 1, 2)?);
+
+        assert!(topo.success_probability(0, 1, 99).is_err());
+        assert!(topo.success_probability(1, 2, 3).is_err());
+        assert!(topo.fidelity(0, 1, 1).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_physical_topology_from_topology_file() -> anyhow::Result<()> {
+        for topology_file in [
+            crate::topology_file::TopologyFile::from_json(&crate::tests::topology_json())?,
+            crate::topology_file::TopologyFile::from_graphml(&crate::tests::topology_graphml())?,
+        ] {
+            let mut graph = PhysicalTopology::from_topology_file(
+                topology_file.clone(),
+                StaticFidelities::default(),
+            )?;
+            assert_eq!(vec![2], graph.sat_indices());
+            assert_eq!(vec![0, 1], graph.ogs_indices());
+            assert_eq!(Some("Pisa"), graph.name(0));
+            assert_eq!(Some("Rome"), graph.name(1));
+            assert_eq!(Some([1000.0, 0.0, 0.0]), graph.coordinates(1));
+            assert_eq!(None, graph.coordinates(2));
+            assert_eq!(None, graph.name(99));
+            assert_float_eq::assert_f64_near!(1000.0, graph.distance(0, 1)?);
+            assert_float_eq::assert_f64_near!(500e3, graph.distance(0, 2)?);
+            assert_eq!(1.0, graph.fidelity(2, 0, 1)?);
+
+            // Invalid edges.
+            for (u, v, distance, attenuation) in [
+                (0, 9, None, None),
+                (0, 0, None, None),
+                (0, 2, None, None),
+                (0, 1, Some(-1.0), None),
+                (0, 1, None, Some(-0.2)),
+                (0, 2, Some(1.0), Some(0.2)),
+            ] {
+                let mut invalid = topology_file.clone();
+                invalid.edges = vec![crate::topology_file::FileEdge {
+                    u,
+                    v,
+                    distance,
+                    attenuation,
+                }];
+                assert!(
+                    PhysicalTopology::from_topology_file(invalid, StaticFidelities::default())
+                        .is_err()
+                );
+            }
+            let mut invalid = topology_file.clone();
+            invalid.edges.push(crate::topology_file::FileEdge {
+                u: 1,
+                v: 0,
+                distance: None,
+                attenuation: None,
+            });
+            assert!(
+                PhysicalTopology::from_topology_file(invalid, StaticFidelities::default()).is_err()
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_physical_topology_fiber() -> anyhow::Result<()> {
+        // Two OGSs connected via a ground repeater with a source on board,
+        // with 10 dB loss on each fiber link, and via a satellite.
+        let topology_file =
+            crate::topology_file::TopologyFile::from_json(&crate::tests::topology_hybrid_json())?;
+        let fidelities = StaticFidelities {
+            f_o: 0.6,
+            f_g: 0.7,
+            f_oo: 0.8,
+            f_og: 0.9,
+            f_gg: 1.0,
+        };
+        let mut topo = PhysicalTopology::from_topology_file(topology_file, fidelities)?;
+        assert_eq!(vec![0, 2], topo.ogs_indices());
+        assert_eq!(vec![1], topo.gr_indices());
+        assert_eq!(vec![3], topo.sat_indices());
+        assert_float_eq::assert_f64_near!(100e3, topo.distance(0, 2)?);
+
+        // Without link budget only the fiber losses count.
+        assert_float_eq::assert_f64_near!(0.01, topo.success_probability(1, 0, 2)?, 8);
+        assert_float_eq::assert_f64_near!(0.1, topo.success_probability(1, 1, 0)?, 8);
+        assert_float_eq::assert_f64_near!(1.0, topo.success_probability(3, 0, 2)?);
+        assert_eq!(1.0, topo.fidelity(1, 0, 2)?);
+        assert_eq!(0.7, topo.fidelity(1, 1, 2)?);
+
+        // Sources on OGSs can reach satellites, too.
+        assert_eq!(0.9, topo.fidelity(0, 1, 3)?);
+        assert!(topo.fidelity(2, 1, 3).is_err());
+
+        // With the link budget the detector efficiency adds to fiber losses.
+        let params = crate::link_budget::LinkBudgetParams::default();
+        topo.set_link_budget(params.clone())?;
+        let eta = 0.1 * params.detector_efficiency;
+        assert_float_eq::assert_f64_near!(
+            params.success_probability(eta, eta),
+            topo.success_probability(1, 0, 2)?,
+            8
+        );
+        assert_float_eq::assert_f64_near!(
+            params.pair_fidelity(eta, eta),
+            topo.fidelity(1, 0, 2)?,
+            8
+        );
+        assert!(topo.success_probability(0, 1, 3)? < topo.success_probability(1, 0, 2)?);
+
+        Ok(())
+    }
+
     #[test]
     fn test_physical_topology_fidelities() {
         let fidelities = StaticFidelities {
@@ -962,4 +2823,23 @@ mod tests {
         assert!(topo.fidelity(0, 99, 1).is_err());
         assert!(topo.fidelity(99, 1, 2).is_err());
     }
+
+    #[test]
+    fn test_physical_topology_node_weight_without_dephasing() -> anyhow::Result<()> {
+        let weight: NodeWeight = serde_json::from_str(
+            r#"{
+                "node_type": "SAT",
+                "memory_qubits": 20,
+                "decay_rate": 1.0,
+                "swapping_success_prob": 0.95,
+                "detectors": 10,
+                "transmitters": 10,
+                "capacity": 1000.0
+            }"#,
+        )?;
+        assert_eq!(NodeType::SAT, weight.node_type);
+        assert_eq!(0.0, weight.dephasing_rate);
+        assert_eq!(1.0, weight.decay_rate);
+        Ok(())
+    }
 }
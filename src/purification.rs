@@ -0,0 +1,486 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+use rand::Rng;
+
+use crate::bell_diagonal::BellDiagonal;
+use crate::output::Sample;
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Protocol {
+    /// No purification.
+    Disabled,
+    /// Bennett-Brassard-Popescu-Schumacher-Smolin-Wootters.
+    Bbpssw,
+    /// Deutsch-Ekert-Jozsa-Macchiavello-Popescu-Sanpera.
+    Dejmps,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Policy {
+    /// Purify the most recent `num_pairs` EPR pairs as soon as available.
+    Greedy,
+    /// Symmetric nested purification, up to `max_rounds`.
+    Nested,
+    /// Entanglement pumping, up to `max_rounds`.
+    Pumping,
+    /// Purify until the fidelity reaches `threshold`.
+    Threshold,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PurificationParams {
+    /// Purification protocol.
+    pub protocol: Protocol,
+    /// Policy deciding when and which EPR pairs are purified.
+    pub policy: Policy,
+    /// Number of EPR pairs consumed to obtain one purified EPR pair,
+    /// only used with the greedy policy.
+    pub num_pairs: u32,
+    /// Maximum number of purification rounds, only used with the nested and
+    /// pumping policies.
+    pub max_rounds: u32,
+    /// Target fidelity, only used with the threshold policy.
+    pub threshold: f64,
+}
+
+impl Default for PurificationParams {
+    fn default() -> Self {
+        Self {
+            protocol: Protocol::Disabled,
+            policy: Policy::Greedy,
+            num_pairs: 2,
+            max_rounds: 2,
+            threshold: 0.9,
+        }
+    }
+}
+
+impl PurificationParams {
+    pub fn valid(&self) -> anyhow::Result<()> {
+        let mut errors = vec![];
+        if self.num_pairs < 2 {
+            errors.push(format!("number of EPR pairs ({}) < 2", self.num_pairs));
+        }
+        if self.max_rounds == 0 {
+            errors.push(String::from("vanishing maximum number of rounds"));
+        }
+        if self.threshold < 0.0 || self.threshold > 1.0 {
+            errors.push(format!("invalid threshold ({})", self.threshold));
+        }
+        if !errors.is_empty() {
+            anyhow::bail!("invalid purification parameters: {}", errors.join(","))
+        }
+        Ok(())
+    }
+
+    /// Return the purification policy.
+    pub fn policy(&self) -> Box<dyn crate::purification_policy::PurificationPolicy> {
+        match self.policy {
+            Policy::Greedy => Box::new(crate::purification_policy::Greedy {
+                num_pairs: self.num_pairs,
+            }),
+            Policy::Nested => Box::new(crate::purification_policy::Nested {
+                max_rounds: self.max_rounds,
+            }),
+            Policy::Pumping => Box::new(crate::purification_policy::Pumping {
+                max_rounds: self.max_rounds,
+            }),
+            Policy::Threshold => Box::new(crate::purification_policy::Threshold {
+                threshold: self.threshold,
+            }),
+        }
+    }
+}
+
+/// Return the success probability and output state of the purification
+/// of the given EPR pairs, done by purifying the first one with each of the
+/// others in sequence, which all must succeed.
+pub fn purify(protocol: Protocol, states: &[BellDiagonal]) -> (f64, BellDiagonal) {
+    assert!(!states.is_empty());
+    let mut success_prob = 1.0;
+    let mut state = states[0];
+    for other in states.iter().skip(1) {
+        let (p, s) = match protocol {
+            Protocol::Disabled => (1.0, state),
+            Protocol::Bbpssw => state.bbpssw(other),
+            Protocol::Dejmps => state.dejmps(other),
+        };
+        success_prob *= p;
+        state = s;
+    }
+    (success_prob, state)
+}
+
+/// Same as `purify()` but with the exact states of the EPR pairs.
+pub fn purify_exact(
+    protocol: Protocol,
+    states: &[crate::density_matrix::DensityMatrix],
+) -> (f64, crate::density_matrix::DensityMatrix) {
+    assert!(!states.is_empty());
+    let mut success_prob = 1.0;
+    let mut state = states[0].clone();
+    for other in states.iter().skip(1) {
+        let (p, s) = match protocol {
+            Protocol::Disabled => (1.0, state),
+            Protocol::Bbpssw => state.bbpssw(other),
+            Protocol::Dejmps => state.dejmps(other),
+        };
+        success_prob *= p;
+        state = s;
+    }
+    (success_prob, state)
+}
+
+/// Purify the EPR pairs on the logical link between a master and a slave
+/// selected by the policy among those available at both ends.
+///
+/// The first EPR pair selected is kept, while all the others are consumed.
+/// The kept EPR pair is reserved at both ends until they learn the outcome,
+/// which is returned, if any, together with the samples: then
+/// `complete_purification()` must be called.
+///
+/// The fidelity of the EPR pairs decays until `now` at both ends.
+#[allow(clippy::too_many_arguments)]
+pub fn purify_link(
+    now: u64,
+    master_node_id: u32,
+    slave_node_id: u32,
+    protocol: Protocol,
+    policy: &dyn crate::purification_policy::PurificationPolicy,
+    nodes: &mut [crate::node::Node],
+    epr_register: &mut crate::epr_register::EprRegister,
+    rng: &mut rand::rngs::StdRng,
+) -> (Option<crate::event::PurificationOutcomeData>, Vec<Sample>) {
+    if protocol == Protocol::Disabled {
+        return (None, vec![]);
+    }
+
+    // Find the EPR pairs that are valid at both ends.
+    let master_nic =
+        match nodes[master_node_id as usize].nic(slave_node_id, &crate::nic::Role::Master) {
+            Some(nic) => nic,
+            None => return (None, vec![]),
+        };
+    let slave_nic =
+        match nodes[slave_node_id as usize].nic(master_node_id, &crate::nic::Role::Slave) {
+            Some(nic) => nic,
+            None => return (None, vec![]),
+        };
+    let slave_valid_indices = slave_nic.valid_indices();
+    let mut candidates = vec![];
+    for master_index in master_nic.valid_indices() {
+        let (created, epr_pair_id) = master_nic.get(master_index).unwrap();
+        if let Some(slave_index) = slave_nic.find(epr_pair_id) {
+            if slave_valid_indices.contains(&slave_index) {
+                assert!(now >= created);
+                candidates.push(crate::purification_policy::Candidate {
+                    master_index,
+                    slave_index,
+                    epr_pair_id,
+                    age: crate::utils::to_seconds(now - created),
+                    fidelity: epr_register
+                        .fidelity(epr_pair_id, now)
+                        .unwrap_or_else(|| panic!("EPR pair not found {}", epr_pair_id)),
+                    rounds: epr_register
+                        .rounds(epr_pair_id)
+                        .unwrap_or_else(|| panic!("EPR pair not found {}", epr_pair_id)),
+                });
+            }
+        }
+    }
+
+    let selected = policy
+        .select(&candidates)
+        .into_iter()
+        .map(|ndx| candidates[ndx].clone())
+        .collect::<Vec<crate::purification_policy::Candidate>>();
+    if selected.len() < 2 {
+        return (None, vec![]);
+    }
+
+    let states = selected
+        .iter()
+        .map(|c| epr_register.state(c.epr_pair_id, now).unwrap())
+        .collect::<Vec<BellDiagonal>>();
+    let (success_prob, state) = purify(protocol, &states);
+    let success = rng.gen_bool(success_prob.clamp(0.0, 1.0));
+
+    // Update the EPR pair kept upon success.
+    if success {
+        let epr_pair_ids = selected.iter().map(|c| c.epr_pair_id).collect::<Vec<u64>>();
+        let rounds = selected.iter().map(|c| c.rounds).max().unwrap() + selected.len() as u32 - 1;
+        let res = epr_register.purified(&epr_pair_ids, now, protocol, state, rounds);
+        assert!(res, "EPR pairs not found {:?}", epr_pair_ids);
+    }
+
+    // Reserve the EPR pair kept until the outcome is known.
+    let kept = &selected[0];
+    let res = nodes[master_node_id as usize].reserve(
+        slave_node_id,
+        &crate::nic::Role::Master,
+        kept.master_index,
+    );
+    assert!(res.is_some());
+    let res = nodes[slave_node_id as usize].reserve(
+        master_node_id,
+        &crate::nic::Role::Slave,
+        kept.slave_index,
+    );
+    assert!(res.is_some());
+
+    // Consume the sacrificed EPR pairs.
+    for c in selected.iter().skip(1) {
+        nodes[master_node_id as usize].consume(
+            slave_node_id,
+            &crate::nic::Role::Master,
+            c.master_index,
+        );
+        nodes[slave_node_id as usize].consume(
+            master_node_id,
+            &crate::nic::Role::Slave,
+            c.slave_index,
+        );
+        epr_register.remove(c.epr_pair_id);
+    }
+
+    let label = format!("{}-{}", master_node_id, slave_node_id);
+    let mut samples = vec![Sample::Series(
+        "purification_success".to_string(),
+        label.clone(),
+        success as u32 as f64,
+    )];
+    if success {
+        samples.push(Sample::Series(
+            "purification_gain".to_string(),
+            label,
+            state.fidelity() - selected.iter().map(|c| c.fidelity).fold(0.0, f64::max),
+        ));
+    }
+    (
+        Some(crate::event::PurificationOutcomeData {
+            master_node_id,
+            slave_node_id,
+            epr_pair_id: kept.epr_pair_id,
+            success,
+        }),
+        samples,
+    )
+}
+
+/// Complete the purification on a logical link when the outcome is known at
+/// both ends: the EPR pair kept is released upon success, otherwise it is
+/// consumed. Return true if the EPR pair has been released, false also if the
+/// EPR pair is not stored anymore, e.g., because the logical link went down.
+pub fn complete_purification(
+    data: &crate::event::PurificationOutcomeData,
+    nodes: &mut [crate::node::Node],
+    epr_register: &mut crate::epr_register::EprRegister,
+) -> bool {
+    let find = |nodes: &[crate::node::Node], node_id: u32, peer_node_id, role| {
+        nodes[node_id as usize]
+            .nic(peer_node_id, role)
+            .and_then(|nic| nic.find(data.epr_pair_id))
+    };
+    let (master_index, slave_index) = match (
+        find(
+            nodes,
+            data.master_node_id,
+            data.slave_node_id,
+            &crate::nic::Role::Master,
+        ),
+        find(
+            nodes,
+            data.slave_node_id,
+            data.master_node_id,
+            &crate::nic::Role::Slave,
+        ),
+    ) {
+        (Some(master_index), Some(slave_index)) => (master_index, slave_index),
+        _ => return false,
+    };
+
+    if data.success {
+        let res = nodes[data.master_node_id as usize].release(
+            data.slave_node_id,
+            &crate::nic::Role::Master,
+            master_index,
+        ) && nodes[data.slave_node_id as usize].release(
+            data.master_node_id,
+            &crate::nic::Role::Slave,
+            slave_index,
+        );
+        assert!(res, "EPR pair not reserved {}", data.epr_pair_id);
+    } else {
+        nodes[data.master_node_id as usize].consume(
+            data.slave_node_id,
+            &crate::nic::Role::Master,
+            master_index,
+        );
+        nodes[data.slave_node_id as usize].consume(
+            data.master_node_id,
+            &crate::nic::Role::Slave,
+            slave_index,
+        );
+        epr_register.remove(data.epr_pair_id);
+    }
+    data.success
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::epr_register::EprRegister;
+    use crate::nic::{ReplacementPolicy, Role};
+    use crate::node::Node;
+
+    #[test]
+    fn test_purification_bbpssw_dejmps() {
+        // Purify Werner states with given fidelities.
+        let purify = |protocol, fidelities: &[f64]| {
+            let states = fidelities
+                .iter()
+                .map(|f| BellDiagonal::werner(*f))
+                .collect::<Vec<BellDiagonal>>();
+            let (p, state) = super::purify(protocol, &states);
+            (p, state.fidelity())
+        };
+
+        for protocol in [Protocol::Bbpssw, Protocol::Dejmps] {
+            // Perfect EPR pairs remain such.
+            let (p, f) = purify(protocol, &[1.0, 1.0]);
+            assert_float_eq::assert_f64_near!(1.0, p);
+            assert_float_eq::assert_f64_near!(1.0, f);
+
+            // Maximally mixed states cannot be purified.
+            let (p, f) = purify(protocol, &[0.25, 0.25]);
+            assert_float_eq::assert_f64_near!(0.5, p);
+            assert_float_eq::assert_f64_near!(0.25, f);
+
+            // Fidelity increases above 0.5.
+            let (p, f) = purify(protocol, &[0.8, 0.8]);
+            assert!(p > 0.0 && p < 1.0);
+            assert!(f > 0.8);
+            assert_float_eq::assert_f64_near!(0.8 * 0.8 + 0.2 * 0.2 / 9.0, f * p);
+
+            // And decreases below 0.5.
+            let (_, f) = purify(protocol, &[0.4, 0.4]);
+            assert!(f < 0.4);
+
+            // Purifying three EPR pairs is the same as two rounds.
+            let werner = BellDiagonal::werner(0.8);
+            let (p2, s2) = super::purify(protocol, &[werner, werner]);
+            let (p3, s3) = super::purify(protocol, &[werner, werner, werner]);
+            let (p_round, s_round) = super::purify(protocol, &[s2, werner]);
+            assert_float_eq::assert_f64_near!(p2 * p_round, p3);
+            assert_float_eq::assert_f64_near!(s_round.fidelity(), s3.fidelity());
+        }
+
+        let (p, f) = purify(Protocol::Disabled, &[0.7, 0.9]);
+        assert_float_eq::assert_f64_near!(1.0, p);
+        assert_float_eq::assert_f64_near!(0.7, f);
+    }
+
+    #[test]
+    fn test_purification_purify_link() {
+        let mut nodes = vec![Node::new(0), Node::new(1), Node::new(2)];
+        nodes[2].add_nic(0, Role::Master, 3, ReplacementPolicy::OverwriteOldest);
+        nodes[0].add_nic(2, Role::Slave, 3, ReplacementPolicy::OverwriteOldest);
+        let mut epr_register = EprRegister::default();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let policy = crate::purification_policy::Greedy { num_pairs: 2 };
+
+        let add = |nodes: &mut [Node], epr_register: &mut EprRegister, now, fidelity| {
+            let epr_pair_id = epr_register.new_epr_pair(2, 0, now, fidelity);
+            nodes[2].epr_established(now, 0, Role::Master, epr_pair_id, &|_| 1.0);
+            nodes[0].epr_established(now, 2, Role::Slave, epr_pair_id, &|_| 1.0);
+            epr_pair_id
+        };
+
+        // Not enough EPR pairs.
+        add(&mut nodes, &mut epr_register, 10, 0.8);
+        let (outcome, samples) = purify_link(
+            100,
+            2,
+            0,
+            Protocol::Bbpssw,
+            &policy,
+            &mut nodes,
+            &mut epr_register,
+            &mut rng,
+        );
+        assert!(outcome.is_none());
+        assert!(samples.is_empty());
+
+        // Purification disabled.
+        let mut id = add(&mut nodes, &mut epr_register, 20, 0.8);
+        let (outcome, samples) = purify_link(
+            100,
+            2,
+            0,
+            Protocol::Disabled,
+            &policy,
+            &mut nodes,
+            &mut epr_register,
+            &mut rng,
+        );
+        assert!(outcome.is_none());
+        assert!(samples.is_empty());
+
+        // Purify until a success, the most recent EPR pair is kept.
+        loop {
+            let (outcome, samples) = purify_link(
+                100,
+                2,
+                0,
+                Protocol::Bbpssw,
+                &policy,
+                &mut nodes,
+                &mut epr_register,
+                &mut rng,
+            );
+            assert!(!samples.is_empty());
+
+            // The EPR pair kept is reserved until the outcome is known.
+            let outcome = outcome.unwrap();
+            assert_eq!(id, outcome.epr_pair_id);
+            assert_eq!(samples.len() == 2, outcome.success);
+            assert!(nodes[2]
+                .nic(0, &Role::Master)
+                .unwrap()
+                .valid_indices()
+                .is_empty());
+            assert!(nodes[0]
+                .nic(2, &Role::Slave)
+                .unwrap()
+                .valid_indices()
+                .is_empty());
+            assert!(nodes[2].nic(0, &Role::Master).unwrap().find(id).is_some());
+            assert_eq!(
+                outcome.success,
+                complete_purification(&outcome, &mut nodes, &mut epr_register)
+            );
+
+            let master_nic = nodes[2].nic(0, &Role::Master).unwrap();
+            let slave_nic = nodes[0].nic(2, &Role::Slave).unwrap();
+            if samples.len() == 2 {
+                assert_eq!(1, master_nic.valid_indices().len());
+                assert_eq!(1, slave_nic.valid_indices().len());
+                assert!(master_nic.find(id).is_some());
+                assert!(slave_nic.find(id).is_some());
+                let fidelity = epr_register.fidelity(id, 100).unwrap();
+                let (_, expected) = BellDiagonal::werner(0.8).bbpssw(&BellDiagonal::werner(0.8));
+                assert_float_eq::assert_f64_near!(expected.fidelity(), fidelity);
+                assert_eq!(Some(1), epr_register.rounds(id));
+                break;
+            }
+            assert!(master_nic.valid_indices().is_empty());
+            assert!(slave_nic.valid_indices().is_empty());
+            assert!(epr_register.fidelity(id, 100).is_none());
+            add(&mut nodes, &mut epr_register, 30, 0.8);
+            id = add(&mut nodes, &mut epr_register, 40, 0.8);
+        }
+    }
+}
@@ -0,0 +1,237 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+/// EPR pair stored at both ends of a logical link that can be purified.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    /// Index of the memory cell in the master NIC.
+    pub master_index: usize,
+    /// Index of the memory cell in the slave NIC.
+    pub slave_index: usize,
+    /// Identifier of the EPR pair.
+    pub epr_pair_id: u64,
+    /// Time since the EPR pair was stored in the memory of the master, in s.
+    pub age: f64,
+    /// Estimated fidelity of the EPR pair.
+    pub fidelity: f64,
+    /// Number of purification rounds that produced the EPR pair.
+    pub rounds: u32,
+}
+
+/// Policy that decides which EPR pairs on a logical link are purified.
+pub trait PurificationPolicy: std::fmt::Debug + Send {
+    /// Select the EPR pairs to be purified among the candidates, in any
+    /// order: the policies compare them by age.
+    ///
+    /// Return the indices of the selected candidates: the first one is kept,
+    /// the others are consumed. Return an empty vector if no purification
+    /// should happen.
+    fn select(&self, candidates: &[Candidate]) -> Vec<usize>;
+}
+
+/// Return the indices of the candidates that satisfy a predicate, from the
+/// most recent to the oldest one.
+fn youngest_first(candidates: &[Candidate], predicate: impl Fn(&Candidate) -> bool) -> Vec<usize> {
+    let mut indices = (0..candidates.len())
+        .filter(|ndx| predicate(&candidates[*ndx]))
+        .collect::<Vec<usize>>();
+    indices.sort_by(|a, b| candidates[*a].age.total_cmp(&candidates[*b].age));
+    indices
+}
+
+/// Purify the `num_pairs` most recent EPR pairs as soon as they are available.
+#[derive(Debug)]
+pub struct Greedy {
+    pub num_pairs: u32,
+}
+
+impl PurificationPolicy for Greedy {
+    fn select(&self, candidates: &[Candidate]) -> Vec<usize> {
+        if candidates.len() < self.num_pairs as usize {
+            vec![]
+        } else {
+            youngest_first(candidates, |_| true)
+                .into_iter()
+                .take(self.num_pairs as usize)
+                .collect()
+        }
+    }
+}
+
+/// Symmetric nested purification: two EPR pairs are purified only if they
+/// were produced by the same number of rounds, up to a maximum.
+/// Pairs with more rounds are served first, then the most recent ones.
+#[derive(Debug)]
+pub struct Nested {
+    pub max_rounds: u32,
+}
+
+impl PurificationPolicy for Nested {
+    fn select(&self, candidates: &[Candidate]) -> Vec<usize> {
+        for rounds in (0..self.max_rounds).rev() {
+            let same_level = youngest_first(candidates, |c| c.rounds == rounds)
+                .into_iter()
+                .take(2)
+                .collect::<Vec<usize>>();
+            if same_level.len() == 2 {
+                return same_level;
+            }
+        }
+        vec![]
+    }
+}
+
+/// Entanglement pumping: the EPR pair with most rounds, up to a maximum, is
+/// purified again with the most recent EPR pair that has never been purified.
+#[derive(Debug)]
+pub struct Pumping {
+    pub max_rounds: u32,
+}
+
+impl PurificationPolicy for Pumping {
+    fn select(&self, candidates: &[Candidate]) -> Vec<usize> {
+        // The most recent among those with most rounds.
+        let eligible = youngest_first(candidates, |c| c.rounds < self.max_rounds);
+        let base = match eligible
+            .iter()
+            .min_by_key(|ndx| std::cmp::Reverse(candidates[**ndx].rounds))
+        {
+            Some(base) => *base,
+            None => return vec![],
+        };
+        match eligible
+            .into_iter()
+            .find(|ndx| *ndx != base && candidates[*ndx].rounds == 0)
+        {
+            Some(fresh) => vec![base, fresh],
+            None => vec![],
+        }
+    }
+}
+
+/// Purify until a fidelity threshold is reached: the two EPR pairs with
+/// highest fidelity below the threshold are purified together, while those
+/// already above the threshold are left untouched.
+/// With the same fidelity, the most recent EPR pairs are preferred.
+#[derive(Debug)]
+pub struct Threshold {
+    pub threshold: f64,
+}
+
+impl PurificationPolicy for Threshold {
+    fn select(&self, candidates: &[Candidate]) -> Vec<usize> {
+        let mut below = youngest_first(candidates, |c| c.fidelity < self.threshold);
+        // Stable sort, hence the youngest first with the same fidelity.
+        below.sort_by(|a, b| candidates[*b].fidelity.total_cmp(&candidates[*a].fidelity));
+        if below.len() < 2 {
+            vec![]
+        } else {
+            below.into_iter().take(2).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(values: &[(f64, u32)]) -> Vec<Candidate> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(ndx, (fidelity, rounds))| Candidate {
+                master_index: ndx,
+                slave_index: ndx,
+                epr_pair_id: ndx as u64,
+                age: ndx as f64,
+                fidelity: *fidelity,
+                rounds: *rounds,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_purification_policy_greedy() {
+        let policy = Greedy { num_pairs: 3 };
+        assert!(policy.select(&candidates(&[])).is_empty());
+        assert!(policy.select(&candidates(&[(0.8, 0), (0.8, 0)])).is_empty());
+        assert_eq!(
+            vec![0, 1, 2],
+            policy.select(&candidates(&[(0.8, 0), (0.8, 1), (0.8, 2), (0.8, 0)]))
+        );
+    }
+
+    #[test]
+    fn test_purification_policy_nested() {
+        let policy = Nested { max_rounds: 2 };
+        assert!(policy.select(&candidates(&[(0.8, 0)])).is_empty());
+        assert!(policy.select(&candidates(&[(0.8, 0), (0.9, 1)])).is_empty());
+        assert_eq!(
+            vec![0, 2],
+            policy.select(&candidates(&[(0.8, 0), (0.9, 1), (0.8, 0)]))
+        );
+        assert_eq!(
+            vec![1, 3],
+            policy.select(&candidates(&[(0.8, 0), (0.9, 1), (0.8, 0), (0.9, 1)]))
+        );
+        assert!(policy
+            .select(&candidates(&[(0.8, 2), (0.9, 1), (0.8, 2)]))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_purification_policy_pumping() {
+        let policy = Pumping { max_rounds: 2 };
+        assert!(policy.select(&candidates(&[(0.8, 0)])).is_empty());
+        assert_eq!(
+            vec![0, 1],
+            policy.select(&candidates(&[(0.8, 0), (0.8, 0)]))
+        );
+        assert_eq!(
+            vec![2, 0],
+            policy.select(&candidates(&[(0.8, 0), (0.8, 0), (0.9, 1)]))
+        );
+        assert!(policy.select(&candidates(&[(0.9, 1), (0.9, 1)])).is_empty());
+        assert_eq!(
+            vec![0, 2],
+            policy.select(&candidates(&[(0.9, 1), (0.95, 2), (0.8, 0)]))
+        );
+        assert!(policy
+            .select(&candidates(&[(0.95, 2), (0.8, 2)]))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_purification_policy_threshold() {
+        let policy = Threshold { threshold: 0.9 };
+        assert!(policy.select(&candidates(&[(0.8, 0)])).is_empty());
+        assert!(policy
+            .select(&candidates(&[(0.95, 0), (0.91, 0)]))
+            .is_empty());
+        // Pairs above the threshold are never sacrificed.
+        assert!(policy
+            .select(&candidates(&[(0.95, 0), (0.85, 0)]))
+            .is_empty());
+        assert_eq!(
+            vec![1, 2],
+            policy.select(&candidates(&[(0.95, 0), (0.85, 0), (0.7, 0)]))
+        );
+        assert_eq!(
+            vec![2, 1],
+            policy.select(&candidates(&[(0.7, 0), (0.8, 0), (0.85, 0)]))
+        );
+    }
+
+    #[test]
+    fn test_purification_policy_age() {
+        // The candidates are not sorted by age: the most recent are preferred.
+        let mut shuffled = candidates(&[(0.8, 0), (0.8, 0), (0.8, 0), (0.9, 1)]);
+        for (c, age) in shuffled.iter_mut().zip([3.0, 1.0, 2.0, 0.5]) {
+            c.age = age;
+        }
+        assert_eq!(vec![3, 1], Greedy { num_pairs: 2 }.select(&shuffled));
+        assert_eq!(vec![1, 2], Nested { max_rounds: 1 }.select(&shuffled));
+        assert_eq!(vec![3, 1], Pumping { max_rounds: 2 }.select(&shuffled));
+        assert_eq!(vec![1, 2], Threshold { threshold: 0.85 }.select(&shuffled));
+    }
+}
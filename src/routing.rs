@@ -0,0 +1,406 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+use petgraph::visit::EdgeRef;
+
+use crate::logical_topology::{EdgeWeight, Graph, LogicalTopology};
+
+/// Return the path with minimum cost from `source` to `target`, including
+/// both end-points, or None if `target` cannot be reached from `source`.
+///
+/// The cost of a path is the sum of the costs of its logical edges, i.e.,
+/// the number of hops.
+pub fn shortest_path(
+    logical_topology: &LogicalTopology,
+    source: u32,
+    target: u32,
+) -> Option<Vec<u32>> {
+    logical_topology.path(source, target)
+}
+
+/// Return up to `k` loopless paths from `source` to `target` with
+/// minimum cost, sorted by increasing cost, using Yen's algorithm.
+///
+/// Paths with the same cost are sorted lexicographically by their nodes.
+pub fn k_shortest_paths(
+    logical_topology: &LogicalTopology,
+    source: u32,
+    target: u32,
+    k: usize,
+) -> Vec<Vec<u32>> {
+    let graph = logical_topology.graph();
+    if k == 0 || source == target {
+        return vec![];
+    }
+    let mut shortest = match dijkstra(graph, source, target, &[], &[], |_, _, w| {
+        Some(w.cost as f64)
+    }) {
+        Some((path, _)) => vec![path],
+        None => return vec![],
+    };
+    let mut candidates: Vec<(usize, Vec<u32>)> = vec![];
+
+    while shortest.len() < k {
+        let prev = shortest.last().unwrap();
+        for i in 0..prev.len() - 1 {
+            let root = &prev[..=i];
+
+            // Remove the edges leaving the spur node along the paths already
+            // found with the same root, and the nodes of the root.
+            let banned_edges = shortest
+                .iter()
+                .filter(|path| path.len() > i + 1 && path[..=i] == *root)
+                .map(|path| (path[i], path[i + 1]))
+                .collect::<Vec<_>>();
+            if let Some((spur, _)) = dijkstra(
+                graph,
+                prev[i],
+                target,
+                &root[..i],
+                &banned_edges,
+                |_, _, w| Some(w.cost as f64),
+            ) {
+                let path = root[..i]
+                    .iter()
+                    .chain(spur.iter())
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if !shortest.contains(&path) && !candidates.iter().any(|(_, p)| *p == path) {
+                    candidates.push((path_cost(graph, &path), path));
+                }
+            }
+        }
+
+        match candidates.iter().enumerate().min_by(|a, b| a.1.cmp(b.1)) {
+            Some((ndx, _)) => shortest.push(candidates.swap_remove(ndx).1),
+            None => break,
+        }
+    }
+
+    shortest
+}
+
+/// Return the path from `source` to `target` with maximum bottleneck
+/// capacity, i.e., the minimum capacity of its logical edges, together with
+/// the latter, or None if `target` cannot be reached from `source`.
+///
+/// Among the paths with the same bottleneck capacity, the one with the
+/// minimum number of hops is returned.
+pub fn widest_path(
+    logical_topology: &LogicalTopology,
+    source: u32,
+    target: u32,
+) -> Option<(Vec<u32>, f64)> {
+    let graph = logical_topology.graph();
+    let num_nodes = graph.node_count();
+    if source == target || source as usize >= num_nodes || target as usize >= num_nodes {
+        return None;
+    }
+    let mut width = vec![None; num_nodes];
+    let mut hops = vec![0; num_nodes];
+    let mut predecessors = vec![None; num_nodes];
+    let mut visited = vec![false; num_nodes];
+    width[source as usize] = Some(f64::INFINITY);
+    while let Some(u) = (0..num_nodes)
+        .filter(|u| !visited[*u] && width[*u].is_some())
+        .max_by(|a, b| {
+            width[*a]
+                .unwrap()
+                .total_cmp(&width[*b].unwrap())
+                .then(hops[*b].cmp(&hops[*a]))
+                .then(b.cmp(a))
+        })
+    {
+        visited[u] = true;
+        for e in graph.edges((u as u32).into()) {
+            let v = e.target().index();
+            let candidate = width[u].unwrap().min(e.weight().capacity);
+            let better = match width[v] {
+                None => true,
+                Some(w) => candidate > w || (candidate == w && hops[u] + 1 < hops[v]),
+            };
+            if !visited[v] && better {
+                width[v] = Some(candidate);
+                hops[v] = hops[u] + 1;
+                predecessors[v] = Some(u as u32);
+            }
+        }
+    }
+    let width = width[target as usize]?;
+    Some((to_path(&predecessors, source, target), width))
+}
+
+/// Return the expected fidelity of the end-to-end EPR pairs obtained by
+/// entanglement swapping along a path, or None if the path has fewer than
+/// two nodes or any of its logical edges does not exist.
+///
+/// All the EPR pairs are assumed to be Werner states, and a failed swapping
+/// is assumed to produce a maximally mixed state.
+///
+/// Parameters:
+/// - `path`: the nodes of the path, including both end-points.
+/// - `edge_fidelity`: the fidelity of the EPR pairs on the logical edge
+///   from the first to the second node given.
+/// - `swapping_success_prob`: the probability that entanglement swapping
+///   succeeds at a given node.
+pub fn expected_fidelity(
+    logical_topology: &LogicalTopology,
+    path: &[u32],
+    edge_fidelity: impl Fn(u32, u32, &EdgeWeight) -> f64,
+    swapping_success_prob: impl Fn(u32) -> f64,
+) -> Option<f64> {
+    if path.len() < 2 {
+        return None;
+    }
+    let graph = logical_topology.graph();
+    let mut werner = 1.0;
+    for (i, hop) in path.windows(2).enumerate() {
+        let weight = &graph[graph.find_edge(hop[0].into(), hop[1].into())?];
+        werner *= werner_parameter(edge_fidelity(hop[0], hop[1], weight));
+        if i > 0 {
+            werner *= swapping_success_prob(hop[0]);
+        }
+    }
+    Some(0.25 + 0.75 * werner)
+}
+
+/// Return the path from `source` to `target` with maximum expected
+/// fidelity, together with the latter, or None if `target` cannot be
+/// reached from `source` with a fidelity greater than 0.25.
+///
+/// See `expected_fidelity` for the meaning of the parameters.
+pub fn max_fidelity_path(
+    logical_topology: &LogicalTopology,
+    source: u32,
+    target: u32,
+    edge_fidelity: impl Fn(u32, u32, &EdgeWeight) -> f64,
+    swapping_success_prob: impl Fn(u32) -> f64,
+) -> Option<(Vec<u32>, f64)> {
+    // The expected Werner parameter is the product of those of the logical
+    // edges and of the swapping success probabilities: maximizing it is
+    // the same as minimizing the sum of their negative logarithms.
+    let (path, cost) = dijkstra(
+        logical_topology.graph(),
+        source,
+        target,
+        &[],
+        &[],
+        |u, v, w| {
+            let mut factor = werner_parameter(edge_fidelity(u, v, w));
+            if u != source {
+                factor *= swapping_success_prob(u);
+            }
+            if factor > 0.0 {
+                Some(-factor.min(1.0).ln())
+            } else {
+                None
+            }
+        },
+    )?;
+    Some((path, 0.25 + 0.75 * (-cost).exp()))
+}
+
+/// Return the Werner parameter of a Werner state with given fidelity.
+fn werner_parameter(fidelity: f64) -> f64 {
+    (4.0 * fidelity - 1.0) / 3.0
+}
+
+/// Return the sum of the costs of the logical edges of a path.
+fn path_cost(graph: &Graph, path: &[u32]) -> usize {
+    path.windows(2)
+        .map(|hop| graph[graph.find_edge(hop[0].into(), hop[1].into()).unwrap()].cost)
+        .sum()
+}
+
+/// Return the path from `source` to `target` made of nodes following
+/// the predecessors, including both end-points.
+fn to_path(predecessors: &[Option<u32>], source: u32, target: u32) -> Vec<u32> {
+    let mut path = vec![target];
+    let mut node = target;
+    while node != source {
+        node = predecessors[node as usize].expect("broken chain of predecessors");
+        path.push(node);
+    }
+    path.reverse();
+    path
+}
+
+/// Return the path from `source` to `target` with minimum cost, and the
+/// latter, without crossing the banned nodes and edges.
+///
+/// The cost of the logical edge from u to v is given by `edge_cost`, which
+/// must be non-negative or None if the edge cannot be used. Ties are broken
+/// in favor of the nodes with smaller index.
+fn dijkstra(
+    graph: &Graph,
+    source: u32,
+    target: u32,
+    banned_nodes: &[u32],
+    banned_edges: &[(u32, u32)],
+    edge_cost: impl Fn(u32, u32, &EdgeWeight) -> Option<f64>,
+) -> Option<(Vec<u32>, f64)> {
+    let num_nodes = graph.node_count();
+    if source == target || source as usize >= num_nodes || target as usize >= num_nodes {
+        return None;
+    }
+    let mut distances = vec![f64::INFINITY; num_nodes];
+    let mut predecessors = vec![None; num_nodes];
+    let mut visited = vec![false; num_nodes];
+    for node in banned_nodes {
+        visited[*node as usize] = true;
+    }
+    let mut heap = std::collections::BinaryHeap::new();
+    distances[source as usize] = 0.0;
+    // The bit representation of non-negative floats preserves their order.
+    heap.push(std::cmp::Reverse((0.0_f64.to_bits(), source)));
+    while let Some(std::cmp::Reverse((_, u))) = heap.pop() {
+        if visited[u as usize] {
+            continue;
+        }
+        visited[u as usize] = true;
+        if u == target {
+            break;
+        }
+        let mut edges = graph.edges(u.into()).collect::<Vec<_>>();
+        edges.sort_by_key(|e| e.target().index());
+        for e in edges {
+            let v = e.target().index() as u32;
+            if visited[v as usize] || banned_edges.contains(&(u, v)) {
+                continue;
+            }
+            if let Some(cost) = edge_cost(u, v, e.weight()) {
+                let distance = distances[u as usize] + cost;
+                if distance < distances[v as usize] {
+                    distances[v as usize] = distance;
+                    predecessors[v as usize] = Some(u);
+                    heap.push(std::cmp::Reverse((distance.to_bits(), v)));
+                }
+            }
+        }
+    }
+    predecessors[target as usize]?;
+    Some((
+        to_path(&predecessors, source, target),
+        distances[target as usize],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        expected_fidelity, k_shortest_paths, max_fidelity_path, shortest_path, widest_path,
+    };
+    use crate::logical_topology::{EdgeWeight, LogicalTopology};
+
+    /// Return a logical topology with two paths from 0 to 4: a short
+    /// narrow one through 1, a long wide one through 2 and 3, and one
+    /// crossing both through 2 and 1.
+    fn logical_topology() -> LogicalTopology {
+        let mut graph = crate::logical_topology::Graph::new();
+        for _ in 0..5 {
+            graph.add_node(crate::logical_topology::NodeWeight {});
+        }
+        for (u, v, capacity) in [
+            (0, 1, 1.0),
+            (1, 4, 1.0),
+            (0, 2, 5.0),
+            (2, 3, 5.0),
+            (3, 4, 5.0),
+            (2, 1, 2.0),
+        ] {
+            graph.add_edge(
+                u.into(),
+                v.into(),
+                EdgeWeight {
+                    tx: u,
+                    memory_qubits: 1,
+                    capacity,
+                    cost: 1,
+                },
+            );
+        }
+        LogicalTopology::from_graph(graph).unwrap()
+    }
+
+    #[test]
+    fn test_routing_shortest_paths() {
+        let logical_topology = logical_topology();
+        assert_eq!(Some(vec![0, 1, 4]), shortest_path(&logical_topology, 0, 4));
+        assert_eq!(None, shortest_path(&logical_topology, 4, 0));
+
+        assert_eq!(
+            vec![vec![0, 1, 4], vec![0, 2, 1, 4], vec![0, 2, 3, 4]],
+            k_shortest_paths(&logical_topology, 0, 4, 5)
+        );
+        assert_eq!(
+            vec![vec![0, 1, 4], vec![0, 2, 1, 4]],
+            k_shortest_paths(&logical_topology, 0, 4, 2)
+        );
+        assert!(k_shortest_paths(&logical_topology, 0, 4, 0).is_empty());
+        assert!(k_shortest_paths(&logical_topology, 4, 0, 3).is_empty());
+        assert_eq!(
+            vec![vec![2, 1, 4], vec![2, 3, 4]],
+            k_shortest_paths(&logical_topology, 2, 4, 3)
+        );
+    }
+
+    #[test]
+    fn test_routing_widest_path() {
+        let logical_topology = logical_topology();
+        let (path, width) = widest_path(&logical_topology, 0, 4).unwrap();
+        assert_eq!(vec![0, 2, 3, 4], path);
+        assert_float_eq::assert_f64_near!(5.0, width);
+        let (path, width) = widest_path(&logical_topology, 0, 1).unwrap();
+        assert_eq!(vec![0, 2, 1], path);
+        assert_float_eq::assert_f64_near!(2.0, width);
+        assert!(widest_path(&logical_topology, 4, 0).is_none());
+        assert!(widest_path(&logical_topology, 0, 99).is_none());
+        assert!(widest_path(&logical_topology, 99, 0).is_none());
+    }
+
+    #[test]
+    fn test_routing_max_fidelity_path() {
+        let logical_topology = logical_topology();
+        let edge_fidelity = |u: u32, v: u32, _: &EdgeWeight| {
+            if [(0, 1), (1, 4)].contains(&(u, v)) {
+                0.7
+            } else {
+                0.99
+            }
+        };
+
+        // Unreliable swapping in the nodes of the long path.
+        let lossy = |u: u32| if u == 1 { 1.0 } else { 0.5 };
+        let (path, fidelity) =
+            max_fidelity_path(&logical_topology, 0, 4, edge_fidelity, lossy).unwrap();
+        assert_eq!(vec![0, 1, 4], path);
+        assert_float_eq::assert_f64_near!(0.52, fidelity, 16);
+        for other in [vec![0, 2, 1, 4], vec![0, 2, 3, 4]] {
+            assert!(
+                expected_fidelity(&logical_topology, &other, edge_fidelity, lossy).unwrap()
+                    < fidelity
+            );
+        }
+
+        // Perfect swapping: the long path has higher fidelity.
+        let (path, fidelity) =
+            max_fidelity_path(&logical_topology, 0, 4, edge_fidelity, |_| 1.0).unwrap();
+        assert_eq!(vec![0, 2, 3, 4], path);
+        let expected =
+            crate::tests::swapping_fidelity(crate::tests::swapping_fidelity(0.99, 0.99), 0.99);
+        assert_float_eq::assert_f64_near!(expected, fidelity, 16);
+        assert_float_eq::assert_f64_near!(
+            expected,
+            expected_fidelity(&logical_topology, &path, edge_fidelity, |_| 1.0).unwrap(),
+            16
+        );
+
+        // Paths too short or with missing logical edges or nodes.
+        for path in [vec![], vec![0], vec![0, 4], vec![4, 1, 0], vec![0, 1, 99]] {
+            assert!(expected_fidelity(&logical_topology, &path, edge_fidelity, |_| 1.0).is_none());
+        }
+
+        // Edges with fidelity not greater than 0.25 cannot be used.
+        assert!(max_fidelity_path(&logical_topology, 0, 4, |_, _, _| 0.25, |_| 1.0).is_none());
+    }
+}
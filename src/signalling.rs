@@ -0,0 +1,220 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+/// Speed of light in vacuum, in m/s.
+pub const SPEED_OF_LIGHT: f64 = 299_792_458.0;
+
+/// Parameters of the propagation and processing delays of the signalling.
+/// Missing fields take their values from `Default`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SignallingParams {
+    /// If false, photons and classical messages are delivered instantly.
+    pub enabled: bool,
+    /// Propagation speed of photons and classical messages, in m/s.
+    pub propagation_speed: f64,
+    /// Processing delay of a classical message at every hop, in s.
+    pub processing_delay: f64,
+}
+
+impl Default for SignallingParams {
+    /// Signalling disabled, i.e., no propagation and processing delays.
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            propagation_speed: SPEED_OF_LIGHT,
+            processing_delay: 0.0,
+        }
+    }
+}
+
+impl SignallingParams {
+    pub fn valid(&self) -> anyhow::Result<()> {
+        let mut errors = vec![];
+        if self.propagation_speed <= 0.0 {
+            errors.push(format!(
+                "propagation speed ({}) <= 0",
+                self.propagation_speed
+            ));
+        }
+        if self.processing_delay < 0.0 {
+            errors.push(format!("processing delay ({}) < 0", self.processing_delay));
+        }
+        if !errors.is_empty() {
+            anyhow::bail!("invalid signalling parameters: {}", errors.join(","))
+        }
+        Ok(())
+    }
+
+    /// Return the time for a photon to travel from node u to node v, in s,
+    /// or None if the two nodes are not connected in the physical topology.
+    /// Always zero if signalling is disabled.
+    pub fn propagation_delay(
+        &self,
+        physical_topology: &mut crate::physical_topology::PhysicalTopology,
+        u: u32,
+        v: u32,
+    ) -> Option<f64> {
+        if !self.enabled {
+            return Some(0.0);
+        }
+        let distance = physical_topology.distance(u, v).ok()?;
+        Some(distance / self.propagation_speed)
+    }
+
+    /// Return the time for a classical message to travel from node u to
+    /// node v, in s, along the shortest path on the physical topology and
+    /// including the processing delay at every hop, or None if the two nodes
+    /// are not connected in the physical topology.
+    /// Always zero if signalling is disabled.
+    pub fn message_delay(
+        &self,
+        physical_topology: &mut crate::physical_topology::PhysicalTopology,
+        u: u32,
+        v: u32,
+    ) -> Option<f64> {
+        if !self.enabled {
+            return Some(0.0);
+        }
+        let hops = physical_topology.hops(u, v).ok()?;
+        Some(self.propagation_delay(physical_topology, u, v)? + hops as f64 * self.processing_delay)
+    }
+
+    /// Return the time after which node u knows that an EPR pair generated
+    /// by `tx` between u and v has been established, in s, i.e., when it
+    /// receives the detection outcome from v, or None if the nodes are not
+    /// connected in the physical topology.
+    pub fn heralding_delay(
+        &self,
+        physical_topology: &mut crate::physical_topology::PhysicalTopology,
+        tx: u32,
+        u: u32,
+        v: u32,
+    ) -> Option<f64> {
+        Some(
+            self.propagation_delay(physical_topology, tx, v)?
+                + self.message_delay(physical_topology, v, u)?,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SignallingParams;
+    use crate::physical_topology::{ChainParams, NodeWeight, PhysicalTopology, StaticFidelities};
+
+    #[test]
+    fn test_signalling_delays() -> anyhow::Result<()> {
+        // OGS 0 -> SAT 2 -> SAT 3 -> OGS 1
+        let mut physical_topology = PhysicalTopology::from_chain_static(
+            ChainParams {
+                orbit_to_orbit_distance: 3000e3,
+                ground_to_orbit_distance: 1000e3,
+                num_repeaters: 2,
+            },
+            NodeWeight::default_sat(),
+            NodeWeight::default_ogs(),
+            StaticFidelities::default(),
+        )?;
+
+        let params = SignallingParams {
+            enabled: true,
+            propagation_speed: 1e6,
+            processing_delay: 0.01,
+        };
+        assert!(params.valid().is_ok());
+        assert!(SignallingParams::default().valid().is_ok());
+        assert!(SignallingParams {
+            enabled: true,
+            propagation_speed: 0.0,
+            processing_delay: -1.0
+        }
+        .valid()
+        .is_err());
+
+        assert_float_eq::assert_f64_near!(
+            4.0,
+            params
+                .propagation_delay(&mut physical_topology, 2, 1)
+                .unwrap()
+        );
+        assert_float_eq::assert_f64_near!(
+            4.02,
+            params.message_delay(&mut physical_topology, 2, 1).unwrap()
+        );
+        assert_float_eq::assert_f64_near!(
+            0.0,
+            params.message_delay(&mut physical_topology, 2, 2).unwrap()
+        );
+
+        // Generation at SAT 2, which is one of the ends.
+        assert_float_eq::assert_f64_near!(
+            2.01,
+            params
+                .heralding_delay(&mut physical_topology, 2, 2, 0)
+                .unwrap()
+        );
+        assert_float_eq::assert_f64_near!(
+            1.01,
+            params
+                .heralding_delay(&mut physical_topology, 2, 0, 2)
+                .unwrap()
+        );
+
+        // Generation at SAT 3 for OGS 0 and SAT 2.
+        assert_float_eq::assert_f64_near!(
+            4.01,
+            params
+                .heralding_delay(&mut physical_topology, 3, 0, 2)
+                .unwrap()
+        );
+        assert_float_eq::assert_f64_near!(
+            5.01,
+            params
+                .heralding_delay(&mut physical_topology, 3, 2, 0)
+                .unwrap()
+        );
+
+        // Nodes 0 and 1 are not connected to nodes 2 and 3.
+        let mut physical_topology = PhysicalTopology::from_distances(
+            vec![(0, 1, 1000.0), (2, 3, 1000.0)],
+            StaticFidelities::default(),
+        );
+        assert!(params.message_delay(&mut physical_topology, 0, 1).is_some());
+        assert!(params
+            .propagation_delay(&mut physical_topology, 1, 2)
+            .is_none());
+        assert!(params.message_delay(&mut physical_topology, 1, 2).is_none());
+        assert!(params
+            .heralding_delay(&mut physical_topology, 2, 0, 1)
+            .is_none());
+
+        // No delays with signalling disabled, even between disconnected nodes.
+        let disabled = SignallingParams::default();
+        assert_eq!(
+            Some(0.0),
+            disabled.message_delay(&mut physical_topology, 1, 2)
+        );
+        assert_eq!(
+            Some(0.0),
+            disabled.heralding_delay(&mut physical_topology, 2, 0, 1)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_signalling_params_partial() -> anyhow::Result<()> {
+        let params: SignallingParams = serde_json::from_str(r#"{"processing_delay": 0.5}"#)?;
+        assert!(!params.enabled);
+        assert_float_eq::assert_f64_near!(super::SPEED_OF_LIGHT, params.propagation_speed);
+        assert_float_eq::assert_f64_near!(0.5, params.processing_delay);
+
+        let params: SignallingParams = serde_json::from_str(r#"{"enabled": true}"#)?;
+        assert!(params.enabled);
+        assert_float_eq::assert_f64_near!(super::SPEED_OF_LIGHT, params.propagation_speed);
+        assert_float_eq::assert_f64_near!(0.0, params.processing_delay);
+
+        Ok(())
+    }
+}
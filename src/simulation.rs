@@ -13,6 +13,7 @@ pub struct Simulation {
     events: crate::event_queue::EventQueue,
     single: crate::output::OutputSingle,
     series: crate::output::OutputSeries,
+    convergence: crate::topology_search::Convergence,
 
     // configuration
     config: crate::config::Config,
@@ -51,50 +52,68 @@ impl Simulation {
         config: &crate::config::Config,
         physical_topology: crate::physical_topology::PhysicalTopology,
         save_to_dot: bool,
-    ) -> crate::network::Network {
+    ) -> anyhow::Result<(crate::network::Network, crate::topology_search::Convergence)> {
         let mut rng = rand::rngs::StdRng::seed_from_u64(config.seed);
 
-        let logical_topology = if let Ok(logical_topology) =
-            crate::logical_topology::LogicalTopology::from_physical_topology(
-                &config
-                    .user_config
-                    .logical_topology
-                    .physical_to_logical_policy,
-                &physical_topology,
-                &mut rng,
-            ) {
-            if crate::logical_topology::is_valid(logical_topology.graph(), &physical_topology)
-                .is_ok()
-            {
-                log::debug!("{:#?}", logical_topology.graph());
-
-                if save_to_dot {
-                    let _ = save_to_dot_file(logical_topology.graph(), "logical_topology.dot");
-                }
+        let logical_topology = crate::logical_topology::LogicalTopology::from_physical_topology(
+            &config
+                .user_config
+                .logical_topology
+                .physical_to_logical_policy,
+            &config.user_config.logical_topology.search,
+            &config.user_config.logical_topology.allocation,
+            &physical_topology,
+            &mut rng,
+        )?;
+        crate::logical_topology::is_valid(logical_topology.graph(), &physical_topology)?;
+        log::debug!("{:#?}", logical_topology.graph());
 
-                logical_topology
-            } else {
-                crate::logical_topology::LogicalTopology::default()
-            }
-        } else {
-            crate::logical_topology::LogicalTopology::default()
-        };
-        crate::network::Network::new(&logical_topology, physical_topology, config.seed)
+        if save_to_dot {
+            let _ = save_to_dot_file(logical_topology.graph(), "logical_topology.dot");
+        }
+
+        let network = crate::network::Network::new(
+            &logical_topology,
+            physical_topology,
+            &config.user_config.clients,
+            &config.user_config.purification,
+            &config.user_config.memory,
+            &config.user_config.signalling,
+            &config.user_config.mobility,
+            config.user_config.backend,
+            config.seed,
+        );
+        Ok((network, logical_topology.convergence().clone()))
     }
 
     pub fn new(config: crate::config::Config, save_to_dot: bool) -> anyhow::Result<Self> {
         anyhow::ensure!(config.user_config.duration > 0.0, "vanishing duration");
+        config.user_config.clients.valid()?;
+        config.user_config.purification.valid()?;
+        config.user_config.memory.valid()?;
+        config.user_config.signalling.valid()?;
+        config.user_config.mobility.valid()?;
 
-        let physical_topology = config
+        let mut physical_topology = config
             .user_config
             .physical_topology
-            .to_physical_topology()?;
+            .to_physical_topology(config.seed)?;
+        config
+            .user_config
+            .link_model
+            .apply(&mut physical_topology)?;
+
+        config
+            .user_config
+            .logical_topology
+            .allocation
+            .valid(&physical_topology)?;
 
         if save_to_dot {
             save_to_dot_file(physical_topology.graph(), "physical_topology.dot")?;
         }
 
-        let network = Self::create_network(&config, physical_topology, save_to_dot);
+        let (network, convergence) = Self::create_network(&config, physical_topology, save_to_dot)?;
 
         // Save to Graphviz files and terminate immediately.
         anyhow::ensure!(!save_to_dot, "saved to Dot files");
@@ -107,6 +126,7 @@ impl Simulation {
             events: crate::event_queue::EventQueue::default(),
             single: crate::output::OutputSingle::default(),
             series,
+            convergence,
         })
     }
 
@@ -190,6 +210,10 @@ impl Simulation {
                             vec![],
                         )
                     }
+                    EventType::TopologyEvent(event_data) => {
+                        log::debug!("T {} {:?}", now, event_data);
+                        self.network.handle(event)
+                    }
                     EventType::NodeEvent(event_data) => {
                         log::debug!("N {} {:?}", now, event_data);
                         self.network.handle(event)
@@ -213,6 +237,12 @@ impl Simulation {
         self.single.one_time("num_events", num_events as f64);
         self.single
             .one_time("execution_time", real_now.elapsed().as_secs_f64());
+        for (iteration, (current, best)) in self.convergence.iter().enumerate() {
+            for (label, value) in [("current", current), ("best", best)] {
+                self.series
+                    .add("logical_topology_fitness", label, iteration as f64, *value);
+            }
+        }
 
         // return the simulation output
         let single = std::mem::take(&mut self.single);
@@ -231,4 +261,18 @@ mod tests {
     fn test_simulation_run() -> anyhow::Result<()> {
         Ok(())
     }
+
+    #[test]
+    fn test_simulation_invalid_logical_topology() {
+        // Demand between non-existing nodes.
+        let mut config = crate::config::Config::default();
+        config.user_config.logical_topology.allocation =
+            crate::logical_topology::Allocation::Demand(vec![crate::logical_topology::Demand {
+                source: 998,
+                target: 999,
+                rate: 1.0,
+            }]);
+        let err = super::Simulation::new(config, false).err().unwrap();
+        assert!(err.to_string().contains("998"), "{}", err);
+    }
 }
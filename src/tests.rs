@@ -15,6 +15,7 @@ pub fn physical_topology_2_2() -> crate::physical_topology::PhysicalTopology {
             node_type: crate::physical_topology::NodeType::SAT,
             memory_qubits: 10,
             decay_rate: 1.0,
+            dephasing_rate: 0.0,
             swapping_success_prob: 0.5,
             detectors: 10,
             transmitters: 10,
@@ -24,6 +25,7 @@ pub fn physical_topology_2_2() -> crate::physical_topology::PhysicalTopology {
             node_type: crate::physical_topology::NodeType::OGS,
             memory_qubits: 20,
             decay_rate: 1.0,
+            dephasing_rate: 0.0,
             swapping_success_prob: 0.0,
             detectors: 10,
             transmitters: 0,
@@ -34,6 +36,142 @@ pub fn physical_topology_2_2() -> crate::physical_topology::PhysicalTopology {
     .expect("invalid physical topology")
 }
 
+/// Return the two-line element sets of a Walker-like constellation at about
+/// 550 km of altitude, with epoch 2025-01-01T00:00:00.
+pub fn tle_constellation(num_planes: u32, sats_per_plane: u32) -> String {
+    let checksum = |line: &str| {
+        line.chars()
+            .map(|c| match c {
+                '-' => 1,
+                _ => c.to_digit(10).unwrap_or(0),
+            })
+            .sum::<u32>()
+            % 10
+    };
+    let mut tles = String::new();
+    for plane in 0..num_planes {
+        for slot in 0..sats_per_plane {
+            let num = 1 + plane * sats_per_plane + slot;
+            let line1 = format!(
+                "1 {:05}U 25001A   25001.00000000  .00000000  00000-0  10000-4 0  999",
+                num
+            );
+            let line2 = format!(
+                "2 {:05} {:8.4} {:8.4} 0001000 {:8.4} {:8.4} {:11.8}{:05}",
+                num,
+                53.0,
+                360.0 * plane as f64 / num_planes as f64,
+                0.0,
+                360.0 * slot as f64 / sats_per_plane as f64,
+                15.05,
+                1
+            );
+            tles.push_str(&format!(
+                "SAT-{}\n{}{}\n{}{}\n",
+                num,
+                line1,
+                checksum(&line1),
+                line2,
+                checksum(&line2)
+            ));
+        }
+    }
+    tles
+}
+
+/// Return a hybrid topology in JSON format, with two OGSs connected
+/// directly and through a satellite.
+pub fn topology_json() -> String {
+    let ogs = r#"{"node_type": "OGS", "memory_qubits": 100, "decay_rate": 1.0,
+        "dephasing_rate": 0.0, "swapping_success_prob": 0.0, "detectors": 10,
+        "transmitters": 0, "capacity": 0.0}"#;
+    let sat = r#"{"node_type": "SAT", "memory_qubits": 20, "decay_rate": 1.0,
+        "dephasing_rate": 0.0, "swapping_success_prob": 0.95, "detectors": 10,
+        "transmitters": 10, "capacity": 1000.0}"#;
+    format!(
+        r#"{{
+  "nodes": [
+    {{"name": "Pisa", "coordinates": [0.0, 0.0, 0.0], "weight": {ogs}}},
+    {{"name": "Rome", "coordinates": [1000.0, 0.0, 0.0], "weight": {ogs}}},
+    {{"weight": {sat}}}
+  ],
+  "edges": [
+    {{"u": 0, "v": 1}},
+    {{"u": 2, "v": 0, "distance": 500e3}},
+    {{"u": 2, "v": 1, "distance": 500e3}}
+  ]
+}}"#
+    )
+}
+
+/// Return a hybrid space-ground topology in JSON format, with two OGSs
+/// connected via 50 km fiber links to a ground repeater with a source on
+/// board, and through a satellite.
+pub fn topology_hybrid_json() -> String {
+    let node = |node_type: &str, transmitters: u32, capacity: f64| {
+        format!(
+            r#"{{"node_type": "{node_type}", "memory_qubits": 10, "decay_rate": 0.0,
+            "dephasing_rate": 0.0, "swapping_success_prob": 1.0, "detectors": 10,
+            "transmitters": {transmitters}, "capacity": {capacity:.1}}}"#
+        )
+    };
+    format!(
+        r#"{{
+  "nodes": [
+    {{"name": "Pisa", "weight": {ogs_tx}}},
+    {{"name": "Florence", "weight": {gr}}},
+    {{"name": "Bologna", "weight": {ogs}}},
+    {{"weight": {sat}}}
+  ],
+  "edges": [
+    {{"u": 0, "v": 1, "distance": 50e3, "attenuation": 0.2}},
+    {{"u": 1, "v": 2, "distance": 50e3, "attenuation": 0.2}},
+    {{"u": 3, "v": 0, "distance": 500e3}},
+    {{"u": 3, "v": 2, "distance": 500e3}}
+  ]
+}}"#,
+        ogs_tx = node("OGS", 1, 100.0),
+        gr = node("GR", 1, 1000.0),
+        ogs = node("OGS", 0, 0.0),
+        sat = node("SAT", 10, 1000.0),
+    )
+}
+
+/// Return the same topology as `topology_json()` in GraphML format.
+pub fn topology_graphml() -> String {
+    r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="d0" for="node" attr.name="name" attr.type="string"/>
+  <key id="d1" for="node" attr.name="x" attr.type="double"/>
+  <key id="d2" for="node" attr.name="y" attr.type="double"/>
+  <key id="d3" for="node" attr.name="node_type" attr.type="string"><default>OGS</default></key>
+  <key id="d4" for="node" attr.name="memory_qubits" attr.type="int"><default>100</default></key>
+  <key id="d5" for="node" attr.name="decay_rate" attr.type="double"><default>1.0</default></key>
+  <key id="d6" for="node" attr.name="dephasing_rate" attr.type="double"><default>0.0</default></key>
+  <key id="d7" for="node" attr.name="swapping_success_prob" attr.type="double"><default>0.0</default></key>
+  <key id="d8" for="node" attr.name="detectors" attr.type="int"><default>10</default></key>
+  <key id="d9" for="node" attr.name="transmitters" attr.type="int"><default>0</default></key>
+  <key id="d10" for="node" attr.name="capacity" attr.type="double"><default>0.0</default></key>
+  <key id="d11" for="edge" attr.name="distance" attr.type="double"/>
+  <graph id="G" edgedefault="undirected">
+    <node id="n0"><data key="d0">Pisa</data><data key="d1">0</data><data key="d2">0</data></node>
+    <node id="n1"><data key="d0">Rome</data><data key="d1">1000</data><data key="d2">0</data></node>
+    <node id="s0">
+      <data key="d3">SAT</data>
+      <data key="d4">20</data>
+      <data key="d7">0.95</data>
+      <data key="d9">10</data>
+      <data key="d10">1000</data>
+    </node>
+    <edge source="n0" target="n1"/>
+    <edge source="s0" target="n0"><data key="d11">500e3</data></edge>
+    <edge source="s0" target="n1"><data key="d11">500e3</data></edge>
+  </graph>
+</graphml>
+"#
+    .to_string()
+}
+
 pub fn logical_topology_2_2() -> (
     crate::physical_topology::PhysicalTopology,
     crate::logical_topology::LogicalTopology,
@@ -46,11 +184,13 @@ pub fn logical_topology_2_2() -> (
         if let Ok(logical_topology) =
             crate::logical_topology::LogicalTopology::from_physical_topology(
                 &crate::logical_topology::PhysicalToLogicalPolicy::RandomGreedy,
+                &crate::topology_search::SearchParams::default(),
+                &crate::logical_topology::Allocation::default(),
                 &physical_topology,
                 &mut rng,
             )
         {
-            if crate::logical_topology::is_valid(&logical_topology.graph(), &physical_topology)
+            if crate::logical_topology::is_valid(logical_topology.graph(), &physical_topology)
                 .is_ok()
             {
                 return (physical_topology, logical_topology);
@@ -62,3 +202,25 @@ pub fn logical_topology_2_2() -> (
         num_tries
     );
 }
+
+/// Return the fidelity of the EPR pair obtained by entanglement swapping of
+/// two Werner states with fidelities `f1` and `f2`, used as a reference.
+pub fn swapping_fidelity(f1: f64, f2: f64) -> f64 {
+    f1 * f2 + (1.0 - f1) * (1.0 - f2) / 3.0
+}
+
+#[test]
+fn test_swapping_fidelity() {
+    assert_float_eq::assert_f64_near!(1.0, swapping_fidelity(1.0, 1.0));
+    assert_float_eq::assert_f64_near!(0.9, swapping_fidelity(1.0, 0.9));
+    assert_float_eq::assert_f64_near!(0.9, swapping_fidelity(0.9, 1.0));
+    assert_float_eq::assert_f64_near!(0.25, swapping_fidelity(0.25, 0.9));
+    assert_float_eq::assert_f64_near!(0.8133333333333334, swapping_fidelity(0.9, 0.9));
+
+    // The Werner parameters multiply.
+    let werner = |f: f64| (4.0 * f - 1.0) / 3.0;
+    assert_float_eq::assert_f64_near!(
+        werner(0.8) * werner(0.7),
+        werner(swapping_fidelity(0.8, 0.7))
+    );
+}
@@ -0,0 +1,79 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+/// Timers associated with keys, at most one per key.
+///
+/// The expiration events are managed by the caller through the event queue,
+/// which does not support the removal of events: a cancelled timer is only
+/// marked as such, and its expiration event is ignored.
+#[derive(Debug, Default)]
+pub struct Timers {
+    /// Active timer identifier, indexed by key.
+    active: std::collections::HashMap<u64, u64>,
+    /// Last timer identifier assigned.
+    last_timer_id: u64,
+}
+
+impl Timers {
+    /// Schedule a new timer for a given key, which cancels the previous one,
+    /// if any. Return the identifier of the new timer.
+    pub fn schedule(&mut self, key: u64) -> u64 {
+        self.last_timer_id += 1;
+        self.active.insert(key, self.last_timer_id);
+        self.last_timer_id
+    }
+
+    /// Cancel the timer for a given key. Return false if there was none.
+    pub fn cancel(&mut self, key: u64) -> bool {
+        self.active.remove(&key).is_some()
+    }
+
+    /// Notify the expiration of a timer. Return true if the timer is active,
+    /// in which case it is not active anymore after the call.
+    pub fn expire(&mut self, key: u64, timer_id: u64) -> bool {
+        if self.active.get(&key) == Some(&timer_id) {
+            self.active.remove(&key);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Return the number of active timers.
+    pub fn len(&self) -> usize {
+        self.active.len()
+    }
+
+    /// Return true if there are no active timers.
+    pub fn is_empty(&self) -> bool {
+        self.active.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Timers;
+
+    #[test]
+    fn test_timers() {
+        let mut timers = Timers::default();
+        assert!(timers.is_empty());
+
+        let t1 = timers.schedule(10);
+        let t2 = timers.schedule(20);
+        assert_ne!(t1, t2);
+        assert_eq!(2, timers.len());
+
+        // Rescheduling cancels the previous timer.
+        let t3 = timers.schedule(10);
+        assert!(!timers.expire(10, t1));
+        assert!(timers.expire(10, t3));
+        assert!(!timers.expire(10, t3));
+
+        // Cancelled timers do not expire.
+        assert!(timers.cancel(20));
+        assert!(!timers.cancel(20));
+        assert!(!timers.expire(20, t2));
+        assert!(timers.is_empty());
+    }
+}
@@ -0,0 +1,684 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+//! Two-line element sets and the SGP4 propagator for near-Earth orbits,
+//! following Vallado et al., "Revisiting Spacetrack Report #3", 2006,
+//! with WGS-72 constants.
+
+use std::f64::consts::PI;
+
+/// WGS-72 gravitational parameter, in km^3/s^2.
+const MU: f64 = 398600.8;
+/// WGS-72 equatorial radius of the Earth, in km.
+const RADIUS: f64 = 6378.135;
+/// WGS-72 zonal harmonics.
+const J2: f64 = 0.001082616;
+const J3: f64 = -0.00000253881;
+const J4: f64 = -0.00000165597;
+/// Minutes per day.
+const MINUTES_PER_DAY: f64 = 1440.0;
+
+fn xke() -> f64 {
+    60.0 / (RADIUS * RADIUS * RADIUS / MU).sqrt()
+}
+
+/// Orbital elements of a satellite from a two-line element set.
+#[derive(Debug, Clone)]
+pub struct Tle {
+    /// Name of the satellite, empty if not given.
+    pub name: String,
+    /// NORAD catalog number.
+    pub satellite_number: u32,
+    /// Epoch, as Julian date.
+    pub epoch: f64,
+    /// Drag term, in inverse Earth radii.
+    pub bstar: f64,
+    /// Inclination, in degrees.
+    pub inclination: f64,
+    /// Right ascension of the ascending node, in degrees.
+    pub raan: f64,
+    /// Eccentricity.
+    pub eccentricity: f64,
+    /// Argument of perigee, in degrees.
+    pub argument_of_perigee: f64,
+    /// Mean anomaly, in degrees.
+    pub mean_anomaly: f64,
+    /// Mean motion, in revolutions per day.
+    pub mean_motion: f64,
+}
+
+fn checksum(line: &str) -> u32 {
+    line.chars()
+        .take(68)
+        .map(|c| match c {
+            '-' => 1,
+            _ => c.to_digit(10).unwrap_or(0),
+        })
+        .sum::<u32>()
+        % 10
+}
+
+fn field<T: std::str::FromStr>(
+    line: &str,
+    start: usize,
+    end: usize,
+    name: &str,
+) -> anyhow::Result<T> {
+    let value = line
+        .get(start - 1..end)
+        .ok_or_else(|| anyhow::anyhow!("line too short for the {}", name))?
+        .trim();
+    value
+        .parse::<T>()
+        .map_err(|_| anyhow::anyhow!("invalid {}: '{}'", name, value))
+}
+
+/// Parse a number with an implied leading decimal point and an optional
+/// exponent, e.g., " 28098-4" for 0.28098e-4.
+fn implied_decimal(value: &str, name: &str) -> anyhow::Result<f64> {
+    let value = value.trim();
+    let err = || anyhow::anyhow!("invalid {}: '{}'", name, value);
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(digits) => (-1.0, digits),
+        None => (1.0, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let (mantissa, exponent) = match digits.rfind(['-', '+']) {
+        Some(pos) if pos > 0 => (
+            &digits[..pos],
+            digits[pos..].parse::<i32>().map_err(|_| err())?,
+        ),
+        _ => (digits, 0),
+    };
+    let mantissa = format!("0.{}", mantissa)
+        .parse::<f64>()
+        .map_err(|_| err())?;
+    Ok(sign * mantissa * 10.0_f64.powi(exponent))
+}
+
+impl Tle {
+    /// Parse a two-line element set, with an optional name.
+    pub fn parse(name: &str, line1: &str, line2: &str) -> anyhow::Result<Self> {
+        let line1 = line1.trim_end();
+        let line2 = line2.trim_end();
+        for (num, line) in [('1', line1), ('2', line2)] {
+            anyhow::ensure!(
+                line.len() >= 69 && line.starts_with(num),
+                "invalid TLE line {}: '{}'",
+                num,
+                line
+            );
+            let expected = line[68..69].parse::<u32>().unwrap_or(10);
+            anyhow::ensure!(
+                checksum(line) == expected,
+                "invalid checksum of TLE line {}: '{}'",
+                num,
+                line
+            );
+        }
+
+        let satellite_number = field::<u32>(line1, 3, 7, "satellite number")?;
+        anyhow::ensure!(
+            satellite_number == field::<u32>(line2, 3, 7, "satellite number")?,
+            "mismatching satellite numbers in TLE lines: '{}' '{}'",
+            line1,
+            line2
+        );
+
+        let year = field::<i32>(line1, 19, 20, "epoch year")?;
+        let year = if year < 57 { 2000 + year } else { 1900 + year };
+        let day = field::<f64>(line1, 21, 32, "epoch day")?;
+
+        Ok(Self {
+            name: name.trim().to_string(),
+            satellite_number,
+            epoch: julian_date(year, 1, 1, 0, 0, 0.0) + day - 1.0,
+            bstar: implied_decimal(&line1[53..61], "drag term")?,
+            inclination: field(line2, 9, 16, "inclination")?,
+            raan: field(line2, 18, 25, "right ascension of the ascending node")?,
+            eccentricity: implied_decimal(&line2[26..33], "eccentricity")?,
+            argument_of_perigee: field(line2, 35, 42, "argument of perigee")?,
+            mean_anomaly: field(line2, 44, 51, "mean anomaly")?,
+            mean_motion: field(line2, 53, 63, "mean motion")?,
+        })
+    }
+
+    /// Parse all the element sets in a string, in two-line or three-line
+    /// format, skipping empty lines.
+    pub fn parse_all(content: &str) -> anyhow::Result<Vec<Self>> {
+        let lines = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect::<Vec<&str>>();
+        let mut tles = vec![];
+        let mut ndx = 0;
+        while ndx < lines.len() {
+            let name = if lines[ndx].starts_with("1 ") {
+                ""
+            } else {
+                ndx += 1;
+                lines[ndx - 1].strip_prefix("0 ").unwrap_or(lines[ndx - 1])
+            };
+            anyhow::ensure!(
+                ndx + 1 < lines.len(),
+                "truncated TLE at line '{}'",
+                lines[lines.len() - 1]
+            );
+            tles.push(Self::parse(name, lines[ndx], lines[ndx + 1])?);
+            ndx += 2;
+        }
+        Ok(tles)
+    }
+
+    /// Read all the element sets from a local file.
+    pub fn from_file(path: &str) -> anyhow::Result<Vec<Self>> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| anyhow::anyhow!("cannot read TLE file {}: {}", path, err))?;
+        Self::parse_all(&content)
+    }
+}
+
+/// Return the Julian date of a UTC date and time.
+pub fn julian_date(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: f64) -> f64 {
+    let year = year as f64;
+    let month = month as f64;
+    367.0 * year - (7.0 * (year + ((month + 9.0) / 12.0).floor()) * 0.25).floor()
+        + (275.0 * month / 9.0).floor()
+        + day as f64
+        + 1721013.5
+        + ((second / 60.0 + minute as f64) / 60.0 + hour as f64) / 24.0
+}
+
+/// Parse a UTC date and time in the format YYYY-MM-DDTHH:MM:SS, with
+/// optional fractional seconds and trailing Z, and return its Julian date.
+pub fn parse_utc(value: &str) -> anyhow::Result<f64> {
+    let err = || anyhow::anyhow!("invalid UTC date and time: '{}'", value);
+    let (date, time) = value
+        .trim()
+        .trim_end_matches('Z')
+        .split_once(['T', ' '])
+        .ok_or_else(err)?;
+    let date = date.split('-').collect::<Vec<&str>>();
+    let time = time.split(':').collect::<Vec<&str>>();
+    anyhow::ensure!(date.len() == 3 && time.len() == 3, err());
+    let year = date[0].parse::<i32>().map_err(|_| err())?;
+    let month = date[1].parse::<u32>().map_err(|_| err())?;
+    let day = date[2].parse::<u32>().map_err(|_| err())?;
+    let hour = time[0].parse::<u32>().map_err(|_| err())?;
+    let minute = time[1].parse::<u32>().map_err(|_| err())?;
+    let second = time[2].parse::<f64>().map_err(|_| err())?;
+    anyhow::ensure!(
+        (1..=12).contains(&month)
+            && (1..=31).contains(&day)
+            && hour < 24
+            && minute < 60
+            && (0.0..61.0).contains(&second),
+        err()
+    );
+    Ok(julian_date(year, month, day, hour, minute, second))
+}
+
+/// Return the Greenwich mean sidereal time at a given Julian date, in rad.
+pub fn gmst(julian_date: f64) -> f64 {
+    let t = (julian_date - 2451545.0) / 36525.0;
+    let seconds = -6.2e-6 * t * t * t
+        + 0.093104 * t * t
+        + (876600.0 * 3600.0 + 8640184.812866) * t
+        + 67310.54841;
+    (seconds * PI / 180.0 / 240.0).rem_euclid(2.0 * PI)
+}
+
+/// SGP4 propagator of a near-Earth satellite, initialized from its TLE.
+///
+/// Positions are in the True Equator Mean Equinox (TEME) frame.
+#[derive(Debug, Clone)]
+pub struct Sgp4 {
+    epoch: f64,
+    bstar: f64,
+    ecco: f64,
+    inclo: f64,
+    nodeo: f64,
+    argpo: f64,
+    mo: f64,
+    no: f64,
+    isimp: bool,
+    aycof: f64,
+    con41: f64,
+    cc1: f64,
+    cc4: f64,
+    cc5: f64,
+    d2: f64,
+    d3: f64,
+    d4: f64,
+    delmo: f64,
+    eta: f64,
+    argpdot: f64,
+    omgcof: f64,
+    sinmao: f64,
+    t2cof: f64,
+    t3cof: f64,
+    t4cof: f64,
+    t5cof: f64,
+    x1mth2: f64,
+    x7thm1: f64,
+    mdot: f64,
+    nodedot: f64,
+    xlcof: f64,
+    xmcof: f64,
+    nodecf: f64,
+}
+
+impl Sgp4 {
+    /// Initialize the propagator. Return error if the orbit is invalid or
+    /// deep-space, i.e., with a period of 225 minutes or more.
+    pub fn new(tle: &Tle) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            (0.0..1.0).contains(&tle.eccentricity),
+            "invalid eccentricity of satellite {}: {}",
+            tle.satellite_number,
+            tle.eccentricity
+        );
+        anyhow::ensure!(
+            tle.mean_motion > 0.0,
+            "invalid mean motion of satellite {}: {}",
+            tle.satellite_number,
+            tle.mean_motion
+        );
+
+        let xke = xke();
+        let j3oj2 = J3 / J2;
+        let x2o3 = 2.0 / 3.0;
+
+        let ecco = tle.eccentricity;
+        let inclo = tle.inclination.to_radians();
+        let nodeo = tle.raan.to_radians();
+        let argpo = tle.argument_of_perigee.to_radians();
+        let mo = tle.mean_anomaly.to_radians();
+        let no_kozai = tle.mean_motion * 2.0 * PI / MINUTES_PER_DAY;
+        let bstar = tle.bstar;
+
+        // Recover the original mean motion and semi-major axis.
+        let eccsq = ecco * ecco;
+        let omeosq = 1.0 - eccsq;
+        let rteosq = omeosq.sqrt();
+        let cosio = inclo.cos();
+        let cosio2 = cosio * cosio;
+        let ak = (xke / no_kozai).powf(x2o3);
+        let d1 = 0.75 * J2 * (3.0 * cosio2 - 1.0) / (rteosq * omeosq);
+        let del = d1 / (ak * ak);
+        let adel = ak * (1.0 - del * del - del * (1.0 / 3.0 + 134.0 * del * del / 81.0));
+        let del = d1 / (adel * adel);
+        let no = no_kozai / (1.0 + del);
+        let ao = (xke / no).powf(x2o3);
+        let sinio = inclo.sin();
+        let po = ao * omeosq;
+        let con42 = 1.0 - 5.0 * cosio2;
+        let con41 = -con42 - cosio2 - cosio2;
+        let posq = po * po;
+        let rp = ao * (1.0 - ecco);
+
+        anyhow::ensure!(
+            2.0 * PI / no < 225.0,
+            "deep-space orbits are not supported (satellite {})",
+            tle.satellite_number
+        );
+
+        // Atmospheric drag parameters, depending on the perigee.
+        let ss = 78.0 / RADIUS + 1.0;
+        let qzms2t = ((120.0 - 78.0) / RADIUS).powi(4);
+        let isimp = rp < 220.0 / RADIUS + 1.0;
+        let mut sfour = ss;
+        let mut qzms24 = qzms2t;
+        let perige = (rp - 1.0) * RADIUS;
+        if perige < 156.0 {
+            sfour = if perige < 98.0 { 20.0 } else { perige - 78.0 };
+            qzms24 = ((120.0 - sfour) / RADIUS).powi(4);
+            sfour = sfour / RADIUS + 1.0;
+        }
+        let pinvsq = 1.0 / posq;
+
+        let tsi = 1.0 / (ao - sfour);
+        let eta = ao * ecco * tsi;
+        let etasq = eta * eta;
+        let eeta = ecco * eta;
+        let psisq = (1.0 - etasq).abs();
+        let coef = qzms24 * tsi.powi(4);
+        let coef1 = coef / psisq.powf(3.5);
+        let cc2 = coef1
+            * no
+            * (ao * (1.0 + 1.5 * etasq + eeta * (4.0 + etasq))
+                + 0.375 * J2 * tsi / psisq * con41 * (8.0 + 3.0 * etasq * (8.0 + etasq)));
+        let cc1 = bstar * cc2;
+        let cc3 = if ecco > 1.0e-4 {
+            -2.0 * coef * tsi * j3oj2 * no * sinio / ecco
+        } else {
+            0.0
+        };
+        let x1mth2 = 1.0 - cosio2;
+        let cc4 = 2.0
+            * no
+            * coef1
+            * ao
+            * omeosq
+            * (eta * (2.0 + 0.5 * etasq) + ecco * (0.5 + 2.0 * etasq)
+                - J2 * tsi / (ao * psisq)
+                    * (-3.0 * con41 * (1.0 - 2.0 * eeta + etasq * (1.5 - 0.5 * eeta))
+                        + 0.75
+                            * x1mth2
+                            * (2.0 * etasq - eeta * (1.0 + etasq))
+                            * (2.0 * argpo).cos()));
+        let cc5 = 2.0 * coef1 * ao * omeosq * (1.0 + 2.75 * (etasq + eeta) + eeta * etasq);
+
+        // Secular rates due to the zonal harmonics.
+        let cosio4 = cosio2 * cosio2;
+        let temp1 = 1.5 * J2 * pinvsq * no;
+        let temp2 = 0.5 * temp1 * J2 * pinvsq;
+        let temp3 = -0.46875 * J4 * pinvsq * pinvsq * no;
+        let mdot = no
+            + 0.5 * temp1 * rteosq * con41
+            + 0.0625 * temp2 * rteosq * (13.0 - 78.0 * cosio2 + 137.0 * cosio4);
+        let argpdot = -0.5 * temp1 * con42
+            + 0.0625 * temp2 * (7.0 - 114.0 * cosio2 + 395.0 * cosio4)
+            + temp3 * (3.0 - 36.0 * cosio2 + 49.0 * cosio4);
+        let xhdot1 = -temp1 * cosio;
+        let nodedot = xhdot1
+            + (0.5 * temp2 * (4.0 - 19.0 * cosio2) + 2.0 * temp3 * (3.0 - 7.0 * cosio2)) * cosio;
+        let omgcof = bstar * cc3 * argpo.cos();
+        let xmcof = if ecco > 1.0e-4 {
+            -x2o3 * coef * bstar / eeta
+        } else {
+            0.0
+        };
+        let nodecf = 3.5 * omeosq * xhdot1 * cc1;
+        let t2cof = 1.5 * cc1;
+        let xlcof = -0.25 * j3oj2 * sinio * (3.0 + 5.0 * cosio)
+            / if (cosio + 1.0).abs() > 1.5e-12 {
+                1.0 + cosio
+            } else {
+                1.5e-12
+            };
+        let aycof = -0.5 * j3oj2 * sinio;
+        let delmo = (1.0 + eta * mo.cos()).powi(3);
+        let sinmao = mo.sin();
+        let x7thm1 = 7.0 * cosio2 - 1.0;
+
+        let (mut d2, mut d3, mut d4, mut t3cof, mut t4cof, mut t5cof) =
+            (0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        if !isimp {
+            let cc1sq = cc1 * cc1;
+            d2 = 4.0 * ao * tsi * cc1sq;
+            let temp = d2 * tsi * cc1 / 3.0;
+            d3 = (17.0 * ao + sfour) * temp;
+            d4 = 0.5 * temp * ao * tsi * (221.0 * ao + 31.0 * sfour) * cc1;
+            t3cof = d2 + 2.0 * cc1sq;
+            t4cof = 0.25 * (3.0 * d3 + cc1 * (12.0 * d2 + 10.0 * cc1sq));
+            t5cof = 0.2
+                * (3.0 * d4 + 12.0 * cc1 * d3 + 6.0 * d2 * d2 + 15.0 * cc1sq * (2.0 * d2 + cc1sq));
+        }
+
+        Ok(Self {
+            epoch: tle.epoch,
+            bstar,
+            ecco,
+            inclo,
+            nodeo,
+            argpo,
+            mo,
+            no,
+            isimp,
+            aycof,
+            con41,
+            cc1,
+            cc4,
+            cc5,
+            d2,
+            d3,
+            d4,
+            delmo,
+            eta,
+            argpdot,
+            omgcof,
+            sinmao,
+            t2cof,
+            t3cof,
+            t4cof,
+            t5cof,
+            x1mth2,
+            x7thm1,
+            mdot,
+            nodedot,
+            xlcof,
+            xmcof,
+            nodecf,
+        })
+    }
+
+    /// Return the epoch of the element set, as Julian date.
+    pub fn epoch(&self) -> f64 {
+        self.epoch
+    }
+
+    /// Return the position, in km, and velocity, in km/s, at a given time
+    /// since the epoch, in minutes. Return error if the satellite has
+    /// decayed or the elements have become invalid.
+    pub fn propagate(&self, tsince: f64) -> anyhow::Result<([f64; 3], [f64; 3])> {
+        let xke = xke();
+        let two_pi = 2.0 * PI;
+
+        // Secular gravity and atmospheric drag.
+        let xmdf = self.mo + self.mdot * tsince;
+        let argpdf = self.argpo + self.argpdot * tsince;
+        let nodedf = self.nodeo + self.nodedot * tsince;
+        let mut argpm = argpdf;
+        let mut mm = xmdf;
+        let t2 = tsince * tsince;
+        let mut nodem = nodedf + self.nodecf * t2;
+        let mut tempa = 1.0 - self.cc1 * tsince;
+        let mut tempe = self.bstar * self.cc4 * tsince;
+        let mut templ = self.t2cof * t2;
+        if !self.isimp {
+            let delomg = self.omgcof * tsince;
+            let delm = self.xmcof * ((1.0 + self.eta * xmdf.cos()).powi(3) - self.delmo);
+            let temp = delomg + delm;
+            mm = xmdf + temp;
+            argpm = argpdf - temp;
+            let t3 = t2 * tsince;
+            let t4 = t3 * tsince;
+            tempa = tempa - self.d2 * t2 - self.d3 * t3 - self.d4 * t4;
+            tempe += self.bstar * self.cc5 * (mm.sin() - self.sinmao);
+            templ += self.t3cof * t3 + t4 * (self.t4cof + tsince * self.t5cof);
+        }
+
+        let am = (xke / self.no).powf(2.0 / 3.0) * tempa * tempa;
+        let nm = xke / am.powf(1.5);
+        let mut em = self.ecco - tempe;
+        anyhow::ensure!(
+            (-0.001..1.0).contains(&em) && am > 0.0,
+            "invalid elements after {} minutes: eccentricity {}, semi-major axis {}",
+            tsince,
+            em,
+            am
+        );
+        if em < 1.0e-6 {
+            em = 1.0e-6
+        }
+        mm += self.no * templ;
+        let xlm = mm + argpm + nodem;
+        nodem = nodem.rem_euclid(two_pi);
+        argpm = argpm.rem_euclid(two_pi);
+        let xlm = xlm.rem_euclid(two_pi);
+        let mm = (xlm - argpm - nodem).rem_euclid(two_pi);
+        let (sinim, cosim) = self.inclo.sin_cos();
+
+        // Long-period periodics.
+        let axnl = em * argpm.cos();
+        let temp = 1.0 / (am * (1.0 - em * em));
+        let aynl = em * argpm.sin() + temp * self.aycof;
+        let xl = mm + argpm + nodem + temp * self.xlcof * axnl;
+
+        // Kepler's equation.
+        let u = (xl - nodem).rem_euclid(two_pi);
+        let mut eo1 = u;
+        let mut tem5: f64 = 9999.9;
+        let mut ktr = 1;
+        let (mut sineo1, mut coseo1) = (0.0, 0.0);
+        while tem5.abs() >= 1.0e-12 && ktr <= 10 {
+            (sineo1, coseo1) = eo1.sin_cos();
+            tem5 = 1.0 - coseo1 * axnl - sineo1 * aynl;
+            tem5 = (u - aynl * coseo1 + axnl * sineo1 - eo1) / tem5;
+            if tem5.abs() >= 0.95 {
+                tem5 = 0.95_f64.copysign(tem5);
+            }
+            eo1 += tem5;
+            ktr += 1;
+        }
+
+        // Short-period periodics.
+        let ecose = axnl * coseo1 + aynl * sineo1;
+        let esine = axnl * sineo1 - aynl * coseo1;
+        let el2 = axnl * axnl + aynl * aynl;
+        let pl = am * (1.0 - el2);
+        anyhow::ensure!(
+            pl >= 0.0,
+            "invalid semi-latus rectum after {} minutes",
+            tsince
+        );
+        let rl = am * (1.0 - ecose);
+        let rdotl = am.sqrt() * esine / rl;
+        let rvdotl = pl.sqrt() / rl;
+        let betal = (1.0 - el2).sqrt();
+        let temp = esine / (1.0 + betal);
+        let sinu = am / rl * (sineo1 - aynl - axnl * temp);
+        let cosu = am / rl * (coseo1 - axnl + aynl * temp);
+        let su = sinu.atan2(cosu);
+        let sin2u = (cosu + cosu) * sinu;
+        let cos2u = 1.0 - 2.0 * sinu * sinu;
+        let temp = 1.0 / pl;
+        let temp1 = 0.5 * J2 * temp;
+        let temp2 = temp1 * temp;
+
+        let mrt = rl * (1.0 - 1.5 * temp2 * betal * self.con41) + 0.5 * temp1 * self.x1mth2 * cos2u;
+        let su = su - 0.25 * temp2 * self.x7thm1 * sin2u;
+        let xnode = nodem + 1.5 * temp2 * cosim * sin2u;
+        let xinc = self.inclo + 1.5 * temp2 * cosim * sinim * cos2u;
+        let mvt = rdotl - nm * temp1 * self.x1mth2 * sin2u / xke;
+        let rvdot = rvdotl + nm * temp1 * (self.x1mth2 * cos2u + 1.5 * self.con41) / xke;
+        anyhow::ensure!(mrt >= 1.0, "satellite decayed after {} minutes", tsince);
+
+        // Orientation vectors.
+        let (sinsu, cossu) = su.sin_cos();
+        let (snod, cnod) = xnode.sin_cos();
+        let (sini, cosi) = xinc.sin_cos();
+        let xmx = -snod * cosi;
+        let xmy = cnod * cosi;
+        let ux = xmx * sinsu + cnod * cossu;
+        let uy = xmy * sinsu + snod * cossu;
+        let uz = sini * sinsu;
+        let vx = xmx * cossu - cnod * sinsu;
+        let vy = xmy * cossu - snod * sinsu;
+        let vz = sini * cossu;
+
+        let vkmpersec = RADIUS * xke / 60.0;
+        Ok((
+            [mrt * ux * RADIUS, mrt * uy * RADIUS, mrt * uz * RADIUS],
+            [
+                (mvt * ux + rvdot * vx) * vkmpersec,
+                (mvt * uy + rvdot * vy) * vkmpersec,
+                (mvt * uz + rvdot * vz) * vkmpersec,
+            ],
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VANGUARD: &str = "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753
+2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667";
+
+    fn assert_near(expected: &[f64; 3], actual: &[f64; 3], tolerance: f64) {
+        for i in 0..3 {
+            assert!(
+                (expected[i] - actual[i]).abs() < tolerance,
+                "expected {:?}, actual {:?}",
+                expected,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn test_tle_parse() -> anyhow::Result<()> {
+        let tles = Tle::parse_all(VANGUARD)?;
+        assert_eq!(1, tles.len());
+        let tle = &tles[0];
+        assert_eq!("", tle.name);
+        assert_eq!(5, tle.satellite_number);
+        assert_float_eq::assert_f64_near!(0.28098e-4, tle.bstar);
+        assert_float_eq::assert_f64_near!(34.2682, tle.inclination);
+        assert_float_eq::assert_f64_near!(0.1859667, tle.eccentricity);
+        assert_float_eq::assert_f64_near!(10.82419157, tle.mean_motion);
+        assert!((julian_date(2000, 6, 27, 18, 50, 19.733568) - tle.epoch).abs() < 1e-8);
+
+        // Three-line format.
+        let tles = Tle::parse_all(&format!(
+            "0 VANGUARD 1\n{}\n\nSAT\n{}\n",
+            VANGUARD, VANGUARD
+        ))?;
+        assert_eq!(2, tles.len());
+        assert_eq!("VANGUARD 1", tles[0].name);
+        assert_eq!("SAT", tles[1].name);
+
+        // Invalid sets.
+        assert!(Tle::parse_all(&VANGUARD.replace("4753", "4754")).is_err());
+        assert!(Tle::parse_all(&VANGUARD.replace("2 00005", "2 00006")).is_err());
+        assert!(Tle::parse_all(VANGUARD.lines().next().unwrap()).is_err());
+        assert!(Tle::from_file("/non/existing/file").is_err());
+
+        let tles = Tle::parse_all(&crate::tests::tle_constellation(2, 3))?;
+        assert_eq!(6, tles.len());
+        assert_eq!("SAT-6", tles[5].name);
+        assert_eq!(6, tles[5].satellite_number);
+        assert_float_eq::assert_f64_near!(180.0, tles[5].raan);
+        assert_float_eq::assert_f64_near!(240.0, tles[5].mean_anomaly);
+        assert_float_eq::assert_f64_near!(parse_utc("2025-01-01T00:00:00")?, tles[5].epoch);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tle_time() -> anyhow::Result<()> {
+        assert_float_eq::assert_f64_near!(2451545.0, julian_date(2000, 1, 1, 12, 0, 0.0));
+        assert_float_eq::assert_f64_near!(2451545.0, parse_utc("2000-01-01T12:00:00Z")?);
+        assert_float_eq::assert_f64_near!(2451545.25, parse_utc("2000-01-01 18:00:00.0")?);
+        assert!(parse_utc("2000-13-01T12:00:00").is_err());
+        assert!(parse_utc("2000-01-01").is_err());
+
+        // Reference value from Vallado, Example 3-5.
+        let jd = julian_date(1992, 8, 20, 12, 14, 0.0);
+        assert!((152.578787886_f64.to_radians() - gmst(jd)).abs() < 1e-6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tle_sgp4() -> anyhow::Result<()> {
+        let tle = Tle::parse_all(VANGUARD)?.pop().unwrap();
+        let sgp4 = Sgp4::new(&tle)?;
+
+        // Reference values from the verification output of Vallado et al.
+        let (r, v) = sgp4.propagate(0.0)?;
+        assert_near(&[7022.46529266, -1400.08296755, 0.03995155], &r, 1e-6);
+        assert_near(&[1.893841015, 6.405893759, 4.534807250], &v, 1e-8);
+        let (r, v) = sgp4.propagate(360.0)?;
+        assert_near(&[-7154.03120202, -3783.17682504, -3536.19412294], &r, 1e-6);
+        assert_near(&[4.741887409, -4.151817765, -2.093935425], &v, 1e-8);
+
+        // Deep-space orbits are not supported.
+        let mut deep_space = tle.clone();
+        deep_space.mean_motion = 2.0;
+        assert!(Sgp4::new(&deep_space).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,301 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+/// Node of a physical topology read from file.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FileNode {
+    /// Human-readable name.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Cartesian coordinates, in m.
+    #[serde(default)]
+    pub coordinates: Option<[f64; 3]>,
+    /// Characteristics of the node.
+    pub weight: crate::physical_topology::NodeWeight,
+}
+
+/// Edge of a physical topology read from file.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FileEdge {
+    /// Index of one end-point, in the order in which nodes appear.
+    pub u: u32,
+    /// Index of the other end-point.
+    pub v: u32,
+    /// Length of the link, in m. If missing, it is the Euclidean distance
+    /// between the coordinates of the end-points.
+    #[serde(default)]
+    pub distance: Option<f64>,
+    /// Attenuation, in dB/km, if this is a fiber link, otherwise it is a
+    /// free-space optical link.
+    #[serde(default)]
+    pub attenuation: Option<f64>,
+}
+
+/// Physical topology read from a JSON or GraphML file.
+///
+/// The JSON format is:
+///
+/// ```json
+/// {
+///   "nodes": [
+///     { "name": "Pisa", "coordinates": [4.5e6, 0.8e6, 4.4e6], "weight": { ... } },
+///     ...
+///   ],
+///   "edges": [ { "u": 0, "v": 1, "distance": 1000.0, "attenuation": 0.2 }, ... ]
+/// }
+/// ```
+///
+/// where `weight` has all the fields of a `NodeWeight`.
+///
+/// In GraphML the fields of `NodeWeight` are node data with the same name,
+/// possibly with default values in the key definitions, while the name and
+/// coordinates are the optional data `name` (the node id if missing), `x`,
+/// `y` and `z` (0 if missing). The length of edges is the data `distance`
+/// and the attenuation of fiber links is the data `attenuation`.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct TopologyFile {
+    pub nodes: Vec<FileNode>,
+    pub edges: Vec<FileEdge>,
+}
+
+/// Convert the text content of a GraphML data element into a JSON value.
+fn to_json_value(text: &str) -> serde_json::Value {
+    if let Ok(value) = text.parse::<u64>() {
+        serde_json::Value::from(value)
+    } else if let Ok(value) = text.parse::<f64>() {
+        serde_json::Value::from(value)
+    } else {
+        serde_json::Value::from(text)
+    }
+}
+
+impl TopologyFile {
+    /// Parse a topology in JSON format.
+    pub fn from_json(content: &str) -> anyhow::Result<Self> {
+        serde_json::from_str(content)
+            .map_err(|err| anyhow::anyhow!("invalid JSON topology: {}", err))
+    }
+
+    /// Parse a topology in GraphML format.
+    pub fn from_graphml(content: &str) -> anyhow::Result<Self> {
+        let document = roxmltree::Document::parse(content)
+            .map_err(|err| anyhow::anyhow!("invalid GraphML topology: {}", err))?;
+
+        // Collect the data keys, with their default values.
+        let mut keys = std::collections::HashMap::new();
+        let mut node_defaults = serde_json::Map::new();
+        for key in document.descendants().filter(|n| n.has_tag_name("key")) {
+            let id = key.attribute("id").unwrap_or_default();
+            let name = key.attribute("attr.name").unwrap_or(id);
+            keys.insert(id.to_string(), name.to_string());
+            if key.attribute("for") == Some("node") {
+                if let Some(default) = key
+                    .children()
+                    .find(|n| n.has_tag_name("default"))
+                    .and_then(|n| n.text())
+                {
+                    node_defaults.insert(name.to_string(), to_json_value(default.trim()));
+                }
+            }
+        }
+        let data = |element: roxmltree::Node| -> Vec<(String, String)> {
+            element
+                .children()
+                .filter(|n| n.has_tag_name("data"))
+                .map(|n| {
+                    let key = n.attribute("key").unwrap_or_default();
+                    (
+                        keys.get(key).cloned().unwrap_or(key.to_string()),
+                        n.text().unwrap_or_default().trim().to_string(),
+                    )
+                })
+                .collect()
+        };
+
+        let graph = match document.descendants().find(|n| n.has_tag_name("graph")) {
+            Some(graph) => graph,
+            None => anyhow::bail!("invalid GraphML topology: no graph element"),
+        };
+
+        let mut topology = Self::default();
+        let mut ids = std::collections::HashMap::new();
+        for node in graph.children().filter(|n| n.has_tag_name("node")) {
+            let id = match node.attribute("id") {
+                Some(id) => id,
+                None => anyhow::bail!("invalid GraphML topology: node without id"),
+            };
+            anyhow::ensure!(
+                ids.insert(id.to_string(), topology.nodes.len() as u32)
+                    .is_none(),
+                "invalid GraphML topology: duplicate node id {}",
+                id
+            );
+
+            let mut name = id.to_string();
+            let mut coordinates = [None; 3];
+            let mut weight = node_defaults.clone();
+            for (key, value) in data(node) {
+                match key.as_str() {
+                    "name" => name = value,
+                    "x" | "y" | "z" => {
+                        let value = value.parse::<f64>().map_err(|_| {
+                            anyhow::anyhow!(
+                                "invalid GraphML topology: invalid coordinate {} of node {}",
+                                key,
+                                id
+                            )
+                        })?;
+                        coordinates[(key.as_bytes()[0] - b'x') as usize] = Some(value);
+                    }
+                    _ => {
+                        weight.insert(key, to_json_value(&value));
+                    }
+                }
+            }
+            let coordinates = match coordinates {
+                [Some(x), Some(y), z] => Some([x, y, z.unwrap_or_default()]),
+                [None, None, None] => None,
+                _ => anyhow::bail!(
+                    "invalid GraphML topology: incomplete coordinates of node {}",
+                    id
+                ),
+            };
+            let weight = serde_json::from_value(serde_json::Value::Object(weight))
+                .map_err(|err| anyhow::anyhow!("invalid GraphML topology: node {}: {}", id, err))?;
+            topology.nodes.push(FileNode {
+                name: Some(name),
+                coordinates,
+                weight,
+            });
+        }
+
+        for edge in graph.children().filter(|n| n.has_tag_name("edge")) {
+            let mut end_points = vec![];
+            for attribute in ["source", "target"] {
+                let id = edge.attribute(attribute).unwrap_or_default();
+                match ids.get(id) {
+                    Some(ndx) => end_points.push(*ndx),
+                    None => anyhow::bail!("invalid GraphML topology: unknown node {}", id),
+                }
+            }
+            let mut distance = None;
+            let mut attenuation = None;
+            for (key, value) in data(edge) {
+                let field = match key.as_str() {
+                    "distance" => &mut distance,
+                    "attenuation" => &mut attenuation,
+                    _ => continue,
+                };
+                *field = Some(value.parse::<f64>().map_err(|_| {
+                    anyhow::anyhow!("invalid GraphML topology: invalid {} {}", key, value)
+                })?);
+            }
+            topology.edges.push(FileEdge {
+                u: end_points[0],
+                v: end_points[1],
+                distance,
+                attenuation,
+            });
+        }
+
+        Ok(topology)
+    }
+
+    /// Read a topology from file, in GraphML format if the extension is
+    /// `.graphml` or `.xml`, in JSON format otherwise.
+    pub fn from_file(path: &str) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| anyhow::anyhow!("cannot read topology file {}: {}", path, err))?;
+        match std::path::Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+        {
+            Some("graphml") | Some("xml") => Self::from_graphml(&content),
+            _ => Self::from_json(&content),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TopologyFile;
+    use crate::physical_topology::NodeType;
+
+    #[test]
+    fn test_topology_file_json() -> anyhow::Result<()> {
+        let topology = TopologyFile::from_json(&crate::tests::topology_json())?;
+        assert_eq!(3, topology.nodes.len());
+        assert_eq!(Some("Pisa".to_string()), topology.nodes[0].name);
+        assert_eq!(Some([0.0, 0.0, 0.0]), topology.nodes[0].coordinates);
+        assert_eq!(None, topology.nodes[2].name);
+        assert_eq!(None, topology.nodes[2].coordinates);
+        assert_eq!(NodeType::SAT, topology.nodes[2].weight.node_type);
+        assert_eq!(3, topology.edges.len());
+        assert_eq!(None, topology.edges[0].distance);
+        assert_eq!(Some(500e3), topology.edges[1].distance);
+
+        assert!(TopologyFile::from_json("{}").is_err());
+        assert!(TopologyFile::from_json(r#"{"nodes": [{"name": "A"}], "edges": []}"#).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_topology_file_graphml() -> anyhow::Result<()> {
+        let topology = TopologyFile::from_graphml(&crate::tests::topology_graphml())?;
+        assert_eq!(3, topology.nodes.len());
+        assert_eq!(Some("Pisa".to_string()), topology.nodes[0].name);
+        assert_eq!(Some("s0".to_string()), topology.nodes[2].name);
+        assert_eq!(Some([0.0, 0.0, 0.0]), topology.nodes[0].coordinates);
+        assert_eq!(Some([1000.0, 0.0, 0.0]), topology.nodes[1].coordinates);
+        assert_eq!(None, topology.nodes[2].coordinates);
+        assert_eq!(NodeType::OGS, topology.nodes[0].weight.node_type);
+        assert_eq!(NodeType::SAT, topology.nodes[2].weight.node_type);
+        assert_eq!(100, topology.nodes[0].weight.memory_qubits);
+        assert_eq!(20, topology.nodes[2].weight.memory_qubits);
+        assert_float_eq::assert_f64_near!(1000.0, topology.nodes[2].weight.capacity);
+        assert_eq!(3, topology.edges.len());
+        assert_eq!((0, 1, None), {
+            let edge = &topology.edges[0];
+            (edge.u, edge.v, edge.distance)
+        });
+        assert_eq!((2, 0, Some(500e3)), {
+            let edge = &topology.edges[1];
+            (edge.u, edge.v, edge.distance)
+        });
+
+        // Fiber links have an attenuation.
+        let topology = TopologyFile::from_graphml(&crate::tests::topology_graphml().replace(
+            r#"<edge source="n0" target="n1"/>"#,
+            r#"<edge source="n0" target="n1"><data key="attenuation">0.2</data></edge>"#,
+        ))?;
+        assert_eq!(Some(0.2), topology.edges[0].attenuation);
+        assert_eq!(None, topology.edges[1].attenuation);
+
+        assert!(TopologyFile::from_graphml("not xml").is_err());
+        assert!(TopologyFile::from_graphml("<graphml></graphml>").is_err());
+        assert!(TopologyFile::from_graphml(
+            r#"<graphml><graph><node id="a"/><edge source="a" target="b"/></graph></graphml>"#
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_topology_file_from_file() -> anyhow::Result<()> {
+        for (ext, content) in [
+            ("json", crate::tests::topology_json()),
+            ("graphml", crate::tests::topology_graphml()),
+        ] {
+            let path = std::env::temp_dir().join(format!("qnet_ll_sim_test_topology_file.{}", ext));
+            std::fs::write(&path, content)?;
+            let topology = TopologyFile::from_file(path.to_str().unwrap());
+            std::fs::remove_file(&path)?;
+            assert_eq!(3, topology?.nodes.len());
+        }
+        assert!(TopologyFile::from_file("/non/existing/file.json").is_err());
+
+        Ok(())
+    }
+}
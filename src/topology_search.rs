@@ -0,0 +1,472 @@
+// SPDX-FileCopyrightText: © 2025 Claudio Cicconetti <c.cicconetti@iit.cnr.it>
+// SPDX-License-Identifier: MIT
+
+use petgraph::visit::EdgeRef;
+use rand::{seq::SliceRandom, Rng};
+
+use crate::logical_topology::{Graph, LogicalEdge};
+
+/// Initial temperature of simulated annealing, relative to the fitness of
+/// the initial solution.
+const INITIAL_TEMPERATURE: f64 = 0.01;
+
+/// Number of solutions in the population of the genetic algorithm.
+const POPULATION_SIZE: usize = 20;
+
+/// Function to be maximized by the search of a logical topology.
+pub trait FitnessFunction {
+    /// Return the fitness of the logical topology, or None if it does not
+    /// connect all the OGS nodes of the physical topology.
+    fn fitness(
+        &self,
+        logical_graph: &Graph,
+        physical_topology: &crate::physical_topology::PhysicalTopology,
+    ) -> Option<f64>;
+}
+
+/// Fitness functions available from the configuration.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Fitness {
+    /// Opposite of the average number of logical hops between OGS nodes.
+    #[default]
+    AverageHops,
+    /// Minimum, across all pairs of OGS nodes, of the bottleneck rate of
+    /// successful EPR pairs along the widest logical path.
+    BottleneckCapacity,
+}
+
+impl FitnessFunction for Fitness {
+    fn fitness(
+        &self,
+        logical_graph: &Graph,
+        physical_topology: &crate::physical_topology::PhysicalTopology,
+    ) -> Option<f64> {
+        let ogs_nodes = physical_topology.ogs_indices();
+        if ogs_nodes
+            .iter()
+            .any(|ndx| *ndx as usize >= logical_graph.node_count())
+        {
+            return None;
+        }
+        let mut values = vec![];
+        for source in &ogs_nodes {
+            let reached = match self {
+                Fitness::AverageHops => hops_from(logical_graph, *source),
+                Fitness::BottleneckCapacity => {
+                    widest_from(logical_graph, physical_topology, *source)
+                }
+            };
+            for target in &ogs_nodes {
+                if source != target {
+                    values.push(reached[*target as usize]?);
+                }
+            }
+        }
+        match self {
+            Fitness::AverageHops => Some(-values.iter().sum::<f64>() / values.len().max(1) as f64),
+            Fitness::BottleneckCapacity => values.into_iter().reduce(f64::min).or(Some(0.0)),
+        }
+    }
+}
+
+/// Return the number of logical hops from `source` to every node, if reachable.
+fn hops_from(logical_graph: &Graph, source: u32) -> Vec<Option<f64>> {
+    let mut hops = vec![None; logical_graph.node_count()];
+    hops[source as usize] = Some(0.0);
+    let mut queue = std::collections::VecDeque::from([source]);
+    while let Some(u) = queue.pop_front() {
+        let next = hops[u as usize].unwrap() + 1.0;
+        for e in logical_graph.edges(u.into()) {
+            let v = e.target().index();
+            if hops[v].is_none() {
+                hops[v] = Some(next);
+                queue.push_back(v as u32);
+            }
+        }
+    }
+    hops
+}
+
+/// Return the maximum, across all the logical paths from `source` to every
+/// node, of the minimum rate of successful EPR pairs along the path, if
+/// reachable.
+fn widest_from(
+    logical_graph: &Graph,
+    physical_topology: &crate::physical_topology::PhysicalTopology,
+    source: u32,
+) -> Vec<Option<f64>> {
+    let mut width = vec![None; logical_graph.node_count()];
+    let mut visited = vec![false; logical_graph.node_count()];
+    width[source as usize] = Some(f64::INFINITY);
+    while let Some(u) = (0..width.len())
+        .filter(|u| !visited[*u] && width[*u].is_some())
+        .max_by(|a, b| width[*a].unwrap().total_cmp(&width[*b].unwrap()))
+    {
+        visited[u] = true;
+        for e in logical_graph.edges((u as u32).into()) {
+            let rate = e.weight().capacity
+                * physical_topology
+                    .success_probability(
+                        e.weight().tx,
+                        e.source().index() as u32,
+                        e.target().index() as u32,
+                    )
+                    .unwrap_or_default();
+            let candidate = width[u].unwrap().min(rate);
+            let v = e.target().index();
+            if !visited[v] && width[v].is_none_or(|w| w < candidate) {
+                width[v] = Some(candidate);
+            }
+        }
+    }
+    width
+}
+
+/// Parameters of the metaheuristic search of a logical topology.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SearchParams {
+    /// Function to be maximized.
+    pub fitness: Fitness,
+    /// Number of iterations of simulated annealing or generations of the
+    /// genetic algorithm.
+    pub iterations: u32,
+    /// Seed of the pseudo-random number generator, if different from
+    /// that of the simulation.
+    pub search_seed: Option<u64>,
+}
+
+impl Default for SearchParams {
+    fn default() -> Self {
+        Self {
+            fitness: Fitness::default(),
+            iterations: 1000,
+            search_seed: None,
+        }
+    }
+}
+
+/// Fitness of the current and best solutions at every iteration.
+pub type Convergence = Vec<(f64, f64)>;
+
+/// Search space of the logical topologies: each solution is a selection of
+/// the possible logical edges, which are added in order as long as the
+/// resources of the physical nodes allow.
+struct SearchSpace<'a> {
+    physical_topology: &'a crate::physical_topology::PhysicalTopology,
+    fitness: &'a dyn FitnessFunction,
+    possible_logical_edges: Vec<LogicalEdge>,
+}
+
+impl<'a> SearchSpace<'a> {
+    fn new(
+        physical_topology: &'a crate::physical_topology::PhysicalTopology,
+        fitness: &'a dyn FitnessFunction,
+    ) -> Self {
+        let mut possible_logical_edges =
+            crate::logical_topology::find_possible_logical_edges(physical_topology);
+        possible_logical_edges.sort_by_key(|e| (e.tx, e.master, e.slave));
+        Self {
+            physical_topology,
+            fitness,
+            possible_logical_edges,
+        }
+    }
+
+    /// Return the selection of the logical edges in the given topology.
+    fn encode(&self, logical_graph: &Graph) -> Vec<bool> {
+        self.possible_logical_edges
+            .iter()
+            .map(|e| {
+                logical_graph
+                    .find_edge(e.master.into(), e.slave.into())
+                    .is_some_and(|edge| logical_graph[edge].tx == e.tx)
+            })
+            .collect()
+    }
+
+    /// Return the logical topology corresponding to a selection.
+    fn decode(&self, selected: &[bool]) -> Graph {
+        let physical_graph = self.physical_topology.graph();
+        let mut residual = physical_graph.node_weights().cloned().collect::<Vec<_>>();
+        let mut logical_graph = Graph::new();
+        for _ in 0..physical_graph.node_count() {
+            logical_graph.add_node(crate::logical_topology::NodeWeight {});
+        }
+        for (logical_edge, _) in self
+            .possible_logical_edges
+            .iter()
+            .zip(selected)
+            .filter(|(_, selected)| **selected)
+        {
+            crate::logical_topology::add_logical_edge_if_possible(
+                &mut logical_graph,
+                &mut residual,
+                logical_edge,
+            );
+        }
+        crate::logical_topology::assign_capacity_and_memory(
+            &mut logical_graph,
+            physical_graph,
+            &residual,
+        );
+        logical_graph
+    }
+
+    /// Return the fitness of a selection, minus infinity if infeasible.
+    fn evaluate(&self, selected: &[bool]) -> f64 {
+        self.fitness
+            .fitness(&self.decode(selected), self.physical_topology)
+            .unwrap_or(f64::NEG_INFINITY)
+    }
+
+    /// Return feasible initial solutions: that of the shortest-path policy
+    /// and `num` random ones, obtained by adding the possible logical edges
+    /// in random order until all the OGS nodes are reachable.
+    fn initial(&self, num: usize, rng: &mut rand::rngs::StdRng) -> Vec<Vec<bool>> {
+        let mut ret = vec![];
+        if let Ok(logical_graph) =
+            crate::logical_topology::physical_to_logical_shortest_path(self.physical_topology)
+        {
+            ret.push(self.encode(&logical_graph));
+        }
+        let physical_graph = self.physical_topology.graph();
+        let ogs_nodes = self.physical_topology.ogs_indices();
+        for _ in 0..num {
+            let mut residual = physical_graph.node_weights().cloned().collect::<Vec<_>>();
+            let mut logical_graph = Graph::new();
+            for _ in 0..physical_graph.node_count() {
+                logical_graph.add_node(crate::logical_topology::NodeWeight {});
+            }
+            let mut order = (0..self.possible_logical_edges.len()).collect::<Vec<_>>();
+            order.shuffle(rng);
+            let mut selected = vec![false; order.len()];
+            for ndx in order {
+                if crate::logical_topology::reachable(&logical_graph, &ogs_nodes) {
+                    break;
+                }
+                selected[ndx] = crate::logical_topology::add_logical_edge_if_possible(
+                    &mut logical_graph,
+                    &mut residual,
+                    &self.possible_logical_edges[ndx],
+                );
+            }
+            ret.push(selected);
+        }
+        ret.retain(|selected| self.evaluate(selected).is_finite());
+        ret
+    }
+
+    /// Flip the selection of one random logical edge.
+    fn flip_one(&self, selected: &mut [bool], rng: &mut rand::rngs::StdRng) {
+        if !selected.is_empty() {
+            let ndx = rng.gen_range(0..selected.len());
+            selected[ndx] = !selected[ndx];
+        }
+    }
+}
+
+/// Search a logical topology with simulated annealing, starting from
+/// the best feasible initial solution and flipping the selection of one
+/// logical edge at every iteration. The temperature decreases linearly
+/// to zero.
+pub fn simulated_annealing(
+    physical_topology: &crate::physical_topology::PhysicalTopology,
+    fitness: &dyn FitnessFunction,
+    iterations: u32,
+    rng: &mut rand::rngs::StdRng,
+) -> anyhow::Result<(Graph, Convergence)> {
+    let space = SearchSpace::new(physical_topology, fitness);
+    let mut current = match space
+        .initial(1, rng)
+        .into_iter()
+        .map(|selected| (space.evaluate(&selected), selected))
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+    {
+        Some((_, selected)) => selected,
+        None => anyhow::bail!("could not find a logical topology for the given physical topology"),
+    };
+    let mut current_fitness = space.evaluate(&current);
+    let mut best = current.clone();
+    let mut best_fitness = current_fitness;
+    let initial_temperature = INITIAL_TEMPERATURE * current_fitness.abs().max(f64::EPSILON);
+
+    let mut convergence = vec![];
+    for iteration in 0..iterations {
+        let temperature = initial_temperature * (1.0 - iteration as f64 / iterations as f64);
+        let mut candidate = current.clone();
+        space.flip_one(&mut candidate, rng);
+        let candidate_fitness = space.evaluate(&candidate);
+        if candidate_fitness >= current_fitness
+            || (candidate_fitness.is_finite()
+                && rng.gen::<f64>() < ((candidate_fitness - current_fitness) / temperature).exp())
+        {
+            current = candidate;
+            current_fitness = candidate_fitness;
+            if current_fitness > best_fitness {
+                best = current.clone();
+                best_fitness = current_fitness;
+            }
+        }
+        convergence.push((current_fitness, best_fitness));
+    }
+
+    Ok((space.decode(&best), convergence))
+}
+
+/// Search a logical topology with a genetic algorithm, with elitism,
+/// binary tournament selection, uniform crossover and mutation of every
+/// logical edge with probability equal to the inverse of their number.
+///
+/// The initial population consists of feasible solutions and their
+/// mutations. The convergence reports the average fitness of the feasible
+/// solutions in the population as the current one.
+pub fn genetic(
+    physical_topology: &crate::physical_topology::PhysicalTopology,
+    fitness: &dyn FitnessFunction,
+    generations: u32,
+    rng: &mut rand::rngs::StdRng,
+) -> anyhow::Result<(Graph, Convergence)> {
+    let space = SearchSpace::new(physical_topology, fitness);
+    let mut population = space.initial(POPULATION_SIZE / 2, rng);
+    anyhow::ensure!(
+        !population.is_empty(),
+        "could not find a logical topology for the given physical topology"
+    );
+    while population.len() < POPULATION_SIZE {
+        let mut mutant = population[rng.gen_range(0..population.len())].clone();
+        space.flip_one(&mut mutant, rng);
+        population.push(mutant);
+    }
+    let mut population = population
+        .into_iter()
+        .map(|selected| (space.evaluate(&selected), selected))
+        .collect::<Vec<_>>();
+
+    let mutation_probability = 1.0 / space.possible_logical_edges.len().max(1) as f64;
+    let mut convergence = vec![];
+    for _generation in 0..generations {
+        population.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let mut next = vec![population[0].clone()];
+        while next.len() < POPULATION_SIZE {
+            let mut tournament = || {
+                let a = rng.gen_range(0..population.len());
+                let b = rng.gen_range(0..population.len());
+                std::cmp::min(a, b)
+            };
+            let (mother, father) = (&population[tournament()].1, &population[tournament()].1);
+            let child = mother
+                .iter()
+                .zip(father)
+                .map(|(m, f)| {
+                    let gene = if rng.gen_bool(0.5) { *m } else { *f };
+                    gene ^ rng.gen_bool(mutation_probability)
+                })
+                .collect::<Vec<_>>();
+            next.push((space.evaluate(&child), child));
+        }
+        population = next;
+
+        let feasible = population
+            .iter()
+            .map(|(fitness, _)| *fitness)
+            .filter(|fitness| fitness.is_finite())
+            .collect::<Vec<_>>();
+        let best_fitness = feasible.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        convergence.push((
+            feasible.iter().sum::<f64>() / feasible.len() as f64,
+            best_fitness,
+        ));
+    }
+
+    let (_, best) = population
+        .into_iter()
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .expect("empty population");
+    Ok((space.decode(&best), convergence))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::{genetic, simulated_annealing, Fitness, FitnessFunction};
+    use crate::tests::physical_topology_2_2;
+
+    #[test]
+    fn test_topology_search_fitness() -> anyhow::Result<()> {
+        let (physical_topology, logical_topology) = crate::tests::logical_topology_2_2();
+        let average_hops = Fitness::AverageHops
+            .fitness(logical_topology.graph(), &physical_topology)
+            .unwrap();
+        assert!(average_hops <= -1.0);
+        let bottleneck = Fitness::BottleneckCapacity
+            .fitness(logical_topology.graph(), &physical_topology)
+            .unwrap();
+        assert!(bottleneck > 0.0 && bottleneck <= 1.0);
+
+        // A logical topology without edges does not connect the OGS nodes.
+        let mut empty = logical_topology.graph().clone();
+        empty.clear_edges();
+        for fitness in [Fitness::AverageHops, Fitness::BottleneckCapacity] {
+            assert!(fitness.fitness(&empty, &physical_topology).is_none());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_topology_search_simulated_annealing() -> anyhow::Result<()> {
+        let physical_topology = physical_topology_2_2();
+        for fitness in [Fitness::AverageHops, Fitness::BottleneckCapacity] {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+            let (logical_graph, convergence) =
+                simulated_annealing(&physical_topology, &fitness, 200, &mut rng)?;
+            crate::logical_topology::is_valid(&logical_graph, &physical_topology)?;
+            assert_eq!(200, convergence.len());
+
+            // The best fitness never decreases and is that of the result.
+            for window in convergence.windows(2) {
+                assert!(window[1].1 >= window[0].1);
+            }
+            for (current, best) in &convergence {
+                assert!(current <= best);
+            }
+            assert_float_eq::assert_f64_near!(
+                convergence.last().unwrap().1,
+                fitness.fitness(&logical_graph, &physical_topology).unwrap()
+            );
+
+            // Same seed, same result.
+            let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+            let (_, other) = simulated_annealing(&physical_topology, &fitness, 200, &mut rng)?;
+            assert_eq!(convergence, other);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_topology_search_genetic() -> anyhow::Result<()> {
+        let physical_topology = physical_topology_2_2();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let (logical_graph, convergence) =
+            genetic(&physical_topology, &Fitness::AverageHops, 20, &mut rng)?;
+        crate::logical_topology::is_valid(&logical_graph, &physical_topology)?;
+        assert_eq!(20, convergence.len());
+        for window in convergence.windows(2) {
+            assert!(window[1].1 >= window[0].1);
+        }
+        for (average, best) in &convergence {
+            assert!(average <= best);
+        }
+        assert_float_eq::assert_f64_near!(
+            convergence.last().unwrap().1,
+            Fitness::AverageHops
+                .fitness(&logical_graph, &physical_topology)
+                .unwrap()
+        );
+
+        Ok(())
+    }
+}
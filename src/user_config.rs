@@ -6,6 +6,7 @@ fn default_sat_weight() -> crate::physical_topology::NodeWeight {
         node_type: crate::physical_topology::NodeType::SAT,
         memory_qubits: 20,
         decay_rate: 1.0,
+        dephasing_rate: 0.0,
         swapping_success_prob: 0.95,
         detectors: 10,
         transmitters: 10,
@@ -18,6 +19,7 @@ fn default_ogs_weight() -> crate::physical_topology::NodeWeight {
         node_type: crate::physical_topology::NodeType::OGS,
         memory_qubits: 100,
         decay_rate: 1.0,
+        dephasing_rate: 0.0,
         swapping_success_prob: 0.0,
         detectors: 10,
         transmitters: 0,
@@ -28,8 +30,14 @@ fn default_ogs_weight() -> crate::physical_topology::NodeWeight {
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ConfGridStatic {
     pub grid_params: crate::physical_topology::GridParams,
+    /// Cells of the grid where to place the OGSs.
+    #[serde(default)]
+    pub ogs_placement: crate::physical_topology::OgsPlacement,
     pub sat_weight: crate::physical_topology::NodeWeight,
     pub ogs_weight: crate::physical_topology::NodeWeight,
+    /// Characteristics of specific nodes, replacing `sat_weight` or `ogs_weight`.
+    #[serde(default)]
+    pub overrides: Vec<crate::physical_topology::NodeWeightOverride>,
     pub fidelities: crate::physical_topology::StaticFidelities,
 }
 
@@ -37,8 +45,10 @@ impl Default for ConfGridStatic {
     fn default() -> Self {
         Self {
             grid_params: Default::default(),
+            ogs_placement: Default::default(),
             sat_weight: default_sat_weight(),
             ogs_weight: default_ogs_weight(),
+            overrides: vec![],
             fidelities: Default::default(),
         }
     }
@@ -49,6 +59,9 @@ pub struct ConfChainStatic {
     pub chain_params: crate::physical_topology::ChainParams,
     pub sat_weight: crate::physical_topology::NodeWeight,
     pub ogs_weight: crate::physical_topology::NodeWeight,
+    /// Characteristics of specific nodes, replacing `sat_weight` or `ogs_weight`.
+    #[serde(default)]
+    pub overrides: Vec<crate::physical_topology::NodeWeightOverride>,
     pub fidelities: crate::physical_topology::StaticFidelities,
 }
 
@@ -58,6 +71,151 @@ impl Default for ConfChainStatic {
             chain_params: Default::default(),
             sat_weight: default_sat_weight(),
             ogs_weight: default_ogs_weight(),
+            overrides: vec![],
+            fidelities: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ConfRingStatic {
+    pub ring_params: crate::physical_topology::RingParams,
+    pub sat_weight: crate::physical_topology::NodeWeight,
+    pub ogs_weight: crate::physical_topology::NodeWeight,
+    pub fidelities: crate::physical_topology::StaticFidelities,
+}
+
+impl Default for ConfRingStatic {
+    fn default() -> Self {
+        Self {
+            ring_params: Default::default(),
+            sat_weight: default_sat_weight(),
+            ogs_weight: default_ogs_weight(),
+            fidelities: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ConfStarStatic {
+    pub star_params: crate::physical_topology::StarParams,
+    pub sat_weight: crate::physical_topology::NodeWeight,
+    pub ogs_weight: crate::physical_topology::NodeWeight,
+    pub fidelities: crate::physical_topology::StaticFidelities,
+}
+
+impl Default for ConfStarStatic {
+    fn default() -> Self {
+        Self {
+            star_params: Default::default(),
+            sat_weight: default_sat_weight(),
+            ogs_weight: default_ogs_weight(),
+            fidelities: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ConfRandomGeometricStatic {
+    pub random_geometric_params: crate::physical_topology::RandomGeometricParams,
+    pub sat_weight: crate::physical_topology::NodeWeight,
+    pub ogs_weight: crate::physical_topology::NodeWeight,
+    pub fidelities: crate::physical_topology::StaticFidelities,
+}
+
+impl Default for ConfRandomGeometricStatic {
+    fn default() -> Self {
+        Self {
+            random_geometric_params: Default::default(),
+            sat_weight: default_sat_weight(),
+            ogs_weight: default_ogs_weight(),
+            fidelities: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ConfWaxmanStatic {
+    pub waxman_params: crate::physical_topology::WaxmanParams,
+    pub sat_weight: crate::physical_topology::NodeWeight,
+    pub ogs_weight: crate::physical_topology::NodeWeight,
+    pub fidelities: crate::physical_topology::StaticFidelities,
+}
+
+impl Default for ConfWaxmanStatic {
+    fn default() -> Self {
+        Self {
+            waxman_params: Default::default(),
+            sat_weight: default_sat_weight(),
+            ogs_weight: default_ogs_weight(),
+            fidelities: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ConfWalkerStatic {
+    pub walker_params: crate::constellation::WalkerParams,
+    pub ground_stations: Vec<crate::constellation::GroundStation>,
+    pub sat_weight: crate::physical_topology::NodeWeight,
+    pub ogs_weight: crate::physical_topology::NodeWeight,
+    pub fidelities: crate::physical_topology::StaticFidelities,
+}
+
+impl Default for ConfWalkerStatic {
+    fn default() -> Self {
+        Self {
+            walker_params: Default::default(),
+            ground_stations: vec![
+                crate::constellation::GroundStation {
+                    latitude: 43.72,
+                    longitude: 10.40,
+                },
+                crate::constellation::GroundStation {
+                    latitude: 41.90,
+                    longitude: 12.50,
+                },
+            ],
+            sat_weight: default_sat_weight(),
+            ogs_weight: default_ogs_weight(),
+            fidelities: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ConfTleStatic {
+    pub tle_params: crate::constellation::TleParams,
+    pub ground_stations: Vec<crate::constellation::GroundStation>,
+    pub sat_weight: crate::physical_topology::NodeWeight,
+    pub ogs_weight: crate::physical_topology::NodeWeight,
+    pub fidelities: crate::physical_topology::StaticFidelities,
+}
+
+impl Default for ConfTleStatic {
+    fn default() -> Self {
+        let walker = ConfWalkerStatic::default();
+        Self {
+            tle_params: Default::default(),
+            ground_stations: walker.ground_stations,
+            sat_weight: walker.sat_weight,
+            ogs_weight: walker.ogs_weight,
+            fidelities: walker.fidelities,
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ConfFileStatic {
+    /// Path of the file with the nodes and edges, in JSON or GraphML format.
+    pub topology_file: String,
+    pub fidelities: crate::physical_topology::StaticFidelities,
+}
+
+impl Default for ConfFileStatic {
+    fn default() -> Self {
+        Self {
+            topology_file: String::from("topology.json"),
             fidelities: Default::default(),
         }
     }
@@ -67,27 +225,108 @@ impl Default for ConfChainStatic {
 pub enum PhysicalTopology {
     ConfGridStatic(ConfGridStatic),
     ConfChainStatic(ConfChainStatic),
+    ConfRingStatic(ConfRingStatic),
+    ConfStarStatic(ConfStarStatic),
+    ConfRandomGeometricStatic(ConfRandomGeometricStatic),
+    ConfWaxmanStatic(ConfWaxmanStatic),
+    ConfWalkerStatic(ConfWalkerStatic),
+    ConfTleStatic(ConfTleStatic),
+    ConfFileStatic(ConfFileStatic),
 }
 
 impl PhysicalTopology {
+    /// Build the physical topology, using the given seed to initialize the
+    /// pseudo-random number generator of randomised generators.
     pub fn to_physical_topology(
         &self,
+        seed: u64,
     ) -> anyhow::Result<crate::physical_topology::PhysicalTopology> {
         match self {
             PhysicalTopology::ConfGridStatic(conf) => {
-                crate::physical_topology::PhysicalTopology::from_grid_static(
-                    conf.grid_params.clone(),
+                let mut physical_topology =
+                    crate::physical_topology::PhysicalTopology::from_grid_placement_static(
+                        conf.grid_params.clone(),
+                        conf.ogs_placement.clone(),
+                        conf.sat_weight.clone(),
+                        conf.ogs_weight.clone(),
+                        conf.fidelities.clone(),
+                        seed,
+                    )?;
+                physical_topology.override_node_weights(&conf.overrides)?;
+                Ok(physical_topology)
+            }
+            PhysicalTopology::ConfChainStatic(conf) => {
+                let mut physical_topology =
+                    crate::physical_topology::PhysicalTopology::from_chain_static(
+                        conf.chain_params.clone(),
+                        conf.sat_weight.clone(),
+                        conf.ogs_weight.clone(),
+                        conf.fidelities.clone(),
+                    )?;
+                physical_topology.override_node_weights(&conf.overrides)?;
+                Ok(physical_topology)
+            }
+            PhysicalTopology::ConfRingStatic(conf) => {
+                crate::physical_topology::PhysicalTopology::from_ring_static(
+                    conf.ring_params.clone(),
                     conf.sat_weight.clone(),
                     conf.ogs_weight.clone(),
                     conf.fidelities.clone(),
                 )
             }
-            PhysicalTopology::ConfChainStatic(conf) => {
-                crate::physical_topology::PhysicalTopology::from_chain_static(
-                    conf.chain_params.clone(),
+            PhysicalTopology::ConfStarStatic(conf) => {
+                crate::physical_topology::PhysicalTopology::from_star_static(
+                    conf.star_params.clone(),
+                    conf.sat_weight.clone(),
+                    conf.ogs_weight.clone(),
+                    conf.fidelities.clone(),
+                )
+            }
+            PhysicalTopology::ConfRandomGeometricStatic(conf) => {
+                crate::physical_topology::PhysicalTopology::from_random_geometric_static(
+                    conf.random_geometric_params.clone(),
+                    conf.sat_weight.clone(),
+                    conf.ogs_weight.clone(),
+                    conf.fidelities.clone(),
+                    seed,
+                )
+            }
+            PhysicalTopology::ConfWaxmanStatic(conf) => {
+                crate::physical_topology::PhysicalTopology::from_waxman_static(
+                    conf.waxman_params.clone(),
                     conf.sat_weight.clone(),
                     conf.ogs_weight.clone(),
                     conf.fidelities.clone(),
+                    seed,
+                )
+            }
+            PhysicalTopology::ConfWalkerStatic(conf) => {
+                crate::physical_topology::PhysicalTopology::from_constellation(
+                    crate::constellation::Constellation::new(
+                        conf.walker_params.clone(),
+                        conf.ground_stations.clone(),
+                    )?,
+                    conf.sat_weight.clone(),
+                    conf.ogs_weight.clone(),
+                    conf.fidelities.clone(),
+                )
+            }
+            PhysicalTopology::ConfTleStatic(conf) => {
+                crate::physical_topology::PhysicalTopology::from_constellation(
+                    crate::constellation::Constellation::from_tle(
+                        conf.tle_params.clone(),
+                        &crate::tle::Tle::from_file(&conf.tle_params.tle_file)?,
+                        conf.ground_stations.clone(),
+                    )?,
+                    conf.sat_weight.clone(),
+                    conf.ogs_weight.clone(),
+                    conf.fidelities.clone(),
+                )
+            }
+            PhysicalTopology::ConfFileStatic(conf) => {
+                crate::physical_topology::PhysicalTopology::from_topology_file(
+                    crate::topology_file::TopologyFile::from_file(&conf.topology_file)?,
+                    conf.fidelities.clone(),
                 )
             }
         }
@@ -98,38 +337,198 @@ impl crate::utils::CsvFriend for PhysicalTopology {
     fn header(&self) -> String {
         match &self {
             PhysicalTopology::ConfGridStatic(conf) => format!(
-                "{},{},{},{}",
+                "{},ogs_placement,num_ogs,{},{},num_overrides,{}",
                 crate::utils::struct_to_csv_header(&conf.grid_params).unwrap(),
                 crate::utils::struct_to_csv_header(&conf.sat_weight).unwrap(),
                 crate::utils::struct_to_csv_header(&conf.ogs_weight).unwrap(),
                 crate::utils::struct_to_csv_header(&conf.fidelities).unwrap()
             ),
             PhysicalTopology::ConfChainStatic(conf) => format!(
-                "{},{},{},{}",
+                "{},{},{},num_overrides,{}",
                 crate::utils::struct_to_csv_header(&conf.chain_params).unwrap(),
                 crate::utils::struct_to_csv_header(&conf.sat_weight).unwrap(),
                 crate::utils::struct_to_csv_header(&conf.ogs_weight).unwrap(),
                 crate::utils::struct_to_csv_header(&conf.fidelities).unwrap()
             ),
+            PhysicalTopology::ConfRingStatic(conf) => format!(
+                "{},{},{},{}",
+                crate::utils::struct_to_csv_header(&conf.ring_params).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.sat_weight).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.ogs_weight).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.fidelities).unwrap()
+            ),
+            PhysicalTopology::ConfStarStatic(conf) => format!(
+                "{},{},{},{}",
+                crate::utils::struct_to_csv_header(&conf.star_params).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.sat_weight).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.ogs_weight).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.fidelities).unwrap()
+            ),
+            PhysicalTopology::ConfRandomGeometricStatic(conf) => format!(
+                "{},{},{},{}",
+                crate::utils::struct_to_csv_header(&conf.random_geometric_params).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.sat_weight).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.ogs_weight).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.fidelities).unwrap()
+            ),
+            PhysicalTopology::ConfWaxmanStatic(conf) => format!(
+                "{},{},{},{}",
+                crate::utils::struct_to_csv_header(&conf.waxman_params).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.sat_weight).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.ogs_weight).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.fidelities).unwrap()
+            ),
+            PhysicalTopology::ConfWalkerStatic(conf) => format!(
+                "{},num_ogs,{},{},{}",
+                crate::utils::struct_to_csv_header(&conf.walker_params).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.sat_weight).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.ogs_weight).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.fidelities).unwrap()
+            ),
+            PhysicalTopology::ConfTleStatic(conf) => format!(
+                "{},num_ogs,{},{},{}",
+                crate::utils::struct_to_csv_header(&conf.tle_params).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.sat_weight).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.ogs_weight).unwrap(),
+                crate::utils::struct_to_csv_header(&conf.fidelities).unwrap()
+            ),
+            PhysicalTopology::ConfFileStatic(conf) => format!(
+                "topology_file,{}",
+                crate::utils::struct_to_csv_header(&conf.fidelities).unwrap()
+            ),
         }
     }
 
     fn to_csv(&self) -> String {
         match &self {
             PhysicalTopology::ConfGridStatic(conf) => format!(
-                "{},{},{},{}",
+                "{},{},{},{},{},{},{}",
                 crate::utils::struct_to_csv(&conf.grid_params).unwrap(),
+                conf.ogs_placement.name(),
+                conf.ogs_placement.num_ogs(&conf.grid_params),
                 crate::utils::struct_to_csv(&conf.sat_weight).unwrap(),
                 crate::utils::struct_to_csv(&conf.ogs_weight).unwrap(),
+                conf.overrides.len(),
                 crate::utils::struct_to_csv(&conf.fidelities).unwrap()
             ),
             PhysicalTopology::ConfChainStatic(conf) => format!(
-                "{},{},{},{}",
+                "{},{},{},{},{}",
                 crate::utils::struct_to_csv(&conf.chain_params).unwrap(),
                 crate::utils::struct_to_csv(&conf.sat_weight).unwrap(),
                 crate::utils::struct_to_csv(&conf.ogs_weight).unwrap(),
+                conf.overrides.len(),
+                crate::utils::struct_to_csv(&conf.fidelities).unwrap()
+            ),
+            PhysicalTopology::ConfRingStatic(conf) => format!(
+                "{},{},{},{}",
+                crate::utils::struct_to_csv(&conf.ring_params).unwrap(),
+                crate::utils::struct_to_csv(&conf.sat_weight).unwrap(),
+                crate::utils::struct_to_csv(&conf.ogs_weight).unwrap(),
+                crate::utils::struct_to_csv(&conf.fidelities).unwrap()
+            ),
+            PhysicalTopology::ConfStarStatic(conf) => format!(
+                "{},{},{},{}",
+                crate::utils::struct_to_csv(&conf.star_params).unwrap(),
+                crate::utils::struct_to_csv(&conf.sat_weight).unwrap(),
+                crate::utils::struct_to_csv(&conf.ogs_weight).unwrap(),
+                crate::utils::struct_to_csv(&conf.fidelities).unwrap()
+            ),
+            PhysicalTopology::ConfRandomGeometricStatic(conf) => format!(
+                "{},{},{},{}",
+                crate::utils::struct_to_csv(&conf.random_geometric_params).unwrap(),
+                crate::utils::struct_to_csv(&conf.sat_weight).unwrap(),
+                crate::utils::struct_to_csv(&conf.ogs_weight).unwrap(),
+                crate::utils::struct_to_csv(&conf.fidelities).unwrap()
+            ),
+            PhysicalTopology::ConfWaxmanStatic(conf) => format!(
+                "{},{},{},{}",
+                crate::utils::struct_to_csv(&conf.waxman_params).unwrap(),
+                crate::utils::struct_to_csv(&conf.sat_weight).unwrap(),
+                crate::utils::struct_to_csv(&conf.ogs_weight).unwrap(),
+                crate::utils::struct_to_csv(&conf.fidelities).unwrap()
+            ),
+            PhysicalTopology::ConfWalkerStatic(conf) => format!(
+                "{},{},{},{},{}",
+                crate::utils::struct_to_csv(&conf.walker_params).unwrap(),
+                conf.ground_stations.len(),
+                crate::utils::struct_to_csv(&conf.sat_weight).unwrap(),
+                crate::utils::struct_to_csv(&conf.ogs_weight).unwrap(),
+                crate::utils::struct_to_csv(&conf.fidelities).unwrap()
+            ),
+            PhysicalTopology::ConfTleStatic(conf) => format!(
+                "{},{},{},{},{}",
+                crate::utils::struct_to_csv(&conf.tle_params).unwrap(),
+                conf.ground_stations.len(),
+                crate::utils::struct_to_csv(&conf.sat_weight).unwrap(),
+                crate::utils::struct_to_csv(&conf.ogs_weight).unwrap(),
                 crate::utils::struct_to_csv(&conf.fidelities).unwrap()
             ),
+            PhysicalTopology::ConfFileStatic(conf) => format!(
+                "{},{}",
+                conf.topology_file,
+                crate::utils::struct_to_csv(&conf.fidelities).unwrap()
+            ),
+        }
+    }
+}
+
+/// Model used to derive the success probability and initial fidelity of
+/// the EPR pairs generated over the physical links.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub enum LinkModel {
+    /// Fixed fidelities of the physical topology, always successful.
+    #[default]
+    Static,
+    /// Fidelity decaying exponentially with the distance, always successful.
+    Exponential(crate::fidelity_model::ExponentialFidelity),
+    /// Free-space optical link budget.
+    LinkBudget(crate::link_budget::LinkBudgetParams),
+}
+
+impl LinkModel {
+    /// Apply the link model to the given physical topology.
+    pub fn apply(
+        &self,
+        physical_topology: &mut crate::physical_topology::PhysicalTopology,
+    ) -> anyhow::Result<()> {
+        match self {
+            LinkModel::Static => Ok(()),
+            LinkModel::Exponential(params) => {
+                params.valid()?;
+                physical_topology.set_fidelity_model(Box::new(params.clone()));
+                Ok(())
+            }
+            LinkModel::LinkBudget(params) => physical_topology.set_link_budget(params.clone()),
+        }
+    }
+}
+
+impl crate::utils::CsvFriend for LinkModel {
+    fn header(&self) -> String {
+        match &self {
+            LinkModel::Static => String::from("link_model"),
+            LinkModel::Exponential(params) => format!(
+                "link_model,{}",
+                crate::utils::struct_to_csv_header(params).unwrap()
+            ),
+            LinkModel::LinkBudget(params) => format!(
+                "link_model,{}",
+                crate::utils::struct_to_csv_header(params).unwrap()
+            ),
+        }
+    }
+
+    fn to_csv(&self) -> String {
+        match &self {
+            LinkModel::Static => String::from("Static"),
+            LinkModel::Exponential(params) => format!(
+                "Exponential,{}",
+                crate::utils::struct_to_csv(params).unwrap()
+            ),
+            LinkModel::LinkBudget(params) => format!(
+                "LinkBudget,{}",
+                crate::utils::struct_to_csv(params).unwrap()
+            ),
         }
     }
 }
@@ -137,6 +536,12 @@ impl crate::utils::CsvFriend for PhysicalTopology {
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct LogicalTopology {
     pub physical_to_logical_policy: crate::logical_topology::PhysicalToLogicalPolicy,
+    /// Parameters of the metaheuristic search policies.
+    #[serde(flatten)]
+    pub search: crate::topology_search::SearchParams,
+    /// Allocation of resources to the logical edges.
+    #[serde(default)]
+    pub allocation: crate::logical_topology::Allocation,
 }
 
 impl Default for LogicalTopology {
@@ -144,17 +549,28 @@ impl Default for LogicalTopology {
         Self {
             physical_to_logical_policy:
                 crate::logical_topology::PhysicalToLogicalPolicy::RandomGreedy,
+            search: crate::topology_search::SearchParams::default(),
+            allocation: crate::logical_topology::Allocation::default(),
         }
     }
 }
 
 impl crate::utils::CsvFriend for LogicalTopology {
     fn header(&self) -> String {
-        crate::utils::struct_to_csv_header(self).unwrap()
+        format!(
+            "physical_to_logical_policy,{},allocation,num_demands",
+            crate::utils::struct_to_csv_header(&self.search).unwrap()
+        )
     }
 
     fn to_csv(&self) -> String {
-        crate::utils::struct_to_csv(self).unwrap()
+        format!(
+            "{:?},{},{},{}",
+            self.physical_to_logical_policy,
+            crate::utils::struct_to_csv(&self.search).unwrap(),
+            self.allocation.name(),
+            self.allocation.num_demands()
+        )
     }
 }
 
@@ -168,8 +584,30 @@ pub struct UserConfig {
     pub series_ignore: std::collections::HashSet<String>,
     /// The physical topology configuration.
     pub physical_topology: PhysicalTopology,
+    /// The model of the physical links, static by default.
+    #[serde(default)]
+    pub link_model: LinkModel,
     /// The logical topology configuration.
     pub logical_topology: LogicalTopology,
+    /// The client applications configuration.
+    #[serde(default)]
+    pub clients: crate::client::ClientParams,
+    /// The link-level purification configuration, disabled by default.
+    #[serde(default)]
+    pub purification: crate::purification::PurificationParams,
+    /// The quantum memory configuration, without cutoff by default.
+    #[serde(default)]
+    pub memory: crate::nic::MemoryParams,
+    /// The classical signalling configuration, disabled by default.
+    #[serde(default)]
+    pub signalling: crate::signalling::SignallingParams,
+    /// The mobility configuration, only used with constellations.
+    #[serde(default)]
+    pub mobility: crate::physical_topology::MobilityParams,
+    /// The representation of the state of the EPR pairs, Bell-diagonal by
+    /// default.
+    #[serde(default)]
+    pub backend: crate::epr_register::Backend,
 }
 
 impl Default for UserConfig {
@@ -179,7 +617,14 @@ impl Default for UserConfig {
             warmup_period: 1.0,
             series_ignore: std::collections::HashSet::new(),
             physical_topology: PhysicalTopology::ConfGridStatic(ConfGridStatic::default()),
+            link_model: LinkModel::default(),
             logical_topology: LogicalTopology::default(),
+            clients: crate::client::ClientParams::default(),
+            purification: crate::purification::PurificationParams::default(),
+            memory: crate::nic::MemoryParams::default(),
+            signalling: crate::signalling::SignallingParams::default(),
+            mobility: crate::physical_topology::MobilityParams::default(),
+            backend: crate::epr_register::Backend::default(),
         }
     }
 }
@@ -187,18 +632,182 @@ impl Default for UserConfig {
 impl crate::utils::CsvFriend for UserConfig {
     fn header(&self) -> String {
         format!(
-            "duration,warmup_period,{},{}",
+            "duration,warmup_period,{},{},{},{},{},{},{},{},backend",
             self.physical_topology.header(),
-            self.logical_topology.header()
+            self.link_model.header(),
+            self.logical_topology.header(),
+            crate::utils::struct_to_csv_header(&self.clients).unwrap(),
+            crate::utils::struct_to_csv_header(&self.purification).unwrap(),
+            crate::utils::struct_to_csv_header(&self.memory).unwrap(),
+            crate::utils::struct_to_csv_header(&self.signalling).unwrap(),
+            crate::utils::struct_to_csv_header(&self.mobility).unwrap()
         )
     }
     fn to_csv(&self) -> String {
         format!(
-            "{},{},{},{}",
+            "{},{},{},{},{},{},{},{},{},{},{:?}",
             self.duration,
             self.warmup_period,
             self.physical_topology.to_csv(),
-            self.logical_topology.to_csv()
+            self.link_model.to_csv(),
+            self.logical_topology.to_csv(),
+            crate::utils::struct_to_csv(&self.clients).unwrap(),
+            crate::utils::struct_to_csv(&self.purification).unwrap(),
+            crate::utils::struct_to_csv(&self.memory).unwrap(),
+            crate::utils::struct_to_csv(&self.signalling).unwrap(),
+            crate::utils::struct_to_csv(&self.mobility).unwrap(),
+            self.backend
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfChainStatic, ConfGridStatic, PhysicalTopology, UserConfig};
+    use crate::utils::CsvFriend;
+
+    #[test]
+    fn test_user_config_csv() {
+        let overrides = vec![crate::physical_topology::NodeWeightOverride {
+            selector: crate::physical_topology::NodeSelector::Orbit(0),
+            weight: crate::physical_topology::NodeWeight::default_sat(),
+        }];
+        for physical_topology in [
+            PhysicalTopology::ConfGridStatic(ConfGridStatic::default()),
+            PhysicalTopology::ConfGridStatic(ConfGridStatic {
+                overrides: overrides.clone(),
+                ..Default::default()
+            }),
+            PhysicalTopology::ConfGridStatic(ConfGridStatic {
+                ogs_placement: crate::physical_topology::OgsPlacement::Cells(vec![(0, 0), (1, 2)]),
+                ..Default::default()
+            }),
+            PhysicalTopology::ConfChainStatic(ConfChainStatic {
+                overrides,
+                ..Default::default()
+            }),
+        ] {
+            let user_config = UserConfig {
+                physical_topology,
+                ..Default::default()
+            };
+            assert_eq!(
+                user_config.header().split(',').count(),
+                user_config.to_csv().split(',').count()
+            );
+        }
+        let user_config = UserConfig {
+            logical_topology: super::LogicalTopology {
+                allocation: crate::logical_topology::Allocation::Demand(vec![
+                    crate::logical_topology::Demand {
+                        source: 4,
+                        target: 5,
+                        rate: 1.0,
+                    },
+                ]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            user_config.header().split(',').count(),
+            user_config.to_csv().split(',').count()
+        );
+        assert!(user_config.to_csv().contains(",Demand,1,"));
+
+        // The overrides, OGS placement, search parameters and allocation are
+        // optional in the configuration and do not change the header.
+        let default = UserConfig::default();
+        let mut value = serde_json::to_value(&default).unwrap();
+        for field in ["overrides", "ogs_placement"] {
+            value["physical_topology"]["ConfGridStatic"]
+                .as_object_mut()
+                .unwrap()
+                .remove(field);
+        }
+        for field in ["fitness", "iterations", "search_seed", "allocation"] {
+            value["logical_topology"]
+                .as_object_mut()
+                .unwrap()
+                .remove(field)
+                .unwrap();
+        }
+        let parsed: UserConfig = serde_json::from_value(value).unwrap();
+        assert_eq!(default.header(), parsed.header());
+        assert_eq!(default.to_csv(), parsed.to_csv());
+    }
+
+    #[test]
+    fn test_user_config_baseline() {
+        // Configuration without any of the optional fields.
+        let user_config: UserConfig = serde_json::from_str(
+            r#"
+            {
+              "duration": 10.0,
+              "warmup_period": 1.0,
+              "series_ignore": [],
+              "physical_topology": {
+                "ConfGridStatic": {
+                  "grid_params": {
+                    "orbit_to_orbit_distance": 3000.0,
+                    "ground_to_orbit_distance": 1000.0,
+                    "num_orbits": 3,
+                    "orbit_length": 4
+                  },
+                  "sat_weight": {
+                    "node_type": "SAT",
+                    "memory_qubits": 20,
+                    "decay_rate": 1.0,
+                    "swapping_success_prob": 0.95,
+                    "detectors": 10,
+                    "transmitters": 10,
+                    "capacity": 1000.0
+                  },
+                  "ogs_weight": {
+                    "node_type": "OGS",
+                    "memory_qubits": 100,
+                    "decay_rate": 1.0,
+                    "swapping_success_prob": 0.0,
+                    "detectors": 10,
+                    "transmitters": 0,
+                    "capacity": 0.0
+                  },
+                  "fidelities": {
+                    "f_o": 1.0,
+                    "f_g": 1.0,
+                    "f_oo": 1.0,
+                    "f_og": 1.0,
+                    "f_gg": 1.0
+                  }
+                }
+              },
+              "logical_topology": {
+                "physical_to_logical_policy": "RandomGreedy"
+              }
+            }
+            "#,
         )
+        .unwrap();
+        assert!(matches!(user_config.link_model, super::LinkModel::Static));
+        assert!(matches!(
+            user_config.purification.protocol,
+            crate::purification::Protocol::Disabled
+        ));
+        assert_eq!(crate::nic::Cutoff::Disabled, user_config.memory.cutoff);
+        assert!(!user_config.signalling.enabled);
+        assert!(matches!(
+            user_config.backend,
+            crate::epr_register::Backend::BellDiagonal
+        ));
+        assert_eq!(
+            crate::utils::struct_to_csv(crate::client::ClientParams::default()).unwrap(),
+            crate::utils::struct_to_csv(&user_config.clients).unwrap()
+        );
+        assert_eq!(UserConfig::default().header(), user_config.header());
+        let physical_topology = match &user_config.physical_topology {
+            PhysicalTopology::ConfGridStatic(conf) => conf,
+            _ => panic!("unexpected physical topology"),
+        };
+        assert_eq!(0.0, physical_topology.sat_weight.dephasing_rate);
     }
 }